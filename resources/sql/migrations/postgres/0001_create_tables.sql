@@ -62,5 +62,6 @@ CREATE TABLE IF NOT EXISTS tips (
     game_id INT NOT NULL REFERENCES games(game_id),
     predicted_home_score INT NOT NULL,
     predicted_away_score INT NOT NULL,
-    tip_date TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    tip_date TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, game_id)
 );
\ No newline at end of file
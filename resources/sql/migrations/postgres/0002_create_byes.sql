@@ -0,0 +1,31 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+
+-- Table to store the teams that have the bye in a round
+CREATE TABLE IF NOT EXISTS byes (
+    bye_id SERIAL PRIMARY KEY,
+    round_id INT NOT NULL REFERENCES rounds(round_id),
+    team_id INT NOT NULL REFERENCES teams(team_id),
+    UNIQUE (round_id, team_id)
+);
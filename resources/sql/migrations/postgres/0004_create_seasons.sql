@@ -0,0 +1,37 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+
+-- Table to store the seasons. Every round belongs to a season, while teams and tippers carry on
+-- from one season to the next
+CREATE TABLE IF NOT EXISTS seasons (
+    season_id SERIAL PRIMARY KEY,
+    name VARCHAR(50) NOT NULL UNIQUE
+);
+
+-- The rounds played so far make up the first season, named for the year they started
+INSERT INTO seasons (name)
+    SELECT COALESCE(TO_CHAR(MIN(start_date), 'YYYY'), TO_CHAR(CURRENT_DATE, 'YYYY')) FROM rounds;
+
+ALTER TABLE rounds ADD COLUMN season_id INT REFERENCES seasons(season_id);
+UPDATE rounds SET season_id = (SELECT MIN(season_id) FROM seasons);
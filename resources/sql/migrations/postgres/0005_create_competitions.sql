@@ -0,0 +1,42 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+
+-- Table to store the competitions. Each has its own tippers and leaderboard, while the rounds,
+-- games and results are shared by them all
+CREATE TABLE IF NOT EXISTS competitions (
+    competition_id SERIAL PRIMARY KEY,
+    name VARCHAR(50) NOT NULL UNIQUE
+);
+
+-- Which tippers are in which competitions
+CREATE TABLE IF NOT EXISTS competition_tippers (
+    competition_id INTEGER NOT NULL REFERENCES competitions(competition_id) ON DELETE CASCADE,
+    tipper_id INTEGER NOT NULL REFERENCES tippers(tipper_id) ON DELETE CASCADE,
+    PRIMARY KEY (competition_id, tipper_id)
+);
+
+-- The tippers so far make up the first competition
+INSERT INTO competitions (name) VALUES ('Main');
+INSERT INTO competition_tippers (competition_id, tipper_id)
+    SELECT competition_id, tipper_id FROM competitions, tippers;
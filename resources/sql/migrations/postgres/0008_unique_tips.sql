@@ -0,0 +1,28 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+
+-- Databases created before migrations were introduced have no unique constraint on tips, which
+-- saving a tip relies on. Keep only the latest tip of a tipper for each game, then add it.
+DELETE FROM tips WHERE tip_id NOT IN (SELECT MAX(tip_id) FROM tips GROUP BY user_id, game_id);
+CREATE UNIQUE INDEX IF NOT EXISTS tips_user_game ON tips(user_id, game_id);
@@ -0,0 +1,56 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+-- Which teams and tippers take part in each season. One left out of a season is archived in it,
+-- while the seasons before keep it
+CREATE TABLE IF NOT EXISTS season_teams (
+    season_id INTEGER NOT NULL REFERENCES seasons(season_id) ON DELETE CASCADE,
+    team_id INTEGER NOT NULL REFERENCES teams(team_id) ON DELETE CASCADE,
+    PRIMARY KEY (season_id, team_id)
+);
+
+CREATE TABLE IF NOT EXISTS season_tippers (
+    season_id INTEGER NOT NULL REFERENCES seasons(season_id) ON DELETE CASCADE,
+    tipper_id INTEGER NOT NULL REFERENCES tippers(tipper_id) ON DELETE CASCADE,
+    PRIMARY KEY (season_id, tipper_id)
+);
+
+-- The active teams and tippers take part in every season so far, as do any that played or
+-- tipped in one
+INSERT INTO season_teams (season_id, team_id)
+    SELECT season_id, team_id FROM seasons, teams WHERE teams.active
+    UNION
+    SELECT rounds.season_id, games.home_team_id FROM rounds JOIN games ON games.round_id = rounds.round_id
+    UNION
+    SELECT rounds.season_id, games.away_team_id FROM rounds JOIN games ON games.round_id = rounds.round_id
+    UNION
+    SELECT rounds.season_id, byes.team_id FROM rounds JOIN byes ON byes.round_id = rounds.round_id;
+INSERT INTO season_tippers (season_id, tipper_id)
+    SELECT season_id, tipper_id FROM seasons, tippers WHERE tippers.active
+    UNION
+    SELECT rounds.season_id, tips.user_id FROM rounds
+        JOIN games ON games.round_id = rounds.round_id
+        JOIN tips ON tips.game_id = games.game_id;
+
+ALTER TABLE teams DROP COLUMN active;
+ALTER TABLE tippers DROP COLUMN active;
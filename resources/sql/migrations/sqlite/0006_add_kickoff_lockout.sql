@@ -0,0 +1,33 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+
+-- When each game starts, so tips can be closed as it kicks off
+ALTER TABLE games ADD COLUMN kickoff TIMESTAMP;
+
+-- Tips entered after their game closed, when the competition allows them
+ALTER TABLE tips ADD COLUMN late BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- When tips close in each competition (none, game or round) and whether late tips are rejected or flagged
+ALTER TABLE competitions ADD COLUMN lockout VARCHAR(10) NOT NULL DEFAULT 'game';
+ALTER TABLE competitions ADD COLUMN late_tips VARCHAR(10) NOT NULL DEFAULT 'reject';
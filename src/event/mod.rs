@@ -24,6 +24,7 @@
 
 #![forbid(unsafe_code)]
 
+use std::future::Future;
 use std::sync::{LazyLock, RwLock};
 
 use async_channel::{Receiver, Sender, TrySendError};
@@ -37,7 +38,13 @@ pub enum Event {
     TippersChanged,
     RoundsChanged {round_id: i32},
     GamesChanged,
+    TipsChanged {round_id: i32},
     PlaydaysChanged,
+    ConnectionChanged,
+    SeasonChanged,
+    CompetitionChanged,
+    LoadingStarted,
+    LoadingFinished,
 }
 
 static MANAGER: LazyLock<EventManager> = LazyLock::new(|| EventManager {
@@ -48,6 +55,15 @@ pub fn manager() -> &'static EventManager {
     &MANAGER
 }
 
+/// Await a database load, telling listeners when it starts and finishes so the window can show
+/// that something is on its way.
+pub async fn while_loading<F: Future>(load: F) -> F::Output {
+    manager().notify_listeners(Event::LoadingStarted);
+    let result = load.await;
+    manager().notify_listeners(Event::LoadingFinished);
+    result
+}
+
 pub struct EventManager {
     listeners: RwLock<Vec<Sender<Event>>>,
 }
@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::model::error::ModelError;
+use crate::util::db::{with_pool, DbPool};
+use log::error;
+
+/// A team that does not play in a round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bye {
+    pub round_id: i32,
+    pub team_id: i32,
+}
+
+impl Bye {
+    pub fn new(round_id: i32, team_id: i32) -> Bye {
+        Bye { round_id, team_id }
+    }
+}
+
+/// Replace the byes for a round with the given teams.
+pub async fn save_for_round(pool: &DbPool, round_id: i32, team_ids: &[i32]) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM byes WHERE round_id=$1")
+            .bind(round_id)
+            .execute(&mut *tx)
+            .await?;
+        let mut count = 0;
+        for team_id in team_ids {
+            let result = sqlx::query("INSERT INTO byes (round_id, team_id) VALUES ($1, $2)")
+                .bind(round_id)
+                .bind(team_id)
+                .execute(&mut *tx)
+                .await?;
+            count += result.rows_affected();
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(count)
+    }.await);
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error saving byes: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+fn build_bye((round_id, team_id): (i32, i32)) -> Bye {
+    Bye::new(round_id, team_id)
+}
+
+pub async fn get_for_round(pool: &DbPool, round_id: i32) -> Result<Vec<Bye>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, i32)>("SELECT round_id, team_id FROM byes WHERE round_id=$1 ORDER BY team_id")
+            .bind(round_id)
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_bye).collect()),
+        Err(e) => {
+            error!("Error getting byes: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+pub async fn get_all(pool: &DbPool) -> Result<Vec<Bye>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, i32)>("SELECT round_id, team_id FROM byes ORDER BY round_id, team_id")
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_bye).collect()),
+        Err(e) => {
+            error!("Error getting byes: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// The byes of every round in a season.
+pub async fn get_for_season(pool: &DbPool, season_id: i32) -> Result<Vec<Bye>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, i32)>(
+            "SELECT round_id, team_id FROM byes WHERE round_id IN (SELECT round_id FROM rounds WHERE season_id=$1) \
+             ORDER BY round_id, team_id",
+        )
+            .bind(season_id)
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_bye).collect()),
+        Err(e) => {
+            error!("Error getting byes for season: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
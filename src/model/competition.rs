@@ -0,0 +1,170 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::event;
+use crate::event::Event;
+use crate::model::error::ModelError;
+use crate::util::db::{with_pool, DbPool};
+use crate::util::lockout::{LateTips, Lockout};
+use log::error;
+use std::cell::Cell;
+
+/// A tipping competition. Each has its own tippers and leaderboard, while the rounds, games and
+/// results are shared by every competition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Competition {
+    pub id: i32,
+    pub name: String,
+    pub lockout: Lockout,
+    pub late_tips: LateTips,
+}
+
+impl Competition {
+    pub fn new(id: i32, name: String) -> Competition {
+        Competition { id, name, lockout: Lockout::Game, late_tips: LateTips::Reject }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<i32>> = const { Cell::new(None) };
+}
+
+/// The competition being shown, once one has been chosen.
+pub fn current() -> Option<i32> {
+    CURRENT.get()
+}
+
+/// Show another competition, telling the views to reload their tippers.
+pub fn set_current(competition_id: i32) {
+    if CURRENT.replace(Some(competition_id)) != Some(competition_id) {
+        event::manager().notify_listeners(Event::CompetitionChanged);
+    }
+}
+
+pub async fn get_all(pool: &DbPool) -> Result<Vec<Competition>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, String, String, String)>(
+            "SELECT competition_id, name, lockout, late_tips FROM competitions ORDER BY competition_id",
+        )
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_competition).collect()),
+        Err(e) => {
+            error!("Error getting competitions: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+fn build_competition(row: (i32, String, String, String)) -> Competition {
+    let (id, name, lockout, late_tips) = row;
+    let mut competition = Competition::new(id, name);
+    competition.lockout = lockout.parse().unwrap_or(Lockout::Game);
+    competition.late_tips = late_tips.parse().unwrap_or(LateTips::Reject);
+    competition
+}
+
+pub async fn insert(pool: &DbPool, name: String) -> Result<Competition, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_scalar::<_, i32>("INSERT INTO competitions (name) VALUES ($1) RETURNING competition_id")
+            .bind(name.clone())
+            .fetch_one(pool)
+            .await
+    });
+
+    match result {
+        Ok(id) => Ok(Competition::new(id, name)),
+        Err(e) => {
+            error!("Error inserting competition: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Change when tips close in a competition and what happens to tips entered after that.
+pub async fn update_rules(pool: &DbPool, competition_id: i32, lockout: Lockout, late_tips: LateTips) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query("UPDATE competitions SET lockout=$1, late_tips=$2 WHERE competition_id=$3")
+            .bind(lockout.to_string())
+            .bind(late_tips.to_string())
+            .bind(competition_id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error updating competition: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// The ids of the tippers in a competition.
+pub async fn get_members(pool: &DbPool, competition_id: i32) -> Result<Vec<i32>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_scalar::<_, i32>("SELECT tipper_id FROM competition_tippers WHERE competition_id=$1")
+            .bind(competition_id)
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(ids) => Ok(ids),
+        Err(e) => {
+            error!("Error getting competition members: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Put a tipper in a competition or take them out of it. Returns the number of memberships changed,
+/// which is zero when the tipper was already in or out.
+pub async fn set_member(pool: &DbPool, competition_id: i32, tipper_id: i32, member: bool) -> Result<u64, ModelError> {
+    let sql = if member {
+        "INSERT INTO competition_tippers (competition_id, tipper_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+    } else {
+        "DELETE FROM competition_tippers WHERE competition_id=$1 AND tipper_id=$2"
+    };
+    let result = with_pool!(pool, |pool| {
+        sqlx::query(sql)
+            .bind(competition_id)
+            .bind(tipper_id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error changing competition member: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
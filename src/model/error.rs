@@ -0,0 +1,104 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use sqlx::error::ErrorKind;
+use std::fmt;
+
+/// Why a model function failed, so callers can tell the user something more useful than the
+/// database's own message. Each variant keeps the underlying detail for the log.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelError {
+    /// The row asked for does not exist.
+    NotFound(String),
+    /// A value that must be unique, such as a tipper's email, is already used.
+    Duplicate(String),
+    /// The row is still used by others, such as a team that plays in games. Only deleting a row
+    /// gives this; saving a row that refers to a missing one is [`ModelError::NotFound`].
+    StillReferenced(String),
+    /// The values given cannot be saved.
+    Validation(String),
+    /// The database could not be reached.
+    Connectivity(String),
+    /// Anything else the database complained about.
+    Database(String),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::NotFound(detail) => write!(f, "It could not be found; it may have been deleted ({})", detail),
+            ModelError::Duplicate(detail) => write!(f, "It is the same as one that already exists ({})", detail),
+            ModelError::StillReferenced(detail) => write!(f, "It is still in use ({})", detail),
+            ModelError::Validation(detail) => write!(f, "{}", detail),
+            ModelError::Connectivity(detail) => write!(f, "Unable to reach the database: {}", detail),
+            ModelError::Database(detail) => write!(f, "Database error: {}", detail),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+impl ModelError {
+    /// The error from deleting a row, where a broken foreign key means other rows still use it.
+    pub fn from_delete(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::Database(ref db_error) if matches!(db_error.kind(), ErrorKind::ForeignKeyViolation) =>
+                ModelError::StillReferenced(db_error.message().to_string()),
+            _ => ModelError::from(e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ModelError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => ModelError::NotFound(e.to_string()),
+            sqlx::Error::Database(ref db_error) => match db_error.kind() {
+                ErrorKind::UniqueViolation => ModelError::Duplicate(db_error.message().to_string()),
+                ErrorKind::ForeignKeyViolation => ModelError::NotFound(db_error.message().to_string()),
+                ErrorKind::NotNullViolation | ErrorKind::CheckViolation => ModelError::Validation(db_error.message().to_string()),
+                _ => ModelError::Database(db_error.message().to_string()),
+            },
+            sqlx::Error::Configuration(_)
+            | sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::Protocol(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => ModelError::Connectivity(e.to_string()),
+            _ => ModelError::Database(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_sqlx() {
+        assert!(matches!(ModelError::from(sqlx::Error::RowNotFound), ModelError::NotFound(_)));
+        assert!(matches!(ModelError::from(sqlx::Error::PoolTimedOut), ModelError::Connectivity(_)));
+        assert!(matches!(ModelError::from(sqlx::Error::ColumnNotFound("name".to_string())), ModelError::Database(_)));
+    }
+}
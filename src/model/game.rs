@@ -22,13 +22,15 @@
  *
  */
 use crate::model::team::Teams;
-use crate::util::db;
+use crate::event::while_loading;
+use crate::model::repository::{connected_repository, Dependents, GameRecord};
+use crate::model::error::ModelError;
+use crate::util::db::{with_pool, DbPool};
 use adw::glib::clone;
 use adw::subclass::prelude::ObjectSubclassIsExt;
 use adw::{gio, glib};
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use log::error;
-use sqlx::{PgPool, Row};
 
 // To use the Game in a Gio::ListModel it needs to ba a glib::Object, so we do all this fancy subclassing stuff
 // Public part of the Model type.
@@ -72,6 +74,24 @@ impl Game {
     pub fn away_team_score(&self) -> Option<i32> {
         self.imp().away_team_score.borrow().clone()
     }
+    /// When the game starts, if the time is known.
+    pub fn kickoff(&self) -> Option<DateTime<Utc>> {
+        self.imp().kickoff.get()
+    }
+    pub fn set_kickoff(&self, kickoff: Option<DateTime<Utc>>) {
+        self.imp().kickoff.set(kickoff);
+    }
+    /// The kickoff typed in for the game could not be read, so the game should not be saved.
+    pub fn kickoff_error(&self) -> bool {
+        self.imp().kickoff_error.get()
+    }
+    pub fn set_kickoff_error(&self, error: bool) {
+        self.imp().kickoff_error.set(error);
+    }
+    /// A game has a result once both scores are entered.
+    pub fn has_result(&self) -> bool {
+        self.home_team_score().is_some() && self.away_team_score().is_some()
+    }
 
 }
 
@@ -86,20 +106,14 @@ impl Games {
         obj
     }
 
-    pub fn for_round(round_id: i32) -> Games {
-        let pool = db::manager().pool();
+    /// The games for a round. The list is empty while the database is unavailable.
+    pub async fn for_round(round_id: i32) -> Games {
         let obj : Games /* Type */ = glib::Object::new();
-        let games = async_std::task::block_on(async move {
-            get_for_round(pool, round_id).await
-        });
-        match games {
-            Ok(game_list) => {
-                let mut temp: Vec<Game> = vec![];
-                for t in game_list.into_iter() {
-                    temp.push(t);
-                }
-                obj.imp().set_games(&mut temp);
-            }
+        let Some(repository) = connected_repository() else {
+            return obj;
+        };
+        match while_loading(repository.get_games_for_round(round_id)).await {
+            Ok(mut game_list) => obj.imp().set_games(&mut game_list),
             Err(err) => {
                 error!("Error getting games for round{}: {}", round_id, err);
             }
@@ -114,9 +128,9 @@ mod imp {
     use adw::glib::Object;
     use adw::prelude::StaticType;
     use adw::subclass::prelude::{ListModelImpl, ObjectImpl, ObjectImplExt, ObjectSubclass};
-    use chrono::NaiveDate;
+    use chrono::{DateTime, NaiveDate, Utc};
     use gtk::glib;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::sync::{Arc, RwLock};
 
     #[derive(Debug)]
@@ -129,6 +143,8 @@ mod imp {
         pub game_date: RefCell<NaiveDate>,
         pub home_team_score: RefCell<Option<i32>>,
         pub away_team_score: RefCell<Option<i32>>,
+        pub kickoff: Cell<Option<DateTime<Utc>>>,
+        pub kickoff_error: Cell<bool>,
     }
     impl Game {
         pub(crate) fn set_id(&self, id: i32) {
@@ -229,184 +245,290 @@ mod imp {
     }
 }
 
-pub async fn insert(
-    pool: &PgPool,
-    round_id: i32,
-    home_team_id: i32,
-    away_team_id: i32,
-    game_date: NaiveDate,
-    home_team_score: Option<i32>,
-    away_team_score: Option<i32>,
-) -> Result<i32, String> {
-    let result = sqlx::query(
-        "INSERT INTO games (round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score) \
-         VALUES ($1, $2, $3, $4, $5, $6) RETURNING game_id",
-    )
-        .bind(round_id)
-        .bind(home_team_id)
-        .bind(away_team_id)
-        .bind(game_date)
-        .bind(home_team_score)
-        .bind(away_team_score)
-        .fetch_one(pool)
-        .await;
+pub async fn insert(pool: &DbPool, game: GameRecord) -> Result<i32, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_scalar::<_, i32>(
+            "INSERT INTO games (round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score, kickoff) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING game_id",
+        )
+            .bind(game.round_id)
+            .bind(game.home_team_id)
+            .bind(game.away_team_id)
+            .bind(game.game_date)
+            .bind(game.home_team_score)
+            .bind(game.away_team_score)
+            .bind(game.kickoff)
+            .fetch_one(pool)
+            .await
+    });
 
     match result {
-        Ok(row) => {
-            let game_id = row.get::<i32, _>(0);
-            Ok(game_id)
-        }
+        Ok(game_id) => Ok(game_id),
         Err(e) => {
             error!("Error inserting game: {}", e);
-            Err(format!("Error inserting game: {}", e))
+            Err(ModelError::from(e))
         }
     }
 }
 
 
-pub async fn update(
-    pool: &PgPool,
-    game_id: i32,
-    round_id: i32,
-    home_team_id: i32,
-    away_team_id: i32,
-    game_date: NaiveDate,
-    home_team_score: Option<i32>,
-    away_team_score: Option<i32>,
-) -> Result<u64, String> {
-    let result = sqlx::query(
-        "UPDATE games SET round_id=$1, home_team_id=$2, away_team_id=$3, game_date=$4, \
-         home_team_score=$5, away_team_score=$6 WHERE game_id=$7",
-    )
-        .bind(round_id)
-        .bind(home_team_id)
-        .bind(away_team_id)
-        .bind(game_date)
-        .bind(home_team_score)
-        .bind(away_team_score)
-        .bind(game_id)
-        .execute(pool)
-        .await;
+pub async fn update(pool: &DbPool, game_id: i32, game: GameRecord) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query(
+            "UPDATE games SET round_id=$1, home_team_id=$2, away_team_id=$3, game_date=$4, \
+             home_team_score=$5, away_team_score=$6, kickoff=$7 WHERE game_id=$8",
+        )
+            .bind(game.round_id)
+            .bind(game.home_team_id)
+            .bind(game.away_team_id)
+            .bind(game.game_date)
+            .bind(game.home_team_score)
+            .bind(game.away_team_score)
+            .bind(game.kickoff)
+            .bind(game_id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
 
     match result {
-        Ok(result) => Ok(result.rows_affected()),
+        Ok(count) => Ok(count),
         Err(e) => {
             error!("Error updating game: {}", e);
-            Err(format!("Error updating game: {}", e))
+            Err(ModelError::from(e))
         }
     }
 }
 
-pub async fn delete(pool: &PgPool, game_id: i32) -> Result<u64, String> {
-    let result = sqlx::query("DELETE FROM games WHERE game_id=$1")
-        .bind(game_id)
-        .execute(pool)
-        .await;
+/// Set the scores of several games, given as (game id, home score, away score), all together or
+/// not at all.
+pub async fn save_results(pool: &DbPool, results: &[(i32, i32, i32)]) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        for (game_id, home_team_score, away_team_score) in results {
+            let updated = sqlx::query("UPDATE games SET home_team_score=$1, away_team_score=$2 WHERE game_id=$3")
+                .bind(home_team_score)
+                .bind(away_team_score)
+                .bind(game_id)
+                .execute(&mut *tx)
+                .await?;
+            if updated.rows_affected() == 0 {
+                return Err(sqlx::Error::RowNotFound);
+            }
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(results.len() as u64)
+    }.await);
 
     match result {
-        Ok(result) => Ok(result.rows_affected()),
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error saving results: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Delete a game along with any tips made for it.
+pub async fn delete(pool: &DbPool, game_id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM tips WHERE game_id=$1")
+            .bind(game_id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM games WHERE game_id=$1")
+            .bind(game_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(result.rows_affected())
+    }.await);
+
+    match result {
+        Ok(count) => Ok(count),
         Err(e) => {
             error!("Error deleting game: {}", e);
-            Err(format!("Error deleting game: {}", e))
+            Err(ModelError::from_delete(e))
         }
     }
 }
 
-pub async fn get(pool: &PgPool, game_id: i32) -> Result<Option<Game>, String> {
-    let result = sqlx::query(
-        "SELECT game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score \
-         FROM games WHERE game_id=$1",
-    )
-        .bind(game_id)
-        .fetch_optional(pool)
-        .await;
+type GameRow = (i32, i32, i32, i32, NaiveDate, Option<i32>, Option<i32>, Option<DateTime<Utc>>);
+
+fn build_game(row: GameRow) -> Game {
+    let (game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score, kickoff) = row;
+    let game = Game::new(game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score);
+    game.set_kickoff(kickoff);
+    game
+}
+
+/// Count the tips made on a game.
+pub async fn dependents(pool: &DbPool, game_id: i32) -> Result<Dependents, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM tips WHERE game_id=$1")
+            .bind(game_id)
+            .fetch_one(pool)
+            .await
+    });
+
+    match result {
+        Ok(tips) => Ok(Dependents { tips, ..Dependents::default() }),
+        Err(e) => {
+            error!("Error counting the tips on game: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+pub async fn get(pool: &DbPool, game_id: i32) -> Result<Option<Game>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, GameRow>(
+            "SELECT game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score, kickoff \
+             FROM games WHERE game_id=$1",
+        )
+            .bind(game_id)
+            .fetch_optional(pool)
+            .await
+    });
 
     match result {
         Ok(row) => match row {
-            Some(row) => {
-                let game = Game::new(
-                    row.get::<i32, _>(0),
-                    row.get::<i32, _>(1),
-                    row.get::<i32, _>(2),
-                    row.get::<i32, _>(3),
-                    row.get::<NaiveDate, _>(4),
-                    row.get::<Option<i32>, _>(5),
-                    row.get::<Option<i32>, _>(6),
-                );
-                Ok(Some(game))
-            }
+            Some(row) => Ok(Some(build_game(row))),
             None => Ok(None),
         },
         Err(e) => {
             error!("Error getting game: {}", e);
-            Err(format!("Error getting game: {}", e))
+            Err(ModelError::from(e))
         }
     }
 }
 
-pub async fn get_for_round(pool: &PgPool, round_id: i32) -> Result<Vec<Game>, String> {
-    let result = sqlx::query(
-        "SELECT game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score \
-         FROM games WHERE round_id = $1 ORDER BY game_date",
-    )
-        .bind(round_id)
-        .fetch_all(pool)
-        .await;
+pub async fn get_for_round(pool: &DbPool, round_id: i32) -> Result<Vec<Game>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, GameRow>(
+            "SELECT game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score, kickoff \
+             FROM games WHERE round_id = $1 ORDER BY game_date, kickoff IS NULL, kickoff",
+        )
+            .bind(round_id)
+            .fetch_all(pool)
+            .await
+    });
 
     match result {
         Ok(rows) => {
-            let games = rows
-                .into_iter()
-                .map(|row| {
-                    Game::new(
-                        row.get::<i32, _>(0),
-                        row.get::<i32, _>(1),
-                        row.get::<i32, _>(2),
-                        row.get::<i32, _>(3),
-                        row.get::<NaiveDate, _>(4),
-                        row.get::<Option<i32>, _>(5),
-                        row.get::<Option<i32>, _>(6),
-                    )
-                })
-                .collect();
+            let games = rows.into_iter().map(build_game).collect();
             Ok(games)
         }
         Err(e) => {
             error!("Error getting all games: {}", e);
-            Err(format!("Error getting all games: {}", e))
+            Err(ModelError::from(e))
         }
     }
 }
 
-pub async fn get_all(pool: &PgPool) -> Result<Vec<Game>, String> {
-    let result = sqlx::query(
-        "SELECT game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score \
-         FROM games ORDER BY game_date",
-    )
-        .fetch_all(pool)
-        .await;
+pub async fn get_all(pool: &DbPool) -> Result<Vec<Game>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, GameRow>(
+            "SELECT game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score, kickoff \
+             FROM games ORDER BY game_date",
+        )
+            .fetch_all(pool)
+            .await
+    });
 
     match result {
         Ok(rows) => {
-            let games = rows
-                .into_iter()
-                .map(|row| {
-                    Game::new(
-                        row.get::<i32, _>(0),
-                        row.get::<i32, _>(1),
-                        row.get::<i32, _>(2),
-                        row.get::<i32, _>(3),
-                        row.get::<NaiveDate, _>(4),
-                        row.get::<Option<i32>, _>(5),
-                        row.get::<Option<i32>, _>(6),
-                    )
-                })
-                .collect();
+            let games = rows.into_iter().map(build_game).collect();
             Ok(games)
         }
         Err(e) => {
             error!("Error getting all games: {}", e);
-            Err(format!("Error getting all games: {}", e))
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// The games of every round in a season.
+pub async fn get_for_season(pool: &DbPool, season_id: i32) -> Result<Vec<Game>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, GameRow>(
+            "SELECT game_id, round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score, kickoff \
+             FROM games WHERE round_id IN (SELECT round_id FROM rounds WHERE season_id=$1) ORDER BY game_date",
+        )
+            .bind(season_id)
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_game).collect()),
+        Err(e) => {
+            error!("Error getting games for season: {}", e);
+            Err(ModelError::from(e))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{round, season, team};
+    use crate::util::test_utils::tests::memory_pool;
+    use async_std::task::block_on;
+
+    #[test]
+    fn test_unknown_kickoff_is_last() {
+        block_on(async {
+            let pool = &memory_pool().await;
+            let teams = [("Adelaide", "Crows"), ("Geelong", "Cats"), ("Carlton", "Blues"), ("Essendon", "Bombers")];
+            let mut team_ids = vec![];
+            for (name, nickname) in teams {
+                team_ids.push(team::insert(pool, name.to_string(), nickname.to_string()).await.unwrap().id());
+            }
+            let season_id = season::get_all(pool).await.unwrap()[0].id;
+            let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+            let round_id = round::insert(pool, season_id, 1, date, date).await.unwrap();
+            let game = |home_team_id: i32, away_team_id: i32, kickoff: Option<DateTime<Utc>>| GameRecord {
+                round_id,
+                home_team_id,
+                away_team_id,
+                game_date: date,
+                home_team_score: None,
+                away_team_score: None,
+                kickoff,
+            };
+            let unknown = insert(pool, game(team_ids[0], team_ids[1], None)).await.unwrap();
+            let kickoff = date.and_hms_opt(9, 30, 0).unwrap().and_utc();
+            let known = insert(pool, game(team_ids[2], team_ids[3], Some(kickoff))).await.unwrap();
+
+            let ids: Vec<i32> = get_for_round(pool, round_id).await.unwrap().iter().map(|g| g.id()).collect();
+            assert_eq!(ids, vec![known, unknown]);
+        });
+    }
+
+    #[test]
+    fn test_missing_reference_is_not_found() {
+        block_on(async {
+            let pool = &memory_pool().await;
+            let home = team::insert(pool, "Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let away = team::insert(pool, "Geelong".to_string(), "Cats".to_string()).await.unwrap();
+            let season_id = season::get_all(pool).await.unwrap()[0].id;
+            let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+            let round_id = round::insert(pool, season_id, 1, date, date).await.unwrap();
+            let game = GameRecord {
+                round_id: round_id + 100,
+                home_team_id: home.id(),
+                away_team_id: away.id(),
+                game_date: date,
+                home_team_score: None,
+                away_team_score: None,
+                kickoff: None,
+            };
+            assert!(matches!(insert(pool, game).await, Err(ModelError::NotFound(_))));
+
+            insert(pool, GameRecord { round_id, ..game }).await.unwrap();
+            assert!(matches!(team::delete(pool, home.id()).await, Err(ModelError::StillReferenced(_))));
+        });
+    }
+}
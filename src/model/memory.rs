@@ -0,0 +1,1075 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::model::bye::Bye;
+use crate::model::competition::Competition;
+use crate::model::error::ModelError;
+use crate::model::game::Game;
+use crate::model::repository::{ByeRepository, CompetitionRepository, Dependents, GameRecord, GameRepository, RepoFuture, RoundRepository, SeasonRepository, TeamRepository, TipRepository, TipperRepository};
+use crate::model::round::Round;
+use crate::model::season::Season;
+use crate::model::team::Team;
+use crate::model::tip::Tip;
+use crate::model::tipper::Tipper;
+use crate::util::fixture::FixtureRound;
+use crate::util::lockout::{LateTips, Lockout};
+use chrono::{NaiveDate, Utc};
+use std::cell::RefCell;
+
+#[derive(Debug, Clone)]
+struct TeamRow {
+    id: i32,
+    name: String,
+    nickname: String,
+}
+
+#[derive(Debug, Clone)]
+struct TipperRow {
+    id: i32,
+    name: String,
+    email: String,
+}
+
+#[derive(Debug, Clone)]
+struct RoundRow {
+    id: i32,
+    season_id: i32,
+    number: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Store {
+    last_id: i32,
+    teams: Vec<TeamRow>,
+    tippers: Vec<TipperRow>,
+    seasons: Vec<Season>,
+    competitions: Vec<Competition>,
+    /// Competition and tipper ids of the tippers in each competition.
+    members: Vec<(i32, i32)>,
+    /// Season and team ids of the teams taking part in each season.
+    season_teams: Vec<(i32, i32)>,
+    /// Season and tipper ids of the tippers taking part in each season.
+    season_tippers: Vec<(i32, i32)>,
+    rounds: Vec<RoundRow>,
+    games: Vec<(i32, GameRecord)>,
+    byes: Vec<(i32, i32)>,
+    tips: Vec<Tip>,
+}
+
+impl Store {
+    fn next_id(&mut self) -> i32 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    /// The season new teams and tippers join, as the database has.
+    fn latest_season(&self) -> Option<i32> {
+        self.seasons.iter().map(|s| s.id).max()
+    }
+
+    fn check_game(&self, game: &GameRecord) -> Result<(), ModelError> {
+        if !self.rounds.iter().any(|r| r.id == game.round_id) {
+            return Err(ModelError::NotFound(format!("Round {} does not exist", game.round_id)));
+        }
+        for team_id in [game.home_team_id, game.away_team_id] {
+            if !self.teams.iter().any(|t| t.id == team_id) {
+                return Err(ModelError::NotFound(format!("Team {} does not exist", team_id)));
+            }
+        }
+        Ok(())
+    }
+
+    fn insert_round(&mut self, season_id: i32, number: i32, start_date: NaiveDate, end_date: NaiveDate) -> i32 {
+        let id = self.next_id();
+        self.rounds.push(RoundRow { id, season_id, number, start_date, end_date });
+        id
+    }
+
+    fn insert_game(&mut self, game: GameRecord) -> Result<i32, ModelError> {
+        self.check_game(&game)?;
+        let id = self.next_id();
+        self.games.push((id, game));
+        Ok(id)
+    }
+
+    /// The ids of the games that match, such as those in a round.
+    fn game_ids(&self, matches: impl Fn(&GameRecord) -> bool) -> Vec<i32> {
+        self.games.iter()
+            .filter(|(_, game)| matches(game))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    fn round_ids(&self, season_id: i32) -> Vec<i32> {
+        self.rounds.iter()
+            .filter(|r| r.season_id == season_id)
+            .map(|r| r.id)
+            .collect()
+    }
+
+    fn count_tips(&self, game_ids: &[i32]) -> i64 {
+        self.tips.iter().filter(|t| game_ids.contains(&t.game_id)).count() as i64
+    }
+
+    /// Delete the games that match, with the tips made on them.
+    fn delete_games(&mut self, matches: impl Fn(&GameRecord) -> bool) -> u64 {
+        let game_ids = self.game_ids(matches);
+        self.tips.retain(|t| !game_ids.contains(&t.game_id));
+        self.games.retain(|(id, _)| !game_ids.contains(id));
+        game_ids.len() as u64
+    }
+}
+
+fn build_team(row: &TeamRow, active: bool) -> Team {
+    let team = Team::new(row.id, row.name.clone(), row.nickname.clone());
+    team.set_active(active);
+    team
+}
+
+fn build_tipper(row: &TipperRow, active: bool) -> Tipper {
+    let tipper = Tipper::new(row.id, row.name.clone(), row.email.clone());
+    tipper.set_active(active);
+    tipper
+}
+
+/// Add or remove a season membership, counting the change as the database would.
+fn set_member(members: &mut Vec<(i32, i32)>, member: (i32, i32), active: bool) -> u64 {
+    let present = members.contains(&member);
+    if active && !present {
+        members.push(member);
+    } else if !active && present {
+        members.retain(|m| *m != member);
+    } else {
+        return 0;
+    }
+    1
+}
+
+fn build_game(id: i32, game: &GameRecord) -> Game {
+    let built = Game::new(id, game.round_id, game.home_team_id, game.away_team_id,
+                          game.game_date, game.home_team_score, game.away_team_score);
+    built.set_kickoff(game.kickoff);
+    built
+}
+
+fn ready<'a, T: 'a>(result: Result<T, ModelError>) -> RepoFuture<'a, T> {
+    Box::pin(std::future::ready(result))
+}
+
+/// A repository that only lives as long as the application, for testing the allocator,
+/// validation and scoring without a database. It keeps the same constraints as the schema,
+/// so rows that are still referenced cannot be deleted.
+#[derive(Debug, Default)]
+pub struct MemoryRepository {
+    store: RefCell<Store>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> MemoryRepository {
+        MemoryRepository::default()
+    }
+}
+
+impl TeamRepository for MemoryRepository {
+    fn insert_team(&self, name: String, nickname: String) -> RepoFuture<'_, Team> {
+        let mut store = self.store.borrow_mut();
+        let id = store.next_id();
+        store.teams.push(TeamRow { id, name: name.clone(), nickname: nickname.clone() });
+        if let Some(season_id) = store.latest_season() {
+            store.season_teams.push((season_id, id));
+        }
+        ready(Ok(Team::new(id, name, nickname)))
+    }
+
+    fn update_team(&self, id: i32, name: String, nickname: String) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        let mut count = 0;
+        for team in store.teams.iter_mut().filter(|t| t.id == id) {
+            team.name = name.clone();
+            team.nickname = nickname.clone();
+            count += 1;
+        }
+        ready(Ok(count))
+    }
+
+    fn delete_team(&self, id: i32) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        let used = store.games.iter().any(|(_, g)| g.home_team_id == id || g.away_team_id == id)
+            || store.byes.iter().any(|(_, team_id)| *team_id == id);
+        if used {
+            return ready(Err(ModelError::StillReferenced(format!("team {} is still used in the draw", id))));
+        }
+        let before = store.teams.len();
+        store.teams.retain(|t| t.id != id);
+        store.season_teams.retain(|(_, team_id)| *team_id != id);
+        ready(Ok((before - store.teams.len()) as u64))
+    }
+
+    fn delete_team_cascade(&self, id: i32) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        store.delete_games(|g| g.home_team_id == id || g.away_team_id == id);
+        store.byes.retain(|(_, team_id)| *team_id != id);
+        store.season_teams.retain(|(_, team_id)| *team_id != id);
+        let before = store.teams.len();
+        store.teams.retain(|t| t.id != id);
+        ready(Ok((before - store.teams.len()) as u64))
+    }
+
+    fn get_team_dependents(&self, id: i32) -> RepoFuture<'_, Dependents> {
+        let store = self.store.borrow();
+        let games = store.game_ids(|g| g.home_team_id == id || g.away_team_id == id);
+        let byes = store.byes.iter().filter(|(_, team_id)| *team_id == id).count();
+        ready(Ok(Dependents { games: games.len() as i64, byes: byes as i64, tips: store.count_tips(&games) }))
+    }
+
+    fn get_team(&self, season_id: i32, id: i32) -> RepoFuture<'_, Option<Team>> {
+        let store = self.store.borrow();
+        let team = store.teams.iter()
+            .find(|t| t.id == id)
+            .map(|t| build_team(t, store.season_teams.contains(&(season_id, t.id))));
+        ready(Ok(team))
+    }
+
+    fn get_all_teams(&self, season_id: i32) -> RepoFuture<'_, Vec<Team>> {
+        let store = self.store.borrow();
+        let mut rows = store.teams.clone();
+        rows.sort_by(|a, b| a.name.cmp(&b.name));
+        ready(Ok(rows.iter().map(|t| build_team(t, store.season_teams.contains(&(season_id, t.id)))).collect()))
+    }
+
+    fn get_active_teams(&self, season_id: i32) -> RepoFuture<'_, Vec<Team>> {
+        let store = self.store.borrow();
+        let mut rows = store.teams.clone();
+        rows.retain(|t| store.season_teams.contains(&(season_id, t.id)));
+        rows.sort_by(|a, b| a.name.cmp(&b.name));
+        ready(Ok(rows.iter().map(|t| build_team(t, true)).collect()))
+    }
+
+    fn set_team_active(&self, season_id: i32, id: i32, active: bool) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        if active && !store.teams.iter().any(|t| t.id == id) {
+            return ready(Err(ModelError::NotFound(format!("Team {} does not exist", id))));
+        }
+        ready(Ok(set_member(&mut store.season_teams, (season_id, id), active)))
+    }
+}
+
+impl TipperRepository for MemoryRepository {
+    fn insert_tipper(&self, name: String, email: String) -> RepoFuture<'_, Tipper> {
+        let mut store = self.store.borrow_mut();
+        if store.tippers.iter().any(|t| t.name == name || t.email == email) {
+            return ready(Err(ModelError::Duplicate(format!("{} or {} is already used", name, email))));
+        }
+        let id = store.next_id();
+        store.tippers.push(TipperRow { id, name: name.clone(), email: email.clone() });
+        if let Some(season_id) = store.latest_season() {
+            store.season_tippers.push((season_id, id));
+        }
+        ready(Ok(Tipper::new(id, name, email)))
+    }
+
+    fn insert_tipper_in_competition(&self, name: String, email: String, competition_id: i32) -> RepoFuture<'_, Tipper> {
+        let mut store = self.store.borrow_mut();
+        if !store.competitions.iter().any(|c| c.id == competition_id) {
+            return ready(Err(ModelError::NotFound(format!("Competition {} does not exist", competition_id))));
+        }
+        if store.tippers.iter().any(|t| t.name == name || t.email == email) {
+            return ready(Err(ModelError::Duplicate(format!("{} or {} is already used", name, email))));
+        }
+        let id = store.next_id();
+        store.tippers.push(TipperRow { id, name: name.clone(), email: email.clone() });
+        store.members.push((competition_id, id));
+        if let Some(season_id) = store.latest_season() {
+            store.season_tippers.push((season_id, id));
+        }
+        ready(Ok(Tipper::new(id, name, email)))
+    }
+
+    fn update_tipper(&self, id: i32, name: String, email: String) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        if store.tippers.iter().any(|t| t.id != id && (t.name == name || t.email == email)) {
+            return ready(Err(ModelError::Duplicate(format!("{} or {} is already used", name, email))));
+        }
+        let mut count = 0;
+        for tipper in store.tippers.iter_mut().filter(|t| t.id == id) {
+            tipper.name = name.clone();
+            tipper.email = email.clone();
+            count += 1;
+        }
+        ready(Ok(count))
+    }
+
+    fn delete_tipper(&self, id: i32) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        if store.tips.iter().any(|t| t.user_id == id) {
+            return ready(Err(ModelError::StillReferenced(format!("tipper {} still has tips", id))));
+        }
+        let before = store.tippers.len();
+        store.tippers.retain(|t| t.id != id);
+        store.members.retain(|(_, tipper_id)| *tipper_id != id);
+        store.season_tippers.retain(|(_, tipper_id)| *tipper_id != id);
+        ready(Ok((before - store.tippers.len()) as u64))
+    }
+
+    fn delete_tipper_cascade(&self, id: i32) -> RepoFuture<'_, u64> {
+        self.store.borrow_mut().tips.retain(|t| t.user_id != id);
+        self.delete_tipper(id)
+    }
+
+    fn get_tipper_dependents(&self, id: i32) -> RepoFuture<'_, Dependents> {
+        let tips = self.store.borrow().tips.iter().filter(|t| t.user_id == id).count();
+        ready(Ok(Dependents { tips: tips as i64, ..Dependents::default() }))
+    }
+
+    fn get_tipper(&self, season_id: i32, id: i32) -> RepoFuture<'_, Option<Tipper>> {
+        let store = self.store.borrow();
+        let tipper = store.tippers.iter()
+            .find(|t| t.id == id)
+            .map(|t| build_tipper(t, store.season_tippers.contains(&(season_id, t.id))));
+        ready(Ok(tipper))
+    }
+
+    fn get_all_tippers(&self, season_id: i32) -> RepoFuture<'_, Vec<Tipper>> {
+        let store = self.store.borrow();
+        let mut rows = store.tippers.clone();
+        rows.sort_by(|a, b| a.name.cmp(&b.name));
+        ready(Ok(rows.iter().map(|t| build_tipper(t, store.season_tippers.contains(&(season_id, t.id)))).collect()))
+    }
+
+    fn get_active_tippers(&self, season_id: i32) -> RepoFuture<'_, Vec<Tipper>> {
+        let store = self.store.borrow();
+        let mut rows = store.tippers.clone();
+        rows.retain(|t| store.season_tippers.contains(&(season_id, t.id)));
+        rows.sort_by(|a, b| a.name.cmp(&b.name));
+        ready(Ok(rows.iter().map(|t| build_tipper(t, true)).collect()))
+    }
+
+    fn set_tipper_active(&self, season_id: i32, id: i32, active: bool) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        if active && !store.tippers.iter().any(|t| t.id == id) {
+            return ready(Err(ModelError::NotFound(format!("Tipper {} does not exist", id))));
+        }
+        ready(Ok(set_member(&mut store.season_tippers, (season_id, id), active)))
+    }
+}
+
+impl SeasonRepository for MemoryRepository {
+    fn get_all_seasons(&self) -> RepoFuture<'_, Vec<Season>> {
+        ready(Ok(self.store.borrow().seasons.clone()))
+    }
+
+    fn start_season(&self, name: String, keep_teams: bool, keep_tippers: bool) -> RepoFuture<'_, Season> {
+        let mut store = self.store.borrow_mut();
+        if store.seasons.iter().any(|s| s.name == name) {
+            return ready(Err(ModelError::Duplicate(format!("season {} already exists", name))));
+        }
+        // Before the first season every team and tipper carries on, as the database starts with a
+        // season holding them all
+        let previous = store.latest_season();
+        let season = Season::new(store.next_id(), name);
+        store.seasons.push(season.clone());
+        if keep_teams {
+            let team_ids: Vec<i32> = store.teams.iter()
+                .map(|t| t.id)
+                .filter(|id| previous.is_none_or(|p| store.season_teams.contains(&(p, *id))))
+                .collect();
+            store.season_teams.extend(team_ids.into_iter().map(|id| (season.id, id)));
+        }
+        if keep_tippers {
+            let tipper_ids: Vec<i32> = store.tippers.iter()
+                .map(|t| t.id)
+                .filter(|id| previous.is_none_or(|p| store.season_tippers.contains(&(p, *id))))
+                .collect();
+            store.season_tippers.extend(tipper_ids.into_iter().map(|id| (season.id, id)));
+        }
+        ready(Ok(season))
+    }
+}
+
+impl CompetitionRepository for MemoryRepository {
+    fn get_all_competitions(&self) -> RepoFuture<'_, Vec<Competition>> {
+        ready(Ok(self.store.borrow().competitions.clone()))
+    }
+
+    fn insert_competition(&self, name: String) -> RepoFuture<'_, Competition> {
+        let mut store = self.store.borrow_mut();
+        if store.competitions.iter().any(|c| c.name == name) {
+            return ready(Err(ModelError::Duplicate(format!("competition {} already exists", name))));
+        }
+        let competition = Competition::new(store.next_id(), name);
+        store.competitions.push(competition.clone());
+        ready(Ok(competition))
+    }
+
+    fn update_competition_rules(&self, id: i32, lockout: Lockout, late_tips: LateTips) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        let mut count = 0;
+        for competition in store.competitions.iter_mut().filter(|c| c.id == id) {
+            competition.lockout = lockout;
+            competition.late_tips = late_tips;
+            count += 1;
+        }
+        ready(Ok(count))
+    }
+
+    fn get_competition_members(&self, competition_id: i32) -> RepoFuture<'_, Vec<i32>> {
+        let store = self.store.borrow();
+        let members = store.members.iter()
+            .filter(|(id, _)| *id == competition_id)
+            .map(|(_, tipper_id)| *tipper_id)
+            .collect();
+        ready(Ok(members))
+    }
+
+    fn set_competition_member(&self, competition_id: i32, tipper_id: i32, member: bool) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        if !store.competitions.iter().any(|c| c.id == competition_id) {
+            return ready(Err(ModelError::NotFound(format!("Competition {} does not exist", competition_id))));
+        }
+        if !store.tippers.iter().any(|t| t.id == tipper_id) {
+            return ready(Err(ModelError::NotFound(format!("Tipper {} does not exist", tipper_id))));
+        }
+        let is_member = store.members.contains(&(competition_id, tipper_id));
+        if is_member == member {
+            return ready(Ok(0));
+        }
+        if member {
+            store.members.push((competition_id, tipper_id));
+        } else {
+            store.members.retain(|m| *m != (competition_id, tipper_id));
+        }
+        ready(Ok(1))
+    }
+}
+
+impl RoundRepository for MemoryRepository {
+    fn insert_round(&self, season_id: i32, round_number: i32, start_date: NaiveDate, end_date: NaiveDate) -> RepoFuture<'_, i32> {
+        ready(Ok(self.store.borrow_mut().insert_round(season_id, round_number, start_date, end_date)))
+    }
+
+    fn insert_fixture<'a>(&'a self, season_id: i32, fixture: &'a [FixtureRound]) -> RepoFuture<'a, Vec<i32>> {
+        // Work on a copy so a bad game leaves nothing behind, as the transaction would
+        let mut store = self.store.borrow().clone();
+        let mut round_ids = Vec::with_capacity(fixture.len());
+        for round in fixture {
+            let existing = store.rounds.iter_mut().find(|r| r.season_id == season_id && r.number == round.number);
+            let round_id = match existing {
+                Some(existing) => {
+                    existing.start_date = existing.start_date.min(round.start_date);
+                    existing.end_date = existing.end_date.max(round.end_date);
+                    existing.id
+                }
+                None => store.insert_round(season_id, round.number, round.start_date, round.end_date),
+            };
+            for game in &round.games {
+                let game = GameRecord {
+                    round_id,
+                    home_team_id: game.home_team_id,
+                    away_team_id: game.away_team_id,
+                    game_date: game.game_date,
+                    home_team_score: None,
+                    away_team_score: None,
+                    kickoff: game.kickoff,
+                };
+                if let Err(e) = store.insert_game(game) {
+                    return ready(Err(e));
+                }
+                store.byes.retain(|(bye_round_id, team_id)| {
+                    *bye_round_id != round_id || (*team_id != game.home_team_id && *team_id != game.away_team_id)
+                });
+            }
+            store.byes.extend(round.byes.iter().map(|team_id| (round_id, *team_id)));
+            round_ids.push(round_id);
+        }
+        self.store.replace(store);
+        ready(Ok(round_ids))
+    }
+
+    fn update_round(&self, id: i32, round_number: i32, start_date: NaiveDate, end_date: NaiveDate) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        let mut count = 0;
+        for round in store.rounds.iter_mut().filter(|r| r.id == id) {
+            round.number = round_number;
+            round.start_date = start_date;
+            round.end_date = end_date;
+            count += 1;
+        }
+        ready(Ok(count))
+    }
+
+    fn save_round<'a>(&'a self, season_id: i32, id: Option<i32>, round_number: i32, start_date: NaiveDate, end_date: NaiveDate,
+                      games: &'a [(i32, GameRecord)], byes: &'a [i32]) -> RepoFuture<'a, (i32, Vec<i32>)> {
+        // Work on a copy so a failure leaves nothing behind, as the transaction would
+        let mut store = self.store.borrow().clone();
+        let round_id = match id {
+            Some(id) => match store.rounds.iter_mut().find(|r| r.id == id) {
+                Some(round) => {
+                    round.number = round_number;
+                    round.start_date = start_date;
+                    round.end_date = end_date;
+                    id
+                }
+                None => return ready(Err(ModelError::NotFound(format!("round {}", id)))),
+            },
+            None => store.insert_round(season_id, round_number, start_date, end_date),
+        };
+
+        let mut game_ids = Vec::with_capacity(games.len());
+        for (game_id, game) in games {
+            let game = GameRecord { round_id, ..*game };
+            if let Err(e) = store.check_game(&game) {
+                return ready(Err(e));
+            }
+            if *game_id > 0 {
+                match store.games.iter_mut().find(|(saved_id, _)| saved_id == game_id) {
+                    Some((_, saved)) => *saved = game,
+                    None => return ready(Err(ModelError::NotFound(format!("game {}", game_id)))),
+                }
+                game_ids.push(*game_id);
+            } else {
+                let id = store.next_id();
+                store.games.push((id, game));
+                game_ids.push(id);
+            }
+        }
+
+        store.byes.retain(|(bye_round_id, _)| *bye_round_id != round_id);
+        store.byes.extend(byes.iter().map(|team_id| (round_id, *team_id)));
+        self.store.replace(store);
+        ready(Ok((round_id, game_ids)))
+    }
+
+    fn delete_round(&self, id: i32) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        let used = store.games.iter().any(|(_, g)| g.round_id == id)
+            || store.byes.iter().any(|(round_id, _)| *round_id == id);
+        if used {
+            return ready(Err(ModelError::StillReferenced(format!("round {} still has games", id))));
+        }
+        let before = store.rounds.len();
+        store.rounds.retain(|r| r.id != id);
+        ready(Ok((before - store.rounds.len()) as u64))
+    }
+
+    fn delete_round_cascade(&self, id: i32) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        store.delete_games(|g| g.round_id == id);
+        store.byes.retain(|(round_id, _)| *round_id != id);
+        let before = store.rounds.len();
+        store.rounds.retain(|r| r.id != id);
+        ready(Ok((before - store.rounds.len()) as u64))
+    }
+
+    fn get_round_dependents(&self, id: i32) -> RepoFuture<'_, Dependents> {
+        let store = self.store.borrow();
+        let games = store.game_ids(|g| g.round_id == id);
+        let byes = store.byes.iter().filter(|(round_id, _)| *round_id == id).count();
+        ready(Ok(Dependents { games: games.len() as i64, byes: byes as i64, tips: store.count_tips(&games) }))
+    }
+
+    fn get_round(&self, id: i32) -> RepoFuture<'_, Option<Round>> {
+        let store = self.store.borrow();
+        let round = store.rounds.iter()
+            .find(|r| r.id == id)
+            .map(|r| Round::new(r.id, r.number, r.start_date, r.end_date));
+        ready(Ok(round))
+    }
+
+    fn get_last_round(&self, season_id: i32) -> RepoFuture<'_, Option<Round>> {
+        let store = self.store.borrow();
+        let round = store.rounds.iter()
+            .filter(|r| r.season_id == season_id)
+            .max_by_key(|r| r.number)
+            .map(|r| Round::new(r.id, r.number, r.start_date, r.end_date));
+        ready(Ok(round))
+    }
+
+    fn get_all_rounds(&self, season_id: i32) -> RepoFuture<'_, Vec<Round>> {
+        let mut rows = self.store.borrow().rounds.clone();
+        rows.retain(|r| r.season_id == season_id);
+        rows.sort_by_key(|r| r.number);
+        ready(Ok(rows.into_iter().map(|r| Round::new(r.id, r.number, r.start_date, r.end_date)).collect()))
+    }
+}
+
+impl GameRepository for MemoryRepository {
+    fn insert_game(&self, game: GameRecord) -> RepoFuture<'_, i32> {
+        ready(self.store.borrow_mut().insert_game(game))
+    }
+
+    fn update_game(&self, id: i32, game: GameRecord) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        if let Err(e) = store.check_game(&game) {
+            return ready(Err(e));
+        }
+        let mut count = 0;
+        for (_, saved) in store.games.iter_mut().filter(|(game_id, _)| *game_id == id) {
+            *saved = game;
+            count += 1;
+        }
+        ready(Ok(count))
+    }
+
+    fn save_results<'a>(&'a self, results: &'a [(i32, i32, i32)]) -> RepoFuture<'a, u64> {
+        let mut store = self.store.borrow_mut();
+        if let Some((game_id, _, _)) = results.iter().find(|(id, _, _)| !store.games.iter().any(|(game_id, _)| game_id == id)) {
+            return ready(Err(ModelError::NotFound(format!("game {}", game_id))));
+        }
+        for (id, home_team_score, away_team_score) in results {
+            for (_, saved) in store.games.iter_mut().filter(|(game_id, _)| game_id == id) {
+                saved.home_team_score = Some(*home_team_score);
+                saved.away_team_score = Some(*away_team_score);
+            }
+        }
+        ready(Ok(results.len() as u64))
+    }
+
+    fn delete_game(&self, id: i32) -> RepoFuture<'_, u64> {
+        let mut store = self.store.borrow_mut();
+        let game_ids = store.game_ids(|_| true);
+        if !game_ids.contains(&id) {
+            return ready(Ok(0));
+        }
+        store.tips.retain(|t| t.game_id != id);
+        store.games.retain(|(game_id, _)| *game_id != id);
+        ready(Ok(1))
+    }
+
+    fn get_game_dependents(&self, id: i32) -> RepoFuture<'_, Dependents> {
+        ready(Ok(Dependents { tips: self.store.borrow().count_tips(&[id]), ..Dependents::default() }))
+    }
+
+    fn get_game(&self, id: i32) -> RepoFuture<'_, Option<Game>> {
+        let store = self.store.borrow();
+        let game = store.games.iter()
+            .find(|(game_id, _)| *game_id == id)
+            .map(|(game_id, game)| build_game(*game_id, game));
+        ready(Ok(game))
+    }
+
+    fn get_games_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Game>> {
+        let mut rows: Vec<(i32, GameRecord)> = self.store.borrow().games.iter()
+            .filter(|(_, game)| game.round_id == round_id)
+            .cloned()
+            .collect();
+        // Games without a kickoff go last, as they do in the database
+        rows.sort_by_key(|(_, game)| (game.game_date, game.kickoff.is_none(), game.kickoff));
+        ready(Ok(rows.iter().map(|(id, game)| build_game(*id, game)).collect()))
+    }
+
+    fn get_games_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Game>> {
+        let store = self.store.borrow();
+        let round_ids = store.round_ids(season_id);
+        let mut rows: Vec<(i32, GameRecord)> = store.games.iter()
+            .filter(|(_, game)| round_ids.contains(&game.round_id))
+            .cloned()
+            .collect();
+        rows.sort_by_key(|(_, game)| game.game_date);
+        ready(Ok(rows.iter().map(|(id, game)| build_game(*id, game)).collect()))
+    }
+
+    fn get_all_games(&self) -> RepoFuture<'_, Vec<Game>> {
+        let mut rows = self.store.borrow().games.clone();
+        rows.sort_by_key(|(_, game)| game.game_date);
+        ready(Ok(rows.iter().map(|(id, game)| build_game(*id, game)).collect()))
+    }
+}
+
+impl TipRepository for MemoryRepository {
+    fn save_tips<'a>(&'a self, tips: &'a [Tip]) -> RepoFuture<'a, u64> {
+        // Work on a copy so a bad tip leaves nothing behind, as the transaction would
+        let mut store = self.store.borrow().clone();
+        for tip in tips {
+            if !store.games.iter().any(|(id, _)| *id == tip.game_id) {
+                return ready(Err(ModelError::NotFound(format!("Game {} does not exist", tip.game_id))));
+            }
+            if !store.tippers.iter().any(|t| t.id == tip.user_id) {
+                return ready(Err(ModelError::NotFound(format!("Tipper {} does not exist", tip.user_id))));
+            }
+            let saved = Tip { tip_date: Some(Utc::now().naive_utc()), ..tip.clone() };
+            match store.tips.iter_mut().find(|t| t.user_id == tip.user_id && t.game_id == tip.game_id) {
+                Some(existing) => *existing = Tip { tip_id: existing.tip_id, ..saved },
+                None => {
+                    let tip_id = store.next_id();
+                    store.tips.push(Tip { tip_id, ..saved });
+                }
+            }
+        }
+        self.store.replace(store);
+        ready(Ok(tips.len() as u64))
+    }
+
+    fn get_tips_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Tip>> {
+        let store = self.store.borrow();
+        let mut tips: Vec<(NaiveDate, Tip)> = store.tips.iter()
+            .filter_map(|tip| store.games.iter()
+                .find(|(id, game)| *id == tip.game_id && game.round_id == round_id)
+                .map(|(_, game)| (game.game_date, tip.clone())))
+            .collect();
+        tips.sort_by_key(|(game_date, tip)| (tip.user_id, *game_date));
+        ready(Ok(tips.into_iter().map(|(_, tip)| tip).collect()))
+    }
+
+    fn get_tips_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Tip>> {
+        let store = self.store.borrow();
+        let round_ids = store.round_ids(season_id);
+        let game_ids = store.game_ids(|g| round_ids.contains(&g.round_id));
+        let mut tips: Vec<Tip> = store.tips.iter()
+            .filter(|t| game_ids.contains(&t.game_id))
+            .cloned()
+            .collect();
+        tips.sort_by_key(|t| (t.user_id, t.game_id));
+        ready(Ok(tips))
+    }
+}
+
+impl ByeRepository for MemoryRepository {
+    fn get_byes_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Bye>> {
+        let mut byes: Vec<(i32, i32)> = self.store.borrow().byes.iter()
+            .filter(|(bye_round_id, _)| *bye_round_id == round_id)
+            .copied()
+            .collect();
+        byes.sort();
+        ready(Ok(byes.into_iter().map(|(round_id, team_id)| Bye::new(round_id, team_id)).collect()))
+    }
+
+    fn get_byes_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Bye>> {
+        let store = self.store.borrow();
+        let round_ids = store.round_ids(season_id);
+        let mut byes: Vec<(i32, i32)> = store.byes.iter()
+            .filter(|(round_id, _)| round_ids.contains(round_id))
+            .copied()
+            .collect();
+        byes.sort();
+        ready(Ok(byes.into_iter().map(|(round_id, team_id)| Bye::new(round_id, team_id)).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::fixture::FixtureGame;
+    use async_std::task::block_on;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_referenced_team_is_kept() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            let home = repository.insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let away = repository.insert_team("Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let season = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            let round_id = repository.insert_round(season.id, 1, date(14), date(16)).await.unwrap();
+            let game = GameRecord {
+                round_id,
+                home_team_id: home.id(),
+                away_team_id: away.id(),
+                game_date: date(15),
+                home_team_score: Some(80),
+                away_team_score: Some(75),
+                kickoff: None,
+            };
+            let game_id = repository.insert_game(game).await.unwrap();
+
+            let names: Vec<String> = repository.get_all_teams(season.id).await.unwrap().iter().map(|t| t.name()).collect();
+            assert_eq!(names, vec!["Adelaide", "Carlton"]);
+            assert!(matches!(repository.delete_team(home.id()).await, Err(ModelError::StillReferenced(_))));
+            assert!(matches!(repository.delete_round(round_id).await, Err(ModelError::StillReferenced(_))));
+
+            // An archived team drops out of the active list but keeps its name for past games
+            assert_eq!(repository.set_team_active(season.id, home.id(), false).await.unwrap(), 1);
+            let active: Vec<String> = repository.get_active_teams(season.id).await.unwrap().iter().map(|t| t.name()).collect();
+            assert_eq!(active, vec!["Adelaide"]);
+            assert_eq!(repository.get_all_teams(season.id).await.unwrap().len(), 2);
+            assert!(!repository.get_team(season.id, home.id()).await.unwrap().unwrap().is_active());
+
+            assert_eq!(repository.delete_game(game_id).await.unwrap(), 1);
+            assert_eq!(repository.delete_team(home.id()).await.unwrap(), 1);
+            assert!(repository.get_team(season.id, home.id()).await.unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn test_cascade_removes_dependents() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            let home = repository.insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let away = repository.insert_team("Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let bye = repository.insert_team("Geelong".to_string(), "Cats".to_string()).await.unwrap();
+            let game = GameRecord {
+                round_id: -1,
+                home_team_id: home.id(),
+                away_team_id: away.id(),
+                game_date: date(15),
+                home_team_score: None,
+                away_team_score: None,
+                kickoff: None,
+            };
+            let season = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            let (round_id, _) = repository.save_round(season.id, None, 1, date(14), date(16), &[(-1, game)], &[bye.id()]).await.unwrap();
+
+            let dependents = repository.get_round_dependents(round_id).await.unwrap();
+            assert_eq!(dependents, Dependents { games: 1, byes: 1, tips: 0 });
+            assert_eq!(dependents.describe(), "1 game and 1 bye");
+            assert_eq!(repository.get_team_dependents(home.id()).await.unwrap().games, 1);
+
+            assert_eq!(repository.delete_team_cascade(home.id()).await.unwrap(), 1);
+            assert!(repository.get_games_for_round(round_id).await.unwrap().is_empty());
+            assert_eq!(repository.delete_round_cascade(round_id).await.unwrap(), 1);
+            assert!(repository.get_team_dependents(bye.id()).await.unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_round_save_is_all_or_nothing() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            let home = repository.insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let away = repository.insert_team("Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let game = |away_team_id: i32| GameRecord {
+                round_id: -1,
+                home_team_id: home.id(),
+                away_team_id,
+                game_date: date(15),
+                home_team_score: None,
+                away_team_score: None,
+                kickoff: None,
+            };
+
+            let season = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            let result = repository.save_round(season.id, None, 1, date(14), date(16), &[(-1, game(away.id())), (-1, game(99))], &[]).await;
+            assert!(matches!(result, Err(ModelError::NotFound(_))));
+            assert!(repository.get_all_rounds(season.id).await.unwrap().is_empty());
+            assert!(repository.get_all_games().await.unwrap().is_empty());
+
+            let (round_id, game_ids) = repository.save_round(season.id, None, 1, date(14), date(16), &[(-1, game(away.id()))], &[]).await.unwrap();
+            let games = repository.get_games_for_round(round_id).await.unwrap();
+            assert_eq!(games.iter().map(|g| g.id()).collect::<Vec<i32>>(), game_ids);
+
+            let result = repository.save_round(season.id, Some(round_id), 2, date(14), date(16), &[(game_ids[0], game(99))], &[]).await;
+            assert!(result.is_err());
+            assert_eq!(repository.get_round(round_id).await.unwrap().map(|r| r.number()), Some(1));
+        });
+    }
+
+    #[test]
+    fn test_fixture_is_all_or_nothing() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            let home = repository.insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let away = repository.insert_team("Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let round = |number: i32, away_team_id: i32| FixtureRound {
+                number,
+                start_date: date(number as u32),
+                end_date: date(number as u32),
+                games: vec![FixtureGame { home_team_id: home.id(), away_team_id, game_date: date(number as u32), kickoff: None, venue: None }],
+                byes: vec![],
+            };
+
+            let season = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            assert!(repository.insert_fixture(season.id, &[round(1, away.id()), round(2, 99)]).await.is_err());
+            assert!(repository.get_all_rounds(season.id).await.unwrap().is_empty());
+
+            let round_ids = repository.insert_fixture(season.id, &[round(1, away.id()), round(2, away.id())]).await.unwrap();
+            assert_eq!(round_ids.len(), 2);
+            assert_eq!(repository.get_last_round(season.id).await.unwrap().map(|r| r.id()), Some(round_ids[1]));
+            assert_eq!(repository.get_games_for_round(round_ids[0]).await.unwrap().len(), 1);
+
+            // Importing into a round that exists adds to it rather than making another
+            let mut later = round(2, away.id());
+            later.end_date = date(9);
+            assert_eq!(repository.insert_fixture(season.id, &[later]).await.unwrap(), vec![round_ids[1]]);
+            assert_eq!(repository.get_all_rounds(season.id).await.unwrap().len(), 2);
+            assert_eq!(repository.get_round(round_ids[1]).await.unwrap().map(|r| r.end_date()), Some(date(9)));
+            assert_eq!(repository.get_games_for_round(round_ids[1]).await.unwrap().len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_merged_games_replace_byes() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            let home = repository.insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let away = repository.insert_team("Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let rested = repository.insert_team("Geelong".to_string(), "Cats".to_string()).await.unwrap();
+            let round = |away_team_id: i32, byes: Vec<i32>| FixtureRound {
+                number: 1,
+                start_date: date(15),
+                end_date: date(15),
+                games: vec![FixtureGame { home_team_id: home.id(), away_team_id, game_date: date(15), kickoff: None, venue: None }],
+                byes,
+            };
+
+            let season = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            let round_ids = repository.insert_fixture(season.id, &[round(away.id(), vec![rested.id()])]).await.unwrap();
+            assert_eq!(repository.get_byes_for_round(round_ids[0]).await.unwrap().len(), 1);
+
+            // Geelong plays in the merged game, so its bye goes
+            repository.insert_fixture(season.id, &[round(rested.id(), vec![])]).await.unwrap();
+            assert!(repository.get_byes_for_round(round_ids[0]).await.unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_results_are_all_or_nothing() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            let home = repository.insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let away = repository.insert_team("Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let season = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            let round_id = repository.insert_round(season.id, 1, date(14), date(16)).await.unwrap();
+            let game_id = repository.insert_game(GameRecord {
+                round_id,
+                home_team_id: home.id(),
+                away_team_id: away.id(),
+                game_date: date(15),
+                home_team_score: None,
+                away_team_score: None,
+                kickoff: None,
+            }).await.unwrap();
+
+            assert!(repository.save_results(&[(game_id, 80, 60), (99, 1, 2)]).await.is_err());
+            assert!(!repository.get_game(game_id).await.unwrap().unwrap().has_result());
+
+            assert_eq!(repository.save_results(&[(game_id, 80, 60)]).await.unwrap(), 1);
+            let game = repository.get_game(game_id).await.unwrap().unwrap();
+            assert_eq!((game.home_team_score(), game.away_team_score()), (Some(80), Some(60)));
+        });
+    }
+
+    #[test]
+    fn test_new_season_starts_without_rounds() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            repository.insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            repository.insert_tipper("Trevor".to_string(), "trevor@example.com".to_string()).await.unwrap();
+            let first = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            repository.insert_round(first.id, 1, date(14), date(16)).await.unwrap();
+
+            assert!(matches!(repository.start_season("2025".to_string(), true, true).await, Err(ModelError::Duplicate(_))));
+            let second = repository.start_season("2026".to_string(), true, false).await.unwrap();
+            assert!(repository.get_all_rounds(second.id).await.unwrap().is_empty());
+            assert!(repository.get_last_round(second.id).await.unwrap().is_none());
+            assert_eq!(repository.get_all_rounds(first.id).await.unwrap().len(), 1);
+
+            // The teams carry on into the new season, the tippers were not kept but still take
+            // part in the season before
+            assert_eq!(repository.get_active_teams(second.id).await.unwrap().len(), 1);
+            assert!(repository.get_active_tippers(second.id).await.unwrap().is_empty());
+            assert_eq!(repository.get_all_tippers(second.id).await.unwrap().len(), 1);
+            assert_eq!(repository.get_active_tippers(first.id).await.unwrap().len(), 1);
+            assert_eq!(repository.get_all_seasons().await.unwrap(), vec![first, second]);
+        });
+    }
+
+    #[test]
+    fn test_competitions_keep_their_own_tippers() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            let season = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            let office = repository.insert_competition("Office".to_string()).await.unwrap();
+            let family = repository.insert_competition("Family".to_string()).await.unwrap();
+            assert!(matches!(repository.insert_competition("Office".to_string()).await, Err(ModelError::Duplicate(_))));
+            let trevor = repository.insert_tipper("Trevor".to_string(), "trevor@example.com".to_string()).await.unwrap();
+            let jane = repository.insert_tipper("Jane".to_string(), "jane@example.com".to_string()).await.unwrap();
+
+            assert_eq!(repository.set_competition_member(office.id, trevor.id(), true).await.unwrap(), 1);
+            assert_eq!(repository.set_competition_member(office.id, trevor.id(), true).await.unwrap(), 0);
+            repository.set_competition_member(office.id, jane.id(), true).await.unwrap();
+            repository.set_competition_member(family.id, jane.id(), true).await.unwrap();
+            assert!(repository.set_competition_member(family.id, 99, true).await.is_err());
+            assert_eq!(repository.get_competition_members(office.id).await.unwrap().len(), 2);
+            assert_eq!(repository.get_competition_members(family.id).await.unwrap(), vec![jane.id()]);
+
+            assert_eq!(repository.set_competition_member(office.id, trevor.id(), false).await.unwrap(), 1);
+            repository.delete_tipper(jane.id()).await.unwrap();
+            assert!(repository.get_competition_members(office.id).await.unwrap().is_empty());
+            assert!(repository.get_competition_members(family.id).await.unwrap().is_empty());
+
+            // A tipper added to a competition is only kept along with the membership
+            let kim = repository.insert_tipper_in_competition("Kim".to_string(), "kim@example.com".to_string(), family.id).await.unwrap();
+            assert_eq!(repository.get_competition_members(family.id).await.unwrap(), vec![kim.id()]);
+            assert!(repository.insert_tipper_in_competition("Lee".to_string(), "lee@example.com".to_string(), 99).await.is_err());
+            assert_eq!(repository.get_all_tippers(season.id).await.unwrap().len(), 2);
+
+            assert_eq!(repository.update_competition_rules(family.id, Lockout::Round, LateTips::Flag).await.unwrap(), 1);
+            let competitions = repository.get_all_competitions().await.unwrap();
+            assert_eq!((competitions[1].lockout, competitions[1].late_tips), (Lockout::Round, LateTips::Flag));
+            assert_eq!((competitions[0].lockout, competitions[0].late_tips), (Lockout::Game, LateTips::Reject));
+        });
+    }
+
+    #[test]
+    fn test_tips_replace_earlier_tips() {
+        let repository = MemoryRepository::new();
+        block_on(async {
+            let home = repository.insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let away = repository.insert_team("Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let bye = repository.insert_team("Geelong".to_string(), "Cats".to_string()).await.unwrap();
+            let tipper = repository.insert_tipper("Kim".to_string(), "kim@example.com".to_string()).await.unwrap();
+            let game = GameRecord {
+                round_id: -1,
+                home_team_id: home.id(),
+                away_team_id: away.id(),
+                game_date: date(15),
+                home_team_score: None,
+                away_team_score: None,
+                kickoff: None,
+            };
+            let season = repository.start_season("2025".to_string(), true, true).await.unwrap();
+            let (round_id, game_ids) = repository.save_round(season.id, None, 1, date(14), date(16), &[(-1, game)], &[bye.id()]).await.unwrap();
+            let game_id = game_ids[0];
+
+            let mut tip = Tip::new(-1, tipper.id(), game_id, 1, 0, None);
+            tip.late = true;
+            assert_eq!(repository.save_tips(&[tip]).await.unwrap(), 1);
+            assert_eq!(repository.save_tips(&[Tip::new(-1, tipper.id(), game_id, 0, 12, None)]).await.unwrap(), 1);
+            let tips = repository.get_tips_for_round(round_id).await.unwrap();
+            assert_eq!(tips.len(), 1);
+            assert_eq!((tips[0].predicted_home_score, tips[0].predicted_away_score, tips[0].late), (0, 12, false));
+            assert!(tips[0].tip_date.is_some());
+            assert_eq!(repository.get_tips_for_season(season.id).await.unwrap().len(), 1);
+
+            // A tip on a missing game leaves the others unsaved
+            let bad = [Tip::new(-1, tipper.id(), game_id, 1, 0, None), Tip::new(-1, tipper.id(), -5, 1, 0, None)];
+            assert!(matches!(repository.save_tips(&bad).await, Err(ModelError::NotFound(_))));
+            assert_eq!(repository.get_tips_for_round(round_id).await.unwrap()[0].predicted_away_score, 12);
+
+            assert_eq!(repository.get_byes_for_round(round_id).await.unwrap(), vec![Bye::new(round_id, bye.id())]);
+            assert_eq!(repository.get_byes_for_season(season.id).await.unwrap().len(), 1);
+            assert_eq!(repository.get_games_for_season(season.id).await.unwrap().len(), 1);
+
+            assert!(matches!(repository.delete_tipper(tipper.id()).await, Err(ModelError::StillReferenced(_))));
+            assert_eq!(repository.get_game_dependents(game_id).await.unwrap().tips, 1);
+            assert_eq!(repository.get_round_dependents(round_id).await.unwrap(), Dependents { games: 1, byes: 1, tips: 1 });
+            assert_eq!(repository.delete_game(game_id).await.unwrap(), 1);
+            assert!(repository.get_tips_for_season(season.id).await.unwrap().is_empty());
+            assert_eq!(repository.delete_tipper(tipper.id()).await.unwrap(), 1);
+        });
+    }
+}
@@ -24,5 +24,11 @@
 pub(crate) mod team;
 pub(crate) mod round;
 pub(crate) mod game;
+pub(crate) mod bye;
 pub(crate) mod tipper;
-pub(crate) mod tip;
\ No newline at end of file
+pub(crate) mod tip;
+pub(crate) mod repository;
+pub(crate) mod memory;
+pub(crate) mod error;
+pub(crate) mod season;
+pub(crate) mod competition;
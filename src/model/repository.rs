@@ -0,0 +1,439 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::model::bye::{self, Bye};
+use crate::model::competition::{self, Competition};
+use crate::model::error::ModelError;
+use crate::model::game::{self, Game};
+use crate::model::round::{self, Round};
+use crate::model::season::{self, Season};
+use crate::model::team::{self, Team};
+use crate::model::tip::{self, Tip};
+use crate::model::tipper::{self, Tipper};
+use crate::util::db;
+use crate::util::db::DbPool;
+use crate::util::fixture::FixtureRound;
+use crate::util::lockout::{LateTips, Lockout};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The result of a repository call. Model objects are not `Send`, so neither are these futures.
+pub type RepoFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, ModelError>> + 'a>>;
+
+/// What goes with a record when it is deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Dependents {
+    pub games: i64,
+    pub byes: i64,
+    pub tips: i64,
+}
+
+impl Dependents {
+    pub fn is_empty(&self) -> bool {
+        self.games == 0 && self.byes == 0 && self.tips == 0
+    }
+
+    /// A list such as "3 games, 1 bye and 27 tips", leaving out anything there is none of.
+    pub fn describe(&self) -> String {
+        let parts: Vec<String> = [(self.games, "game"), (self.byes, "bye"), (self.tips, "tip")]
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, name)| format!("{} {}{}", count, name, if *count == 1 { "" } else { "s" }))
+            .collect();
+        match parts.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+            None => String::new(),
+        }
+    }
+}
+
+pub trait TeamRepository {
+    fn insert_team(&self, name: String, nickname: String) -> RepoFuture<'_, Team>;
+    fn update_team(&self, id: i32, name: String, nickname: String) -> RepoFuture<'_, u64>;
+    fn delete_team(&self, id: i32) -> RepoFuture<'_, u64>;
+    /// Delete a team with the games it plays in, its byes and the tips on its games.
+    fn delete_team_cascade(&self, id: i32) -> RepoFuture<'_, u64>;
+    fn get_team_dependents(&self, id: i32) -> RepoFuture<'_, Dependents>;
+    /// A team, marked active when it takes part in the season.
+    fn get_team(&self, season_id: i32, id: i32) -> RepoFuture<'_, Option<Team>>;
+    /// Every team, including those archived in the season, so past games keep their names.
+    fn get_all_teams(&self, season_id: i32) -> RepoFuture<'_, Vec<Team>>;
+    fn get_active_teams(&self, season_id: i32) -> RepoFuture<'_, Vec<Team>>;
+    /// Archive a team in a season, or bring it back, without touching its games or other seasons.
+    fn set_team_active(&self, season_id: i32, id: i32, active: bool) -> RepoFuture<'_, u64>;
+}
+
+pub trait TipperRepository {
+    fn insert_tipper(&self, name: String, email: String) -> RepoFuture<'_, Tipper>;
+    /// Add a tipper who joins a competition straight away, saving both or neither.
+    fn insert_tipper_in_competition(&self, name: String, email: String, competition_id: i32) -> RepoFuture<'_, Tipper>;
+    fn update_tipper(&self, id: i32, name: String, email: String) -> RepoFuture<'_, u64>;
+    fn delete_tipper(&self, id: i32) -> RepoFuture<'_, u64>;
+    /// Delete a tipper with all of their tips.
+    fn delete_tipper_cascade(&self, id: i32) -> RepoFuture<'_, u64>;
+    fn get_tipper_dependents(&self, id: i32) -> RepoFuture<'_, Dependents>;
+    /// A tipper, marked active when they take part in the season.
+    fn get_tipper(&self, season_id: i32, id: i32) -> RepoFuture<'_, Option<Tipper>>;
+    /// Every tipper, including those archived in the season, so past tips keep their names.
+    fn get_all_tippers(&self, season_id: i32) -> RepoFuture<'_, Vec<Tipper>>;
+    fn get_active_tippers(&self, season_id: i32) -> RepoFuture<'_, Vec<Tipper>>;
+    /// Archive a tipper in a season, or bring them back, without touching their tips or other seasons.
+    fn set_tipper_active(&self, season_id: i32, id: i32, active: bool) -> RepoFuture<'_, u64>;
+}
+
+pub trait SeasonRepository {
+    fn get_all_seasons(&self) -> RepoFuture<'_, Vec<Season>>;
+    /// Start a new season, carrying the teams and tippers of the latest season into it when they are kept.
+    fn start_season(&self, name: String, keep_teams: bool, keep_tippers: bool) -> RepoFuture<'_, Season>;
+}
+
+pub trait CompetitionRepository {
+    fn get_all_competitions(&self) -> RepoFuture<'_, Vec<Competition>>;
+    fn insert_competition(&self, name: String) -> RepoFuture<'_, Competition>;
+    /// Change when tips close in a competition and what happens to tips entered after that.
+    fn update_competition_rules(&self, id: i32, lockout: Lockout, late_tips: LateTips) -> RepoFuture<'_, u64>;
+    /// The ids of the tippers in a competition.
+    fn get_competition_members(&self, competition_id: i32) -> RepoFuture<'_, Vec<i32>>;
+    /// Put a tipper in a competition or take them out of it, leaving their tips alone.
+    fn set_competition_member(&self, competition_id: i32, tipper_id: i32, member: bool) -> RepoFuture<'_, u64>;
+}
+
+pub trait RoundRepository {
+    fn insert_round(&self, season_id: i32, round_number: i32, start_date: NaiveDate, end_date: NaiveDate) -> RepoFuture<'_, i32>;
+    /// Create every round of a draw with its games and byes, returning the round ids. A round whose
+    /// number is already in the season has the games and byes added to it, and its dates widened
+    /// to take them in.
+    fn insert_fixture<'a>(&'a self, season_id: i32, fixture: &'a [FixtureRound]) -> RepoFuture<'a, Vec<i32>>;
+    fn update_round(&self, id: i32, round_number: i32, start_date: NaiveDate, end_date: NaiveDate) -> RepoFuture<'_, u64>;
+    /// Save a round with its games and byes all together, or not at all. A round without an id is
+    /// inserted into the season, as are games with an id of zero or less. Returns the round id and
+    /// the game ids in order.
+    #[allow(clippy::too_many_arguments)]
+    fn save_round<'a>(&'a self, season_id: i32, id: Option<i32>, round_number: i32, start_date: NaiveDate, end_date: NaiveDate,
+                      games: &'a [(i32, GameRecord)], byes: &'a [i32]) -> RepoFuture<'a, (i32, Vec<i32>)>;
+    fn delete_round(&self, id: i32) -> RepoFuture<'_, u64>;
+    /// Delete a round with its games, its byes and the tips on its games.
+    fn delete_round_cascade(&self, id: i32) -> RepoFuture<'_, u64>;
+    fn get_round_dependents(&self, id: i32) -> RepoFuture<'_, Dependents>;
+    fn get_round(&self, id: i32) -> RepoFuture<'_, Option<Round>>;
+    fn get_last_round(&self, season_id: i32) -> RepoFuture<'_, Option<Round>>;
+    fn get_all_rounds(&self, season_id: i32) -> RepoFuture<'_, Vec<Round>>;
+}
+
+/// The fields of a game that are saved, in the order of the games table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameRecord {
+    pub round_id: i32,
+    pub home_team_id: i32,
+    pub away_team_id: i32,
+    pub game_date: NaiveDate,
+    pub home_team_score: Option<i32>,
+    pub away_team_score: Option<i32>,
+    pub kickoff: Option<DateTime<Utc>>,
+}
+
+impl GameRecord {
+    pub fn of(game: &Game) -> GameRecord {
+        GameRecord {
+            round_id: game.round_id(),
+            home_team_id: game.home_team_id(),
+            away_team_id: game.away_team_id(),
+            game_date: game.game_date(),
+            home_team_score: game.home_team_score(),
+            away_team_score: game.away_team_score(),
+            kickoff: game.kickoff(),
+        }
+    }
+}
+
+pub trait GameRepository {
+    fn insert_game(&self, game: GameRecord) -> RepoFuture<'_, i32>;
+    fn update_game(&self, id: i32, game: GameRecord) -> RepoFuture<'_, u64>;
+    /// Set the scores of several games, given as (game id, home score, away score), all together
+    /// or not at all.
+    fn save_results<'a>(&'a self, results: &'a [(i32, i32, i32)]) -> RepoFuture<'a, u64>;
+    /// Delete a game along with any tips made for it.
+    fn delete_game(&self, id: i32) -> RepoFuture<'_, u64>;
+    fn get_game_dependents(&self, id: i32) -> RepoFuture<'_, Dependents>;
+    fn get_game(&self, id: i32) -> RepoFuture<'_, Option<Game>>;
+    fn get_games_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Game>>;
+    /// The games of every round in a season.
+    fn get_games_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Game>>;
+    fn get_all_games(&self) -> RepoFuture<'_, Vec<Game>>;
+}
+
+pub trait TipRepository {
+    /// Save a set of tips all together or not at all. A tip replaces any earlier tip of the same
+    /// tipper for the same game.
+    fn save_tips<'a>(&'a self, tips: &'a [Tip]) -> RepoFuture<'a, u64>;
+    fn get_tips_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Tip>>;
+    /// The tips on every game in a season.
+    fn get_tips_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Tip>>;
+}
+
+pub trait ByeRepository {
+    fn get_byes_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Bye>>;
+    /// The byes of every round in a season.
+    fn get_byes_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Bye>>;
+}
+
+/// Everything the windows need to load and save the competition.
+pub trait Repository: TeamRepository + TipperRepository + SeasonRepository + CompetitionRepository + RoundRepository
+    + GameRepository + TipRepository + ByeRepository {}
+
+impl<T: TeamRepository + TipperRepository + SeasonRepository + CompetitionRepository + RoundRepository
+    + GameRepository + TipRepository + ByeRepository> Repository for T {}
+
+/// A repository kept in the PostgreSQL or SQLite database.
+pub struct DbRepository {
+    pool: Arc<DbPool>,
+}
+
+impl DbRepository {
+    pub fn new(pool: Arc<DbPool>) -> DbRepository {
+        DbRepository { pool }
+    }
+}
+
+impl TeamRepository for DbRepository {
+    fn insert_team(&self, name: String, nickname: String) -> RepoFuture<'_, Team> {
+        Box::pin(team::insert(&self.pool, name, nickname))
+    }
+    fn update_team(&self, id: i32, name: String, nickname: String) -> RepoFuture<'_, u64> {
+        Box::pin(team::update(&self.pool, id, name, nickname))
+    }
+    fn delete_team(&self, id: i32) -> RepoFuture<'_, u64> {
+        Box::pin(team::delete(&self.pool, id))
+    }
+    fn delete_team_cascade(&self, id: i32) -> RepoFuture<'_, u64> {
+        Box::pin(team::delete_cascade(&self.pool, id))
+    }
+    fn get_team_dependents(&self, id: i32) -> RepoFuture<'_, Dependents> {
+        Box::pin(team::dependents(&self.pool, id))
+    }
+    fn get_team(&self, season_id: i32, id: i32) -> RepoFuture<'_, Option<Team>> {
+        Box::pin(team::get(&self.pool, season_id, id))
+    }
+    fn get_all_teams(&self, season_id: i32) -> RepoFuture<'_, Vec<Team>> {
+        Box::pin(team::get_all(&self.pool, season_id))
+    }
+    fn get_active_teams(&self, season_id: i32) -> RepoFuture<'_, Vec<Team>> {
+        Box::pin(team::get_active(&self.pool, season_id))
+    }
+    fn set_team_active(&self, season_id: i32, id: i32, active: bool) -> RepoFuture<'_, u64> {
+        Box::pin(team::set_active(&self.pool, season_id, id, active))
+    }
+}
+
+impl TipperRepository for DbRepository {
+    fn insert_tipper(&self, name: String, email: String) -> RepoFuture<'_, Tipper> {
+        Box::pin(tipper::insert(&self.pool, name, email))
+    }
+    fn insert_tipper_in_competition(&self, name: String, email: String, competition_id: i32) -> RepoFuture<'_, Tipper> {
+        Box::pin(tipper::insert_in_competition(&self.pool, name, email, competition_id))
+    }
+    fn update_tipper(&self, id: i32, name: String, email: String) -> RepoFuture<'_, u64> {
+        Box::pin(tipper::update(&self.pool, id, name, email))
+    }
+    fn delete_tipper(&self, id: i32) -> RepoFuture<'_, u64> {
+        Box::pin(tipper::delete(&self.pool, id))
+    }
+    fn delete_tipper_cascade(&self, id: i32) -> RepoFuture<'_, u64> {
+        Box::pin(tipper::delete_cascade(&self.pool, id))
+    }
+    fn get_tipper_dependents(&self, id: i32) -> RepoFuture<'_, Dependents> {
+        Box::pin(tipper::dependents(&self.pool, id))
+    }
+    fn get_tipper(&self, season_id: i32, id: i32) -> RepoFuture<'_, Option<Tipper>> {
+        Box::pin(tipper::get(&self.pool, season_id, id))
+    }
+    fn get_all_tippers(&self, season_id: i32) -> RepoFuture<'_, Vec<Tipper>> {
+        Box::pin(tipper::get_all(&self.pool, season_id))
+    }
+    fn get_active_tippers(&self, season_id: i32) -> RepoFuture<'_, Vec<Tipper>> {
+        Box::pin(tipper::get_active(&self.pool, season_id))
+    }
+    fn set_tipper_active(&self, season_id: i32, id: i32, active: bool) -> RepoFuture<'_, u64> {
+        Box::pin(tipper::set_active(&self.pool, season_id, id, active))
+    }
+}
+
+impl SeasonRepository for DbRepository {
+    fn get_all_seasons(&self) -> RepoFuture<'_, Vec<Season>> {
+        Box::pin(season::get_all(&self.pool))
+    }
+    fn start_season(&self, name: String, keep_teams: bool, keep_tippers: bool) -> RepoFuture<'_, Season> {
+        Box::pin(season::start(&self.pool, name, keep_teams, keep_tippers))
+    }
+}
+
+impl CompetitionRepository for DbRepository {
+    fn get_all_competitions(&self) -> RepoFuture<'_, Vec<Competition>> {
+        Box::pin(competition::get_all(&self.pool))
+    }
+    fn insert_competition(&self, name: String) -> RepoFuture<'_, Competition> {
+        Box::pin(competition::insert(&self.pool, name))
+    }
+    fn update_competition_rules(&self, id: i32, lockout: Lockout, late_tips: LateTips) -> RepoFuture<'_, u64> {
+        Box::pin(competition::update_rules(&self.pool, id, lockout, late_tips))
+    }
+    fn get_competition_members(&self, competition_id: i32) -> RepoFuture<'_, Vec<i32>> {
+        Box::pin(competition::get_members(&self.pool, competition_id))
+    }
+    fn set_competition_member(&self, competition_id: i32, tipper_id: i32, member: bool) -> RepoFuture<'_, u64> {
+        Box::pin(competition::set_member(&self.pool, competition_id, tipper_id, member))
+    }
+}
+
+impl RoundRepository for DbRepository {
+    fn insert_round(&self, season_id: i32, round_number: i32, start_date: NaiveDate, end_date: NaiveDate) -> RepoFuture<'_, i32> {
+        Box::pin(round::insert(&self.pool, season_id, round_number, start_date, end_date))
+    }
+    fn insert_fixture<'a>(&'a self, season_id: i32, fixture: &'a [FixtureRound]) -> RepoFuture<'a, Vec<i32>> {
+        Box::pin(round::insert_fixture(&self.pool, season_id, fixture))
+    }
+    fn update_round(&self, id: i32, round_number: i32, start_date: NaiveDate, end_date: NaiveDate) -> RepoFuture<'_, u64> {
+        Box::pin(round::update(&self.pool, id, round_number, start_date, end_date))
+    }
+    fn save_round<'a>(&'a self, season_id: i32, id: Option<i32>, round_number: i32, start_date: NaiveDate, end_date: NaiveDate,
+                      games: &'a [(i32, GameRecord)], byes: &'a [i32]) -> RepoFuture<'a, (i32, Vec<i32>)> {
+        Box::pin(round::save(&self.pool, season_id, id, round_number, start_date, end_date, games, byes))
+    }
+    fn delete_round(&self, id: i32) -> RepoFuture<'_, u64> {
+        Box::pin(round::delete(&self.pool, id))
+    }
+    fn delete_round_cascade(&self, id: i32) -> RepoFuture<'_, u64> {
+        Box::pin(round::delete_cascade(&self.pool, id))
+    }
+    fn get_round_dependents(&self, id: i32) -> RepoFuture<'_, Dependents> {
+        Box::pin(round::dependents(&self.pool, id))
+    }
+    fn get_round(&self, id: i32) -> RepoFuture<'_, Option<Round>> {
+        Box::pin(round::get(&self.pool, id))
+    }
+    fn get_last_round(&self, season_id: i32) -> RepoFuture<'_, Option<Round>> {
+        Box::pin(round::get_last_round(&self.pool, season_id))
+    }
+    fn get_all_rounds(&self, season_id: i32) -> RepoFuture<'_, Vec<Round>> {
+        Box::pin(round::get_all(&self.pool, season_id))
+    }
+}
+
+impl GameRepository for DbRepository {
+    fn insert_game(&self, game: GameRecord) -> RepoFuture<'_, i32> {
+        Box::pin(game::insert(&self.pool, game))
+    }
+    fn update_game(&self, id: i32, game: GameRecord) -> RepoFuture<'_, u64> {
+        Box::pin(game::update(&self.pool, id, game))
+    }
+    fn save_results<'a>(&'a self, results: &'a [(i32, i32, i32)]) -> RepoFuture<'a, u64> {
+        Box::pin(game::save_results(&self.pool, results))
+    }
+    fn delete_game(&self, id: i32) -> RepoFuture<'_, u64> {
+        Box::pin(game::delete(&self.pool, id))
+    }
+    fn get_game_dependents(&self, id: i32) -> RepoFuture<'_, Dependents> {
+        Box::pin(game::dependents(&self.pool, id))
+    }
+    fn get_game(&self, id: i32) -> RepoFuture<'_, Option<Game>> {
+        Box::pin(game::get(&self.pool, id))
+    }
+    fn get_games_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Game>> {
+        Box::pin(game::get_for_round(&self.pool, round_id))
+    }
+    fn get_games_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Game>> {
+        Box::pin(game::get_for_season(&self.pool, season_id))
+    }
+    fn get_all_games(&self) -> RepoFuture<'_, Vec<Game>> {
+        Box::pin(game::get_all(&self.pool))
+    }
+}
+
+impl TipRepository for DbRepository {
+    fn save_tips<'a>(&'a self, tips: &'a [Tip]) -> RepoFuture<'a, u64> {
+        Box::pin(tip::save_all(&self.pool, tips))
+    }
+    fn get_tips_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Tip>> {
+        Box::pin(tip::get_for_round(&self.pool, round_id))
+    }
+    fn get_tips_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Tip>> {
+        Box::pin(tip::get_for_season(&self.pool, season_id))
+    }
+}
+
+impl ByeRepository for DbRepository {
+    fn get_byes_for_round(&self, round_id: i32) -> RepoFuture<'_, Vec<Bye>> {
+        Box::pin(bye::get_for_round(&self.pool, round_id))
+    }
+    fn get_byes_for_season(&self, season_id: i32) -> RepoFuture<'_, Vec<Bye>> {
+        Box::pin(bye::get_for_season(&self.pool, season_id))
+    }
+}
+
+thread_local! {
+    static INSTALLED: RefCell<Option<Rc<dyn Repository>>> = const { RefCell::new(None) };
+}
+
+/// Keep the competition in another repository, such as a
+/// [`MemoryRepository`](crate::model::memory::MemoryRepository), instead of the database.
+pub fn install(repository: Rc<dyn Repository>) {
+    INSTALLED.with_borrow_mut(|installed| installed.replace(repository));
+}
+
+/// The repository the competition is kept in. Unless another has been installed this is the
+/// database, which might not be reachable.
+pub fn repository() -> Rc<dyn Repository> {
+    INSTALLED.with_borrow(|installed| installed.clone())
+        .unwrap_or_else(|| Rc::new(DbRepository::new(db::manager().pool())))
+}
+
+/// The repository, or None while the database cannot be reached.
+pub fn connected_repository() -> Option<Rc<dyn Repository>> {
+    match INSTALLED.with_borrow(|installed| installed.clone()) {
+        Some(repository) => Some(repository),
+        None => db::connected_pool().map(|pool| Rc::new(DbRepository::new(pool)) as Rc<dyn Repository>),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::memory::MemoryRepository;
+    use async_std::task::block_on;
+
+    #[test]
+    fn test_installed_repository_is_used() {
+        install(Rc::new(MemoryRepository::new()));
+        block_on(async {
+            let season = repository().start_season("2025".to_string(), true, true).await.unwrap();
+            let team = repository().insert_team("Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let teams = connected_repository().unwrap().get_all_teams(season.id).await.unwrap();
+            assert_eq!(teams.iter().map(|t| t.id()).collect::<Vec<_>>(), vec![team.id()]);
+        });
+    }
+}
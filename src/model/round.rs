@@ -24,9 +24,14 @@
 use adw::subclass::prelude::ObjectSubclassIsExt;
 use adw::{gio, glib};
 use chrono::NaiveDate;
+use crate::util::fixture::FixtureRound;
+use crate::event::while_loading;
+use crate::model::error::ModelError;
+use crate::model::season;
+use crate::model::repository::connected_repository;
+use crate::model::repository::{Dependents, GameRecord};
+use crate::util::db::{with_pool, DbPool};
 use log::error;
-use sqlx::{PgPool, Row};
-use sqlx::postgres::PgRow;
 
 // To use the Round in a Gio::ListModel it needs to ba a glib::Object, so we do all this fancy subclassing stuff
 // Public part of the Model type.
@@ -81,6 +86,29 @@ impl Rounds {
     {
         glib::Object::new()
     }
+
+    /// Fetch the rounds of the current season replacing what the list holds and telling its views
+    /// what changed. The list is left empty while the database is unavailable.
+    pub async fn load(&self) {
+        let Some(repository) = connected_repository() else {
+            return;
+        };
+        let Some(season_id) = season::current() else {
+            self.imp().replace_all(vec![]);
+            return;
+        };
+        match while_loading(repository.get_all_rounds(season_id)).await {
+            Ok(list) => self.imp().replace_all(list),
+            Err(err) => error!("Error getting all rounds: {}", err),
+        }
+    }
+
+    /// A new list, once it has been loaded.
+    pub async fn loaded() -> Rounds {
+        let rounds = Rounds::new();
+        rounds.load().await;
+        rounds
+    }
 }
 
 glib::wrapper! {
@@ -115,15 +143,13 @@ impl Playdays {
 }
 
 mod imp {
-    use crate::model::round::get_all;
-    use crate::util::db;
     use adw::glib::{clone, MainContext, Object};
     use adw::prelude::{ListModelExt, StaticType};
     use adw::subclass::prelude::ObjectSubclassExt;
     use adw::subclass::prelude::{ListModelImpl, ObjectImpl, ObjectImplExt, ObjectSubclass};
     use adw::{gio, glib};
     use chrono::{Local, NaiveDate};
-    use log::{error, info};
+    use log::info;
     use std::cell::RefCell;
     use std::ops::{Deref, Sub};
     use std::sync::{Arc, RwLock};
@@ -172,6 +198,16 @@ mod imp {
     }
 
     impl Rounds {
+        pub(super) fn replace_all(&self, list: Vec<crate::model::round::Round>) {
+            let (removed, added) = {
+                let mut rounds = self.rounds.write().expect("Can't get lock on rounds cache");
+                let removed = rounds.len() as u32;
+                *rounds = list;
+                (removed, rounds.len() as u32)
+            };
+            self.obj().items_changed(0, removed, added);
+        }
+
         pub fn round_at(&self, position: u32) -> Option<crate::model::round::Round> {
             let map = self
                 .rounds
@@ -191,22 +227,6 @@ mod imp {
     impl ObjectImpl for Rounds {
         fn constructed(&self) {
             self.parent_constructed();
-
-            let pool = db::manager().pool();
-            let rounds = async_std::task::block_on( async move {
-                get_all(pool).await
-            });
-            match rounds {
-                Ok(round_list) => {
-                    let mut binding = self.rounds.write().expect("Can't get lock on rounds cache");
-                    for r in round_list.into_iter() {
-                        binding.push(r);
-                    }
-                }
-                Err(err) => {
-                    error!("Error getting all rounds: {}", err);
-                }
-            }
         }
     }
 
@@ -370,86 +390,335 @@ mod imp {
 }
 
 pub async fn insert(
-    pool: &PgPool,
+    pool: &DbPool,
+    season_id: i32,
     round_number: i32,
     start_date: NaiveDate,
     end_date: NaiveDate,
-) -> Result<i32, String> {
-    let result = sqlx::query(
-        "INSERT INTO rounds (round_number, start_date, end_date) VALUES ($1, $2, $3) RETURNING round_id",
-    )
-        .bind(round_number)
-        .bind(start_date)
-        .bind(end_date)
-        .fetch_one(pool)
-        .await;
+) -> Result<i32, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_scalar::<_, i32>(
+            "INSERT INTO rounds (season_id, round_number, start_date, end_date) VALUES ($1, $2, $3, $4) RETURNING round_id",
+        )
+            .bind(season_id)
+            .bind(round_number)
+            .bind(start_date)
+            .bind(end_date)
+            .fetch_one(pool)
+            .await
+    });
 
     match result {
-        Ok(row) => {
-            let id = row.get::<i32, _>(0);
-            Ok(id)
-        }
+        Ok(id) => Ok(id),
         Err(e) => {
             error!("Error inserting round: {}", e);
-            Err(format!("Error inserting round: {}", e))
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Insert a draw into a season, creating every round with its games and byes in a single
+/// transaction. A round whose number is already in the season has the games and byes added to it,
+/// and its dates widened to take them in. A team that had a bye in that round loses it when one of
+/// the new games is theirs. Returns the round ids.
+pub async fn insert_fixture(pool: &DbPool, season_id: i32, fixture: &[FixtureRound]) -> Result<Vec<i32>, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        let mut round_ids = Vec::with_capacity(fixture.len());
+        for round in fixture {
+            let existing = sqlx::query_scalar::<_, i32>(
+                "SELECT round_id FROM rounds WHERE season_id=$1 AND round_number=$2",
+            )
+                .bind(season_id)
+                .bind(round.number)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let round_id = match existing {
+                Some(round_id) => {
+                    sqlx::query(
+                        "UPDATE rounds SET start_date = CASE WHEN start_date > $1 THEN $1 ELSE start_date END, \
+                         end_date = CASE WHEN end_date < $2 THEN $2 ELSE end_date END WHERE round_id=$3",
+                    )
+                        .bind(round.start_date)
+                        .bind(round.end_date)
+                        .bind(round_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    round_id
+                }
+                None => sqlx::query_scalar::<_, i32>(
+                    "INSERT INTO rounds (season_id, round_number, start_date, end_date) VALUES ($1, $2, $3, $4) RETURNING round_id",
+                )
+                    .bind(season_id)
+                    .bind(round.number)
+                    .bind(round.start_date)
+                    .bind(round.end_date)
+                    .fetch_one(&mut *tx)
+                    .await?,
+            };
+            for game in &round.games {
+                sqlx::query(
+                    "INSERT INTO games (round_id, home_team_id, away_team_id, game_date, kickoff, venue) VALUES ($1, $2, $3, $4, $5, $6)",
+                )
+                    .bind(round_id)
+                    .bind(game.home_team_id)
+                    .bind(game.away_team_id)
+                    .bind(game.game_date)
+                    .bind(game.kickoff)
+                    .bind(game.venue.as_deref())
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("DELETE FROM byes WHERE round_id=$1 AND team_id IN ($2, $3)")
+                    .bind(round_id)
+                    .bind(game.home_team_id)
+                    .bind(game.away_team_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            for team_id in &round.byes {
+                sqlx::query("INSERT INTO byes (round_id, team_id) VALUES ($1, $2)")
+                    .bind(round_id)
+                    .bind(team_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            round_ids.push(round_id);
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(round_ids)
+    }.await);
+
+    match result {
+        Ok(round_ids) => Ok(round_ids),
+        Err(e) => {
+            error!("Error inserting draw: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Save a round with its games and byes in a single transaction, so a failure part way through
+/// leaves the database as it was. A round without an id is inserted into the season, and games
+/// with an id of zero or less are inserted. Returns the round id and the id of each game in the
+/// order given.
+#[allow(clippy::too_many_arguments)]
+pub async fn save(
+    pool: &DbPool,
+    season_id: i32,
+    id: Option<i32>,
+    round_number: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    games: &[(i32, GameRecord)],
+    byes: &[i32],
+) -> Result<(i32, Vec<i32>), ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        let round_id = match id {
+            Some(id) => {
+                let updated = sqlx::query(
+                    "UPDATE rounds SET round_number=$1, start_date=$2, end_date=$3 WHERE round_id=$4",
+                )
+                    .bind(round_number)
+                    .bind(start_date)
+                    .bind(end_date)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                if updated.rows_affected() == 0 {
+                    return Err(sqlx::Error::RowNotFound);
+                }
+                id
+            }
+            None => sqlx::query_scalar::<_, i32>(
+                "INSERT INTO rounds (season_id, round_number, start_date, end_date) VALUES ($1, $2, $3, $4) RETURNING round_id",
+            )
+                .bind(season_id)
+                .bind(round_number)
+                .bind(start_date)
+                .bind(end_date)
+                .fetch_one(&mut *tx)
+                .await?,
+        };
+
+        let mut game_ids = Vec::with_capacity(games.len());
+        for (game_id, game) in games {
+            let game_id = if *game_id > 0 {
+                let updated = sqlx::query(
+                    "UPDATE games SET round_id=$1, home_team_id=$2, away_team_id=$3, game_date=$4, \
+                     home_team_score=$5, away_team_score=$6, kickoff=$7 WHERE game_id=$8",
+                )
+                    .bind(round_id)
+                    .bind(game.home_team_id)
+                    .bind(game.away_team_id)
+                    .bind(game.game_date)
+                    .bind(game.home_team_score)
+                    .bind(game.away_team_score)
+                    .bind(game.kickoff)
+                    .bind(game_id)
+                    .execute(&mut *tx)
+                    .await?;
+                if updated.rows_affected() == 0 {
+                    return Err(sqlx::Error::RowNotFound);
+                }
+                *game_id
+            } else {
+                sqlx::query_scalar::<_, i32>(
+                    "INSERT INTO games (round_id, home_team_id, away_team_id, game_date, home_team_score, away_team_score, kickoff) \
+                     VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING game_id",
+                )
+                    .bind(round_id)
+                    .bind(game.home_team_id)
+                    .bind(game.away_team_id)
+                    .bind(game.game_date)
+                    .bind(game.home_team_score)
+                    .bind(game.away_team_score)
+                    .bind(game.kickoff)
+                    .fetch_one(&mut *tx)
+                    .await?
+            };
+            game_ids.push(game_id);
+        }
+
+        sqlx::query("DELETE FROM byes WHERE round_id=$1")
+            .bind(round_id)
+            .execute(&mut *tx)
+            .await?;
+        for team_id in byes {
+            sqlx::query("INSERT INTO byes (round_id, team_id) VALUES ($1, $2)")
+                .bind(round_id)
+                .bind(team_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>((round_id, game_ids))
+    }.await);
+
+    match result {
+        Ok(saved) => Ok(saved),
+        Err(e) => {
+            error!("Error saving round: {}", e);
+            Err(ModelError::from(e))
         }
     }
 }
 
 pub async fn update(
-    pool: &PgPool,
+    pool: &DbPool,
     id: i32,
     round_number: i32,
     start_date: NaiveDate,
     end_date: NaiveDate,
-) -> Result<u64, String> {
-    let result = sqlx::query(
-        "UPDATE rounds SET round_number=$1, start_date=$2, end_date=$3 WHERE round_id=$4",
-    )
-        .bind(round_number)
-        .bind(start_date)
-        .bind(end_date)
-        .bind(id)
-        .execute(pool)
-        .await;
+) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query(
+            "UPDATE rounds SET round_number=$1, start_date=$2, end_date=$3 WHERE round_id=$4",
+        )
+            .bind(round_number)
+            .bind(start_date)
+            .bind(end_date)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
 
     match result {
-        Ok(result) => Ok(result.rows_affected()),
+        Ok(count) => Ok(count),
         Err(e) => {
             error!("Error updating round: {}", e);
-            Err(format!("Error updating round: {}", e))
+            Err(ModelError::from(e))
         }
     }
 }
 
-pub async fn delete(pool: &PgPool, id: i32) -> Result<u64, String> {
-    let result = sqlx::query("DELETE FROM rounds WHERE round_id=$1")
-        .bind(id)
-        .execute(pool)
-        .await;
+pub async fn delete(pool: &DbPool, id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query("DELETE FROM rounds WHERE round_id=$1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
 
     match result {
-        Ok(result) => Ok(result.rows_affected()),
+        Ok(count) => Ok(count),
         Err(e) => {
             error!("Error deleting round: {}", e);
-            Err(format!("Error deleting round: {}", e))
+            Err(ModelError::from_delete(e))
         }
     }
 }
 
-fn build_round(row: PgRow) -> Round {
-    let round_id = row.get::<i32, _>(0);
-    let round_number = row.get::<i32, _>(1);
-    let start_date = row.get::<NaiveDate, _>(2);
-    let end_date = row.get::<NaiveDate, _>(3);
+type RoundRow = (i32, i32, NaiveDate, NaiveDate);
+
+fn build_round((round_id, round_number, start_date, end_date): RoundRow) -> Round {
     Round::new(round_id, round_number, start_date, end_date)
 }
 
-pub async fn get(pool: &PgPool, id: i32) -> Result<Option<Round>, String> {
-    let result = sqlx::query("SELECT round_id, round_number, start_date, end_date FROM rounds WHERE round_id=$1")
-        .bind(id)
-        .fetch_optional(pool)
-        .await;
+/// Count the games and byes in a round and the tips made on its games.
+pub async fn dependents(pool: &DbPool, id: i32) -> Result<Dependents, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i64, i64, i64)>(
+            "SELECT (SELECT COUNT(*) FROM games WHERE round_id=$1), \
+             (SELECT COUNT(*) FROM byes WHERE round_id=$1), \
+             (SELECT COUNT(*) FROM tips WHERE game_id IN (SELECT game_id FROM games WHERE round_id=$1))",
+        )
+            .bind(id)
+            .fetch_one(pool)
+            .await
+    });
+
+    match result {
+        Ok((games, byes, tips)) => Ok(Dependents { games, byes, tips }),
+        Err(e) => {
+            error!("Error counting what is in round: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Delete a round along with its games, its byes and the tips on its games.
+pub async fn delete_cascade(pool: &DbPool, id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM tips WHERE game_id IN (SELECT game_id FROM games WHERE round_id=$1)")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM games WHERE round_id=$1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM byes WHERE round_id=$1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM rounds WHERE round_id=$1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(result.rows_affected())
+    }.await);
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error deleting round: {}", e);
+            Err(ModelError::from_delete(e))
+        }
+    }
+}
+
+pub async fn get(pool: &DbPool, id: i32) -> Result<Option<Round>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, RoundRow>("SELECT round_id, round_number, start_date, end_date FROM rounds WHERE round_id=$1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    });
 
     match result {
         Ok(row) => match row {
@@ -460,15 +729,19 @@ pub async fn get(pool: &PgPool, id: i32) -> Result<Option<Round>, String> {
         },
         Err(e) => {
             error!("Error getting round: {}", e);
-            Err(format!("Error getting round: {}", e))
+            Err(ModelError::from(e))
         }
     }
 }
 
-pub async fn get_last_round (pool: &PgPool) -> Result<Option<Round>, String> {
-    let result = sqlx::query("SELECT round_id, round_number, start_date, end_date FROM rounds ORDER BY round_number DESC LIMIT 1")
-        .fetch_optional(pool)
-        .await;
+/// The highest numbered round of a season.
+pub async fn get_last_round (pool: &DbPool, season_id: i32) -> Result<Option<Round>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, RoundRow>("SELECT round_id, round_number, start_date, end_date FROM rounds WHERE season_id=$1 ORDER BY round_number DESC LIMIT 1")
+            .bind(season_id)
+            .fetch_optional(pool)
+            .await
+    });
 
     match result {
         Ok(row) => match row {
@@ -479,15 +752,19 @@ pub async fn get_last_round (pool: &PgPool) -> Result<Option<Round>, String> {
         },
         Err(e) => {
             error!("Error getting round: {}", e);
-            Err(format!("Error getting round: {}", e))
+            Err(ModelError::from(e))
         }
     }
 }
 
-pub async fn get_all(pool: &PgPool) -> Result<Vec<Round>, String> {
-    let result = sqlx::query("SELECT round_id, round_number, start_date, end_date FROM rounds ORDER BY round_number")
-        .fetch_all(pool)
-        .await;
+/// The rounds of a season, in order.
+pub async fn get_all(pool: &DbPool, season_id: i32) -> Result<Vec<Round>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, RoundRow>("SELECT round_id, round_number, start_date, end_date FROM rounds WHERE season_id=$1 ORDER BY round_number")
+            .bind(season_id)
+            .fetch_all(pool)
+            .await
+    });
 
     match result {
         Ok(rows) => {
@@ -499,7 +776,82 @@ pub async fn get_all(pool: &PgPool) -> Result<Vec<Round>, String> {
         }
         Err(e) => {
             error!("Error getting all rounds: {}", e);
-            Err(format!("Error getting all rounds: {}", e))
+            Err(ModelError::from(e))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{bye, season, team};
+    use crate::util::fixture::FixtureGame;
+    use crate::util::test_utils::tests::memory_pool;
+    use async_std::task::block_on;
+
+    #[test]
+    fn test_fixture_keeps_venue() {
+        block_on(async {
+            let pool = &memory_pool().await;
+            let home = team::insert(pool, "Geelong".to_string(), "Cats".to_string()).await.unwrap();
+            let away = team::insert(pool, "Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let season_id = season::get_all(pool).await.unwrap()[0].id;
+            let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+            let game = FixtureGame {
+                home_team_id: home.id(),
+                away_team_id: away.id(),
+                game_date: date,
+                kickoff: None,
+                venue: Some("Kardinia Park".to_string()),
+            };
+            let round = FixtureRound { number: 1, start_date: date, end_date: date, games: vec![game], byes: vec![] };
+            insert_fixture(pool, season_id, &[round]).await.unwrap();
+
+            let DbPool::Sqlite(sqlite) = pool else { unreachable!() };
+            let venue = sqlx::query_scalar::<_, Option<String>>("SELECT venue FROM games")
+                .fetch_one(sqlite)
+                .await
+                .unwrap();
+            assert_eq!(venue.as_deref(), Some("Kardinia Park"));
+        });
+    }
+
+    #[test]
+    fn test_merged_games_replace_byes() {
+        block_on(async {
+            let pool = &memory_pool().await;
+            let mut team_ids = vec![];
+            for (name, nickname) in [("Geelong", "Cats"), ("Adelaide", "Crows"), ("Carlton", "Blues")] {
+                team_ids.push(team::insert(pool, name.to_string(), nickname.to_string()).await.unwrap().id());
+            }
+            let season_id = season::get_all(pool).await.unwrap()[0].id;
+            let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+            let game = |home_team_id: i32, away_team_id: i32| FixtureGame {
+                home_team_id,
+                away_team_id,
+                game_date: date,
+                kickoff: None,
+                venue: None,
+            };
+            let round = FixtureRound {
+                number: 1,
+                start_date: date,
+                end_date: date,
+                games: vec![game(team_ids[0], team_ids[1])],
+                byes: vec![team_ids[2]],
+            };
+            let round_ids = insert_fixture(pool, season_id, &[round]).await.unwrap();
+
+            // Carlton plays in the merged game, so its bye goes
+            let merged = FixtureRound {
+                number: 1,
+                start_date: date,
+                end_date: date,
+                games: vec![game(team_ids[2], team_ids[0])],
+                byes: vec![],
+            };
+            insert_fixture(pool, season_id, &[merged]).await.unwrap();
+            assert!(bye::get_for_round(pool, round_ids[0]).await.unwrap().is_empty());
+        });
+    }
+}
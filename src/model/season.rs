@@ -0,0 +1,145 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::event;
+use crate::event::Event;
+use crate::model::error::ModelError;
+use crate::util::db::{with_pool, DbPool};
+use log::error;
+use std::cell::Cell;
+
+/// A year of the competition. Rounds, and so their games and tips, belong to a season, while
+/// teams and tippers take part in each season they are kept for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Season {
+    pub id: i32,
+    pub name: String,
+}
+
+impl Season {
+    pub fn new(id: i32, name: String) -> Season {
+        Season { id, name }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<i32>> = const { Cell::new(None) };
+}
+
+/// The season being shown, once one has been chosen.
+pub fn current() -> Option<i32> {
+    CURRENT.get()
+}
+
+/// Show another season, telling the views to reload their rounds.
+pub fn set_current(season_id: i32) {
+    if CURRENT.replace(Some(season_id)) != Some(season_id) {
+        event::manager().notify_listeners(Event::SeasonChanged);
+    }
+}
+
+pub async fn get_all(pool: &DbPool) -> Result<Vec<Season>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, String)>("SELECT season_id, name FROM seasons ORDER BY season_id")
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(|(id, name)| Season::new(id, name)).collect()),
+        Err(e) => {
+            error!("Error getting seasons: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Start a new season. The teams and tippers of the latest season carry on into it unless they are
+/// not kept, in which case the new season starts without them while the old seasons keep them.
+pub async fn start(pool: &DbPool, name: String, keep_teams: bool, keep_tippers: bool) -> Result<Season, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        let id = sqlx::query_scalar::<_, i32>("INSERT INTO seasons (name) VALUES ($1) RETURNING season_id")
+            .bind(name.clone())
+            .fetch_one(&mut *tx)
+            .await?;
+        if keep_teams {
+            sqlx::query(
+                "INSERT INTO season_teams (season_id, team_id) SELECT $1, team_id FROM season_teams \
+                 WHERE season_id = (SELECT MAX(season_id) FROM seasons WHERE season_id < $1)",
+            )
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        if keep_tippers {
+            sqlx::query(
+                "INSERT INTO season_tippers (season_id, tipper_id) SELECT $1, tipper_id FROM season_tippers \
+                 WHERE season_id = (SELECT MAX(season_id) FROM seasons WHERE season_id < $1)",
+            )
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(id)
+    }.await);
+
+    match result {
+        Ok(id) => Ok(Season::new(id, name)),
+        Err(e) => {
+            error!("Error starting season: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{team, tipper};
+    use crate::util::test_utils::tests::memory_pool;
+    use async_std::task::block_on;
+
+    #[test]
+    fn test_earlier_seasons_keep_their_members() {
+        block_on(async {
+            let pool = &memory_pool().await;
+            let first = get_all(pool).await.unwrap()[0].id;
+            let carlton = team::insert(pool, "Carlton".to_string(), "Blues".to_string()).await.unwrap();
+            let geelong = team::insert(pool, "Geelong".to_string(), "Cats".to_string()).await.unwrap();
+            tipper::insert(pool, "Trevor".to_string(), "trevor@example.com".to_string()).await.unwrap();
+            team::set_active(pool, first, geelong.id(), false).await.unwrap();
+
+            let second = start(pool, "Next".to_string(), true, false).await.unwrap();
+            let kept: Vec<i32> = team::get_active(pool, second.id).await.unwrap().iter().map(|t| t.id()).collect();
+            assert_eq!(kept, vec![carlton.id()]);
+            assert!(tipper::get_active(pool, second.id).await.unwrap().is_empty());
+
+            // Leaving the tipper out of the new season does not archive them in the first
+            assert_eq!(tipper::get_active(pool, first).await.unwrap().len(), 1);
+            team::set_active(pool, second.id, carlton.id(), false).await.unwrap();
+            assert!(team::get(pool, first, carlton.id()).await.unwrap().unwrap().is_active());
+        });
+    }
+}
@@ -24,7 +24,11 @@
 use adw::subclass::prelude::ObjectSubclassIsExt;
 use adw::{gio, glib};
 use log::error;
-use sqlx::{PgPool, Row};
+use crate::event::while_loading;
+use crate::model::error::ModelError;
+use crate::model::repository::{connected_repository, Dependents};
+use crate::model::season;
+use crate::util::db::{with_pool, DbPool};
 
 // To use the Team in a Gio::ListModel it needs to ba a glib::Object, so we do all this fancy subclassing stuff
 // Public part of the Model type.
@@ -39,6 +43,7 @@ impl Team {
         obj.imp().set_id(team_id);
         obj.imp().set_name(name);
         obj.imp().set_nickname(nickname);
+        obj.imp().set_active(true);
         obj
     }
 
@@ -51,6 +56,10 @@ impl Team {
     pub fn nickname(&self) -> String {
         self.imp().nickname.borrow().clone()
     }
+    /// False once the team has been archived.
+    pub fn is_active(&self) -> bool {
+        self.imp().active.get()
+    }
     pub fn set_id(&self, id: i32) {
         self.imp().set_id(id);
     }
@@ -60,6 +69,9 @@ impl Team {
     pub fn set_nickname(&self, nickname: String) {
         self.imp().set_nickname(nickname);
     }
+    pub fn set_active(&self, active: bool) {
+        self.imp().set_active(active);
+    }
 }
 
 glib::wrapper! {
@@ -70,18 +82,69 @@ impl Teams {
     pub fn new() -> Teams {
         glib::Object::new()
     }
+
+    /// Whether archived teams are loaded as well as active ones.
+    pub fn set_include_archived(&self, include_archived: bool) {
+        self.imp().include_archived.set(include_archived);
+    }
+
+    /// Fetch the teams of the current season replacing what the list holds and telling its views
+    /// what changed. The list is left empty while the database is unavailable.
+    pub async fn load(&self) {
+        let Some(repository) = connected_repository() else {
+            return;
+        };
+        let Some(season_id) = season::current() else {
+            self.imp().replace_all(vec![]);
+            return;
+        };
+        let list = if self.imp().include_archived.get() {
+            while_loading(repository.get_all_teams(season_id)).await
+        } else {
+            while_loading(repository.get_active_teams(season_id)).await
+        };
+        match list {
+            Ok(list) => self.imp().replace_all(list),
+            Err(err) => error!("Error getting all teams: {}", err),
+        }
+    }
+
+    /// A new list of the teams in the current season, once it has been loaded.
+    pub async fn loaded() -> Teams {
+        let teams = Teams::new();
+        teams.load().await;
+        teams
+    }
+
+    /// A new list of every team, archived or not, for looking up names in past rounds.
+    pub async fn loaded_with_archived() -> Teams {
+        let teams = Teams::new();
+        teams.set_include_archived(true);
+        teams.load().await;
+        teams
+    }
+
+    /// A new list of the teams in the current season and any others among the given ones, so a past
+    /// round can still show the teams it was played by.
+    pub async fn loaded_including(team_ids: &[i32]) -> Teams {
+        let teams = Teams::loaded_with_archived().await;
+        let list: Vec<Team> = teams.imp().teams.read().expect("Unable to get a lock on teams").iter()
+            .filter(|t| t.is_active() || team_ids.contains(&t.id()))
+            .cloned()
+            .collect();
+        teams.set_include_archived(false);
+        teams.imp().replace_all(list);
+        teams
+    }
 }
 
 mod imp {
-    use crate::model::team::get_all;
-    use crate::util::db;
     use adw::gio;
     use adw::glib::{clone, Object};
-    use adw::prelude::StaticType;
-    use adw::subclass::prelude::{ListModelImpl, ObjectImpl, ObjectImplExt, ObjectSubclass};
+    use adw::prelude::{ListModelExt, StaticType};
+    use adw::subclass::prelude::{ListModelImpl, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt};
     use gtk::glib;
-    use log::error;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::sync::{Arc, RwLock};
 
     #[derive(Default)]
@@ -89,6 +152,7 @@ mod imp {
         pub(crate) id: RefCell<i32>,
         pub(super) name: RefCell<String>,
         pub(super) nickname: RefCell<String>,
+        pub(super) active: Cell<bool>,
     }
 
     impl Team {
@@ -103,6 +167,10 @@ mod imp {
         pub(super) fn set_nickname(&self, nickname: String) {
             self.nickname.replace(nickname);
         }
+
+        pub(super) fn set_active(&self, active: bool) {
+            self.active.set(active);
+        }
     }
 
     /// Basic declaration of our type for the GObject type system
@@ -121,9 +189,19 @@ mod imp {
     #[derive(Default)]
     pub struct Teams {
         pub teams: Arc<RwLock<Vec<crate::model::team::Team>>>,
+        pub(super) include_archived: Cell<bool>,
     }
 
     impl Teams {
+        pub(super) fn replace_all(&self, list: Vec<crate::model::team::Team>) {
+            let (removed, added) = {
+                let mut teams = self.teams.write().expect("Can't get lock on teams cache");
+                let removed = teams.len() as u32;
+                *teams = list;
+                (removed, teams.len() as u32)
+            };
+            self.obj().items_changed(0, removed, added);
+        }
 
         pub fn team_at(&self, position: u32) -> Option<crate::model::team::Team> {
             let map = self
@@ -145,22 +223,6 @@ mod imp {
     impl ObjectImpl for Teams {
         fn constructed(&self) {
             self.parent_constructed();
-
-            let pool = db::manager().pool();
-            let teams = async_std::task::block_on(async move {
-                get_all(pool).await
-            });
-            match teams {
-                Ok(team_list) => {
-                    let mut binding = self.teams.write().expect("Can't get lock on teams cache");
-                    for t in team_list.into_iter() {
-                        binding.push(t);
-                    }
-                }
-                Err(err) => {
-                    error!("Error getting all teams: {}", err);
-                }
-            }
         }
     }
 
@@ -186,71 +248,178 @@ mod imp {
 
 }
 
-pub async fn insert(pool: &PgPool, name: String, nickname: String) -> Result<crate::model::team::Team, String> {
-    let result = sqlx::query("INSERT INTO teams (name, nickname) VALUES ($1, $2) RETURNING team_id")
-        .bind(name.clone())
-        .bind(nickname.clone())
-        .fetch_one(pool)
-        .await;
+/// Add a team, taking part in the latest season. Either both are saved or neither is.
+pub async fn insert(pool: &DbPool, name: String, nickname: String) -> Result<crate::model::team::Team, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        let id = sqlx::query_scalar::<_, i32>("INSERT INTO teams (name, nickname) VALUES ($1, $2) RETURNING team_id")
+            .bind(name.clone())
+            .bind(nickname.clone())
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO season_teams (season_id, team_id) SELECT season_id, $1 FROM seasons ORDER BY season_id DESC LIMIT 1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(id)
+    }.await);
     match result {
-        Ok(row) => {
-            let id = row.get::<i32, _>(0);
+        Ok(id) => {
             Ok(crate::model::team::Team::new(id, name, nickname))
         },
         Err(e) => {
             error!("Error inserting team: {}", e);
-            Err(format!("Error inserting team: {}", e))
+            Err(ModelError::from(e))
         },
     }
 }
 
-pub async fn update(pool: &PgPool, id: i32, name: String, nickname: String) -> Result<u64, String> {
-    let result = sqlx::query("UPDATE teams SET name=$1, nickname=$2 WHERE team_id = $3")
-        .bind(name.clone())
-        .bind(nickname.clone())
-        .bind(id)
-        .execute(pool)
-        .await;
+pub async fn update(pool: &DbPool, id: i32, name: String, nickname: String) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query("UPDATE teams SET name=$1, nickname=$2 WHERE team_id = $3")
+            .bind(name.clone())
+            .bind(nickname.clone())
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
     match result {
-        Ok(result) => {
-            Ok(result.rows_affected())
+        Ok(count) => {
+            Ok(count)
         },
         Err(e) => {
             error!("Error updating team: {}", e);
-            Err(format!("Error updating team: {}", e))
+            Err(ModelError::from(e))
         },
     }
 }
 
-pub async fn delete(pool: &PgPool, id: i32) -> Result<u64, String> {
-    let result = sqlx::query("DELETE FROM teams WHERE team_id = $1")
-        .bind(id)
-        .execute(pool)
-        .await;
+pub async fn delete(pool: &DbPool, id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query("DELETE FROM teams WHERE team_id = $1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
     match result {
-        Ok(result) => {
-            Ok(result.rows_affected())
+        Ok(count) => {
+            Ok(count)
         },
         Err(e) => {
             error!("Error deleting team: {}", e);
-            Err(format!("Error deleting team: {}", e))
+            Err(ModelError::from_delete(e))
+        },
+    }
+}
+
+/// Count the games a team plays in, its byes and the tips made on its games.
+pub async fn dependents(pool: &DbPool, id: i32) -> Result<Dependents, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i64, i64, i64)>(
+            "SELECT (SELECT COUNT(*) FROM games WHERE home_team_id=$1 OR away_team_id=$1), \
+             (SELECT COUNT(*) FROM byes WHERE team_id=$1), \
+             (SELECT COUNT(*) FROM tips WHERE game_id IN (SELECT game_id FROM games WHERE home_team_id=$1 OR away_team_id=$1))",
+        )
+            .bind(id)
+            .fetch_one(pool)
+            .await
+    });
+
+    match result {
+        Ok((games, byes, tips)) => Ok(Dependents { games, byes, tips }),
+        Err(e) => {
+            error!("Error counting what refers to team: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Delete a team along with the games it plays in, its byes and the tips on its games.
+pub async fn delete_cascade(pool: &DbPool, id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM tips WHERE game_id IN (SELECT game_id FROM games WHERE home_team_id=$1 OR away_team_id=$1)")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM games WHERE home_team_id=$1 OR away_team_id=$1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM byes WHERE team_id=$1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM teams WHERE team_id=$1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(result.rows_affected())
+    }.await);
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error deleting team: {}", e);
+            Err(ModelError::from_delete(e))
+        }
+    }
+}
+
+/// Archive a team in a season, or bring it back. Archived teams keep their games but are left out
+/// of new rounds, and the other seasons are left as they were. Returns the number of seasons
+/// changed, which is zero when the team was already in or out.
+pub async fn set_active(pool: &DbPool, season_id: i32, id: i32, active: bool) -> Result<u64, ModelError> {
+    let sql = if active {
+        "INSERT INTO season_teams (season_id, team_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+    } else {
+        "DELETE FROM season_teams WHERE season_id=$1 AND team_id=$2"
+    };
+    let result = with_pool!(pool, |pool| {
+        sqlx::query(sql)
+            .bind(season_id)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
+    match result {
+        Ok(count) => {
+            Ok(count)
+        },
+        Err(e) => {
+            error!("Error archiving team: {}", e);
+            Err(ModelError::from(e))
         },
     }
 }
 
-pub async fn get(pool: &PgPool, id: i32) -> Result<Option<crate::model::team::Team>, String> {
-    let result = sqlx::query("SELECT team_id, name, nickname FROM teams WHERE team_id = $1")
-        .bind(id)
-        .fetch_optional(pool)
-        .await;
+fn build_team((team_id, name, nickname, active): (i32, String, String, bool)) -> Team {
+    let team = Team::new(team_id, name, nickname);
+    team.set_active(active);
+    team
+}
+
+pub async fn get(pool: &DbPool, season_id: i32, id: i32) -> Result<Option<crate::model::team::Team>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, String, String, bool)>(
+            "SELECT team_id, name, nickname, EXISTS (SELECT 1 FROM season_teams s WHERE s.season_id = $1 AND s.team_id = teams.team_id) \
+             FROM teams WHERE team_id = $2",
+        )
+            .bind(season_id)
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    });
     match result {
         Ok(row) => {
             match row {
                 Some(row) => {
-                    let team_id = row.get::<i32, _>(0);
-                    let name = row.get::<String, _>(1);
-                    let nickname = row.get::<String, _>(2);
-                    Ok(Some(Team::new(team_id, name, nickname)))
+                    Ok(Some(build_team(row)))
                 },
                 None => {
                     Ok(None)
@@ -259,30 +428,47 @@ pub async fn get(pool: &PgPool, id: i32) -> Result<Option<crate::model::team::Te
         },
         Err(e) => {
             error!("Error getting team: {}", e);
-            Err(format!("Error getting team: {}", e))
+            Err(ModelError::from(e))
         },
     }
 }
 
-pub async fn get_all(pool: &PgPool) -> Result<Vec<crate::model::team::Team>, String> {
-    let result =
-        sqlx::query("SELECT team_id, name, nickname FROM teams ORDER BY name")
+/// Every team, including those that have been archived in the season.
+pub async fn get_all(pool: &DbPool, season_id: i32) -> Result<Vec<crate::model::team::Team>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, String, String, bool)>(
+            "SELECT team_id, name, nickname, EXISTS (SELECT 1 FROM season_teams s WHERE s.season_id = $1 AND s.team_id = teams.team_id) \
+             FROM teams ORDER BY name",
+        )
+            .bind(season_id)
             .fetch_all(pool)
-            .await;
+            .await
+    });
     match result {
-        Ok(rows) => {
-            let mut teams = Vec::new();
-            for row in rows {
-                let team_id = row.get::<i32, _>(0);
-                let name = row.get::<String, _>(1);
-                let nickname = row.get::<String, _>(2);
-                teams.push(Team::new(team_id, name, nickname));
-            }
-            Ok(teams)
-        },
+        Ok(rows) => Ok(rows.into_iter().map(build_team).collect()),
         Err(e) => {
             error!("Error getting all teams: {}", e);
-            Err(format!("Error getting all teams: {}", e))
+            Err(ModelError::from(e))
+        },
+    }
+}
+
+/// The teams taking part in the season.
+pub async fn get_active(pool: &DbPool, season_id: i32) -> Result<Vec<crate::model::team::Team>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, String, String, bool)>(
+            "SELECT t.team_id, t.name, t.nickname, TRUE FROM teams t \
+             JOIN season_teams s ON s.team_id = t.team_id WHERE s.season_id = $1 ORDER BY t.name",
+        )
+            .bind(season_id)
+            .fetch_all(pool)
+            .await
+    });
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_team).collect()),
+        Err(e) => {
+            error!("Error getting active teams: {}", e);
+            Err(ModelError::from(e))
         },
     }
 }
@@ -21,14 +21,305 @@
  *      Trevor Campbell
  *
  */
+use crate::model::error::ModelError;
+use crate::util::db::{with_pool, DbPool};
 use chrono::NaiveDateTime;
+use log::error;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Tip {
     pub tip_id: i32,
     pub user_id: i32,
-    pub match_id: i32,
+    pub game_id: i32,
     pub predicted_home_score: i32,
     pub predicted_away_score: i32,
     pub tip_date: Option<NaiveDateTime>,
-}
\ No newline at end of file
+    /// Made after tips on the game had closed.
+    pub late: bool,
+    /// Made by the default tips rule for a tipper who tipped nothing in the round.
+    pub auto: bool,
+}
+
+impl Tip {
+    pub fn new(tip_id: i32, user_id: i32, game_id: i32, predicted_home_score: i32,
+               predicted_away_score: i32, tip_date: Option<NaiveDateTime>) -> Tip {
+        Tip {
+            tip_id,
+            user_id,
+            game_id,
+            predicted_home_score,
+            predicted_away_score,
+            tip_date,
+            late: false,
+            auto: false,
+        }
+    }
+}
+
+/// Insert or update the tip of a tipper for a game. A tipper has at most one tip per game.
+/// The tip is an ordinary one, so any late or automatic mark on an earlier tip is cleared.
+pub async fn upsert(
+    pool: &DbPool,
+    user_id: i32,
+    game_id: i32,
+    predicted_home_score: i32,
+    predicted_away_score: i32,
+) -> Result<i32, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_scalar::<_, i32>(
+            "INSERT INTO tips (user_id, game_id, predicted_home_score, predicted_away_score, tip_date, late, auto) \
+             VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP, FALSE, FALSE) \
+             ON CONFLICT (user_id, game_id) DO UPDATE SET predicted_home_score = EXCLUDED.predicted_home_score, \
+             predicted_away_score = EXCLUDED.predicted_away_score, tip_date = EXCLUDED.tip_date, late = EXCLUDED.late, \
+             auto = EXCLUDED.auto \
+             RETURNING tip_id",
+        )
+            .bind(user_id)
+            .bind(game_id)
+            .bind(predicted_home_score)
+            .bind(predicted_away_score)
+            .fetch_one(pool)
+            .await
+    });
+
+    match result {
+        Ok(tip_id) => Ok(tip_id),
+        Err(e) => {
+            error!("Error saving tip: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Save a set of tips in a single transaction, so either all of them are recorded or none are.
+pub async fn save_all(pool: &DbPool, tips: &[Tip]) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        let mut count = 0;
+        for tip in tips {
+            let result = sqlx::query(
+                "INSERT INTO tips (user_id, game_id, predicted_home_score, predicted_away_score, tip_date, late, auto) \
+                 VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP, $5, $6) \
+                 ON CONFLICT (user_id, game_id) DO UPDATE SET predicted_home_score = EXCLUDED.predicted_home_score, \
+                 predicted_away_score = EXCLUDED.predicted_away_score, tip_date = EXCLUDED.tip_date, late = EXCLUDED.late, \
+                 auto = EXCLUDED.auto",
+            )
+                .bind(tip.user_id)
+                .bind(tip.game_id)
+                .bind(tip.predicted_home_score)
+                .bind(tip.predicted_away_score)
+                .bind(tip.late)
+                .bind(tip.auto)
+                .execute(&mut *tx)
+                .await?;
+            count += result.rows_affected();
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(count)
+    }.await);
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error saving tips: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+pub async fn delete(pool: &DbPool, tip_id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query("DELETE FROM tips WHERE tip_id=$1")
+            .bind(tip_id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error deleting tip: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+pub async fn delete_for_game(pool: &DbPool, game_id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query("DELETE FROM tips WHERE game_id=$1")
+            .bind(game_id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error deleting tips for game {}: {}", game_id, e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+type TipRow = (i32, i32, i32, i32, i32, Option<NaiveDateTime>, bool, bool);
+
+fn build_tip(row: TipRow) -> Tip {
+    let (tip_id, user_id, game_id, predicted_home_score, predicted_away_score, tip_date, late, auto) = row;
+    let mut tip = Tip::new(tip_id, user_id, game_id, predicted_home_score, predicted_away_score, tip_date);
+    tip.late = late;
+    tip.auto = auto;
+    tip
+}
+
+pub async fn get(pool: &DbPool, tip_id: i32) -> Result<Option<Tip>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, TipRow>(
+            "SELECT tip_id, user_id, game_id, predicted_home_score, predicted_away_score, tip_date, late, auto \
+             FROM tips WHERE tip_id=$1",
+        )
+            .bind(tip_id)
+            .fetch_optional(pool)
+            .await
+    });
+
+    match result {
+        Ok(row) => Ok(row.map(build_tip)),
+        Err(e) => {
+            error!("Error getting tip: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+pub async fn get_for_round(pool: &DbPool, round_id: i32) -> Result<Vec<Tip>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, TipRow>(
+            "SELECT t.tip_id, t.user_id, t.game_id, t.predicted_home_score, t.predicted_away_score, t.tip_date, t.late, t.auto \
+             FROM tips t JOIN games g ON g.game_id = t.game_id WHERE g.round_id = $1 ORDER BY t.user_id, g.game_date",
+        )
+            .bind(round_id)
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_tip).collect()),
+        Err(e) => {
+            error!("Error getting tips for round {}: {}", round_id, e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+pub async fn get_for_tipper(pool: &DbPool, user_id: i32) -> Result<Vec<Tip>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, TipRow>(
+            "SELECT t.tip_id, t.user_id, t.game_id, t.predicted_home_score, t.predicted_away_score, t.tip_date, t.late, t.auto \
+             FROM tips t JOIN games g ON g.game_id = t.game_id WHERE t.user_id = $1 ORDER BY g.game_date",
+        )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_tip).collect()),
+        Err(e) => {
+            error!("Error getting tips for tipper {}: {}", user_id, e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+pub async fn get_all(pool: &DbPool) -> Result<Vec<Tip>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, TipRow>(
+            "SELECT tip_id, user_id, game_id, predicted_home_score, predicted_away_score, tip_date, late, auto \
+             FROM tips ORDER BY user_id, game_id",
+        )
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_tip).collect()),
+        Err(e) => {
+            error!("Error getting all tips: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// The tips on every game in a season.
+pub async fn get_for_season(pool: &DbPool, season_id: i32) -> Result<Vec<Tip>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, TipRow>(
+            "SELECT tip_id, user_id, game_id, predicted_home_score, predicted_away_score, tip_date, late, auto \
+             FROM tips WHERE game_id IN (SELECT g.game_id FROM games g JOIN rounds r ON r.round_id = g.round_id \
+             WHERE r.season_id=$1) ORDER BY user_id, game_id",
+        )
+            .bind(season_id)
+            .fetch_all(pool)
+            .await
+    });
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_tip).collect()),
+        Err(e) => {
+            error!("Error getting tips for season: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::repository::GameRecord;
+    use crate::model::{game, round, season, team, tipper};
+    use crate::util::test_utils::tests::memory_pool;
+    use async_std::task::block_on;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_save_and_delete_tip() {
+        block_on(async {
+            let pool = &memory_pool().await;
+            let home = team::insert(pool, "Adelaide".to_string(), "Crows".to_string()).await.unwrap();
+            let away = team::insert(pool, "Geelong".to_string(), "Cats".to_string()).await.unwrap();
+            let tipper = tipper::insert(pool, "Trevor".to_string(), "trevor@example.com".to_string()).await.unwrap();
+            let season_id = season::get_all(pool).await.unwrap()[0].id;
+            let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+            let round_id = round::insert(pool, season_id, 1, date, date).await.unwrap();
+            let game_id = game::insert(pool, GameRecord {
+                round_id,
+                home_team_id: home.id(),
+                away_team_id: away.id(),
+                game_date: date,
+                home_team_score: None,
+                away_team_score: None,
+                kickoff: None,
+            }).await.unwrap();
+
+            let tip_id = upsert(pool, tipper.id(), game_id, 80, 70).await.unwrap();
+            let tip = get(pool, tip_id).await.unwrap().unwrap();
+            assert_eq!((tip.predicted_home_score, tip.predicted_away_score), (80, 70));
+
+            // Saving the same tip again replaces it, and a tip made by hand is never late or automatic
+            let mut late = Tip::new(0, tipper.id(), game_id, 60, 50, None);
+            late.late = true;
+            late.auto = true;
+            assert_eq!(save_all(pool, &[late]).await.unwrap(), 1);
+            assert!(get(pool, tip_id).await.unwrap().is_some_and(|t| t.late && t.auto));
+            assert_eq!(upsert(pool, tipper.id(), game_id, 90, 70).await.unwrap(), tip_id);
+            let tips = get_all(pool).await.unwrap();
+            assert_eq!(tips.len(), 1);
+            assert_eq!((tips[0].predicted_home_score, tips[0].late, tips[0].auto), (90, false, false));
+
+            assert_eq!(delete(pool, tip_id).await.unwrap(), 1);
+            assert!(get(pool, tip_id).await.unwrap().is_none());
+        });
+    }
+}
@@ -24,7 +24,11 @@
 use adw::subclass::prelude::ObjectSubclassIsExt;
 use adw::{gio, glib};
 use log::error;
-use sqlx::{PgPool, Row};
+use crate::event::while_loading;
+use crate::model::error::ModelError;
+use crate::model::repository::{connected_repository, Dependents};
+use crate::model::season;
+use crate::util::db::{with_pool, DbPool};
 
 // To use the Tipper in a Gio::ListModel it needs to ba a glib::Object, so we do all this fancy subclassing stuff
 // Public part of the Model type.
@@ -39,6 +43,7 @@ impl Tipper {
         obj.imp().set_id(tipper_id);
         obj.imp().set_name(name);
         obj.imp().set_email(email);
+        obj.imp().set_active(true);
         obj
     }
 
@@ -51,6 +56,10 @@ impl Tipper {
     pub fn email(&self) -> String {
         self.imp().email.borrow().clone()
     }
+    /// False once the tipper has been archived.
+    pub fn is_active(&self) -> bool {
+        self.imp().active.get()
+    }
     pub fn set_id(&self, id: i32) {
         self.imp().set_id(id);
     }
@@ -60,6 +69,9 @@ impl Tipper {
     pub fn set_email(&self, email: String) {
         self.imp().set_email(email);
     }
+    pub fn set_active(&self, active: bool) {
+        self.imp().set_active(active);
+    }
 }
 
 glib::wrapper! {
@@ -70,18 +82,69 @@ impl Tippers {
     pub fn new() -> Tippers {
         glib::Object::new()
     }
+
+    /// Whether archived tippers are loaded as well as active ones.
+    pub fn set_include_archived(&self, include_archived: bool) {
+        self.imp().include_archived.set(include_archived);
+    }
+
+    /// Only load the tippers in this competition, or every tipper when there is none.
+    pub fn set_competition(&self, competition_id: Option<i32>) {
+        self.imp().competition.set(competition_id);
+    }
+
+    /// Fetch the tippers of the current season replacing what the list holds and telling its views
+    /// what changed. The list is left empty while the database is unavailable.
+    pub async fn load(&self) {
+        let Some(repository) = connected_repository() else {
+            return;
+        };
+        let Some(season_id) = season::current() else {
+            self.imp().replace_all(vec![]);
+            return;
+        };
+        let list = if self.imp().include_archived.get() {
+            while_loading(repository.get_all_tippers(season_id)).await
+        } else {
+            while_loading(repository.get_active_tippers(season_id)).await
+        };
+        let list = match (list, self.imp().competition.get()) {
+            (Ok(list), Some(competition_id)) => while_loading(repository.get_competition_members(competition_id))
+                .await
+                .map(|members| list.into_iter().filter(|t| members.contains(&t.id())).collect()),
+            (list, _) => list,
+        };
+        match list {
+            Ok(list) => self.imp().replace_all(list),
+            Err(err) => error!("Error getting all tippers: {}", err),
+        }
+    }
+
+    /// A new list of the tippers in the current season, once it has been loaded.
+    pub async fn loaded() -> Tippers {
+        let tippers = Tippers::new();
+        tippers.load().await;
+        tippers
+    }
+
+    /// A new list of every tipper in a competition, archived in the current season or not, so past
+    /// tips and scores keep their names.
+    pub async fn loaded_with_archived(competition_id: Option<i32>) -> Tippers {
+        let tippers = Tippers::new();
+        tippers.set_include_archived(true);
+        tippers.set_competition(competition_id);
+        tippers.load().await;
+        tippers
+    }
 }
 
 mod imp {
-    use crate::model::tipper::get_all;
-    use crate::util::db;
     use adw::gio;
     use adw::glib::{clone, Object};
-    use adw::prelude::StaticType;
-    use adw::subclass::prelude::{ListModelImpl, ObjectImpl, ObjectImplExt, ObjectSubclass};
+    use adw::prelude::{ListModelExt, StaticType};
+    use adw::subclass::prelude::{ListModelImpl, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt};
     use gtk::glib;
-    use log::error;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::sync::{Arc, RwLock};
 
     #[derive(Default)]
@@ -89,6 +152,7 @@ mod imp {
         pub(super) tipper_id: RefCell<i32>,
         pub(super) name: RefCell<String>,
         pub(super) email: RefCell<String>,
+        pub(super) active: Cell<bool>,
     }
 
     impl Tipper {
@@ -103,6 +167,10 @@ mod imp {
         pub(super) fn set_email(&self, email: String) {
             self.email.replace(email);
         }
+
+        pub(super) fn set_active(&self, active: bool) {
+            self.active.set(active);
+        }
     }
 
     /// Basic declaration of our type for the GObject type system
@@ -121,9 +189,20 @@ mod imp {
     #[derive(Default)]
     pub struct Tippers {
         pub tippers: Arc<RwLock<Vec<crate::model::tipper::Tipper>>>,
+        pub(super) include_archived: Cell<bool>,
+        pub(super) competition: Cell<Option<i32>>,
     }
 
     impl Tippers {
+        pub(super) fn replace_all(&self, list: Vec<crate::model::tipper::Tipper>) {
+            let (removed, added) = {
+                let mut tippers = self.tippers.write().expect("Can't get lock on tippers cache");
+                let removed = tippers.len() as u32;
+                *tippers = list;
+                (removed, tippers.len() as u32)
+            };
+            self.obj().items_changed(0, removed, added);
+        }
 
         pub fn tipper_at(&self, position: u32) -> Option<crate::model::tipper::Tipper> {
             let map = self
@@ -145,22 +224,6 @@ mod imp {
     impl ObjectImpl for Tippers {
         fn constructed(&self) {
             self.parent_constructed();
-
-            let pool = db::manager().pool();
-            let tippers = async_std::task::block_on(async move {
-                get_all(pool).await
-            });
-            match tippers {
-                Ok(tipper_list) => {
-                    let mut binding = self.tippers.write().expect("Can't get lock on tippers cache");
-                    for t in tipper_list.into_iter() {
-                        binding.push(t);
-                    }
-                }
-                Err(err) => {
-                    error!("Error getting all tippers: {}", err);
-                }
-            }
         }
     }
 
@@ -186,71 +249,197 @@ mod imp {
 
 }
 
-pub async fn insert(pool: &PgPool, name: String, email: String) -> Result<crate::model::tipper::Tipper, String> {
-    let result = sqlx::query("INSERT INTO tippers (name, email) VALUES ($1, $2) RETURNING tipper_id")
-        .bind(name.clone())
-        .bind(email.clone())
-        .fetch_one(pool)
-        .await;
+/// Add a tipper, taking part in the latest season. Either both are saved or neither is.
+pub async fn insert(pool: &DbPool, name: String, email: String) -> Result<crate::model::tipper::Tipper, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        let id = sqlx::query_scalar::<_, i32>("INSERT INTO tippers (name, email) VALUES ($1, $2) RETURNING tipper_id")
+            .bind(name.clone())
+            .bind(email.clone())
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO season_tippers (season_id, tipper_id) SELECT season_id, $1 FROM seasons ORDER BY season_id DESC LIMIT 1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(id)
+    }.await);
     match result {
-        Ok(row) => {
-            let id = row.get::<i32, _>(0);
+        Ok(id) => {
             Ok(crate::model::tipper::Tipper::new(id, name, email))
         },
         Err(e) => {
             error!("Error inserting tipper: {}", e);
-            Err(format!("Error inserting tipper: {}", e))
+            Err(ModelError::from(e))
+        },
+    }
+}
+
+/// Add a tipper as a member of a competition, taking part in the latest season. Either all of it
+/// is saved or none of it is.
+pub async fn insert_in_competition(pool: &DbPool, name: String, email: String, competition_id: i32) -> Result<crate::model::tipper::Tipper, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        let id = sqlx::query_scalar::<_, i32>("INSERT INTO tippers (name, email) VALUES ($1, $2) RETURNING tipper_id")
+            .bind(name.clone())
+            .bind(email.clone())
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO competition_tippers (competition_id, tipper_id) VALUES ($1, $2)")
+            .bind(competition_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO season_tippers (season_id, tipper_id) SELECT season_id, $1 FROM seasons ORDER BY season_id DESC LIMIT 1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(id)
+    }.await);
+    match result {
+        Ok(id) => Ok(crate::model::tipper::Tipper::new(id, name, email)),
+        Err(e) => {
+            error!("Error inserting tipper into competition {}: {}", competition_id, e);
+            Err(ModelError::from(e))
         },
     }
 }
 
-pub async fn update(pool: &PgPool, id: i32, name: String, email: String) -> Result<u64, String> {
-    let result = sqlx::query("UPDATE tippers SET name=$1, email=$2 WHERE tipper_id = $3")
-        .bind(name.clone())
-        .bind(email.clone())
-        .bind(id)
-        .execute(pool)
-        .await;
+pub async fn update(pool: &DbPool, id: i32, name: String, email: String) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query("UPDATE tippers SET name=$1, email=$2 WHERE tipper_id = $3")
+            .bind(name.clone())
+            .bind(email.clone())
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
     match result {
-        Ok(result) => {
-            Ok(result.rows_affected())
+        Ok(count) => {
+            Ok(count)
         },
         Err(e) => {
             error!("Error updating tipper: {}", e);
-            Err(format!("Error updating tipper: {}", e))
+            Err(ModelError::from(e))
         },
     }
 }
 
-pub async fn delete(pool: &PgPool, id: i32) -> Result<u64, String> {
-    let result = sqlx::query("DELETE FROM tippers WHERE tipper_id = $1")
-        .bind(id)
-        .execute(pool)
-        .await;
+pub async fn delete(pool: &DbPool, id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query("DELETE FROM tippers WHERE tipper_id = $1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
     match result {
-        Ok(result) => {
-            Ok(result.rows_affected())
+        Ok(count) => {
+            Ok(count)
         },
         Err(e) => {
             error!("Error deleting tipper: {}", e);
-            Err(format!("Error deleting tipper: {}", e))
+            Err(ModelError::from_delete(e))
         },
     }
 }
 
-pub async fn get(pool: &PgPool, id: i32) -> Result<Option<crate::model::tipper::Tipper>, String> {
-    let result = sqlx::query("SELECT tipper_id, name, email FROM tippers WHERE tipper_id = $1")
-        .bind(id)
-        .fetch_optional(pool)
-        .await;
+/// Count the tips a tipper has made.
+pub async fn dependents(pool: &DbPool, id: i32) -> Result<Dependents, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM tips WHERE user_id=$1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+    });
+
+    match result {
+        Ok(tips) => Ok(Dependents { tips, ..Dependents::default() }),
+        Err(e) => {
+            error!("Error counting the tips of tipper: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Delete a tipper along with all of their tips.
+pub async fn delete_cascade(pool: &DbPool, id: i32) -> Result<u64, ModelError> {
+    let result = with_pool!(pool, |pool| async {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM tips WHERE user_id=$1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM tippers WHERE tipper_id=$1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(result.rows_affected())
+    }.await);
+
+    match result {
+        Ok(count) => Ok(count),
+        Err(e) => {
+            error!("Error deleting tipper: {}", e);
+            Err(ModelError::from_delete(e))
+        }
+    }
+}
+
+/// Archive a tipper in a season, or bring them back. Archived tippers keep their tips but no longer
+/// tip, and the other seasons are left as they were. Returns the number of seasons changed, which
+/// is zero when the tipper was already in or out.
+pub async fn set_active(pool: &DbPool, season_id: i32, id: i32, active: bool) -> Result<u64, ModelError> {
+    let sql = if active {
+        "INSERT INTO season_tippers (season_id, tipper_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+    } else {
+        "DELETE FROM season_tippers WHERE season_id=$1 AND tipper_id=$2"
+    };
+    let result = with_pool!(pool, |pool| {
+        sqlx::query(sql)
+            .bind(season_id)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    });
+    match result {
+        Ok(count) => {
+            Ok(count)
+        },
+        Err(e) => {
+            error!("Error archiving tipper: {}", e);
+            Err(ModelError::from(e))
+        },
+    }
+}
+
+fn build_tipper((tipper_id, name, email, active): (i32, String, String, bool)) -> Tipper {
+    let tipper = Tipper::new(tipper_id, name, email);
+    tipper.set_active(active);
+    tipper
+}
+
+pub async fn get(pool: &DbPool, season_id: i32, id: i32) -> Result<Option<crate::model::tipper::Tipper>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, String, String, bool)>(
+            "SELECT tipper_id, name, email, EXISTS (SELECT 1 FROM season_tippers s WHERE s.season_id = $1 AND s.tipper_id = tippers.tipper_id) \
+             FROM tippers WHERE tipper_id = $2",
+        )
+            .bind(season_id)
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    });
     match result {
         Ok(row) => {
             match row {
                 Some(row) => {
-                    let tipper_id = row.get::<i32, _>(0);
-                    let name = row.get::<String, _>(1);
-                    let email = row.get::<String, _>(2);
-                    Ok(Some(Tipper::new(tipper_id, name, email)))
+                    Ok(Some(build_tipper(row)))
                 },
                 None => {
                     Ok(None)
@@ -259,30 +448,71 @@ pub async fn get(pool: &PgPool, id: i32) -> Result<Option<crate::model::tipper::
         },
         Err(e) => {
             error!("Error getting tipper: {}", e);
-            Err(format!("Error getting tipper: {}", e))
+            Err(ModelError::from(e))
         },
     }
 }
 
-pub async fn get_all(pool: &PgPool) -> Result<Vec<crate::model::tipper::Tipper>, String> {
-    let result =
-        sqlx::query("SELECT tipper_id, name, email FROM tippers ORDER BY name")
+/// Every tipper, including those who have been archived in the season.
+pub async fn get_all(pool: &DbPool, season_id: i32) -> Result<Vec<crate::model::tipper::Tipper>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, String, String, bool)>(
+            "SELECT tipper_id, name, email, EXISTS (SELECT 1 FROM season_tippers s WHERE s.season_id = $1 AND s.tipper_id = tippers.tipper_id) \
+             FROM tippers ORDER BY name",
+        )
+            .bind(season_id)
             .fetch_all(pool)
-            .await;
+            .await
+    });
     match result {
-        Ok(rows) => {
-            let mut tippers = Vec::new();
-            for row in rows {
-                let tipper_id = row.get::<i32, _>(0);
-                let name = row.get::<String, _>(1);
-                let email = row.get::<String, _>(2);
-                tippers.push(Tipper::new(tipper_id, name, email));
-            }
-            Ok(tippers)
-        },
+        Ok(rows) => Ok(rows.into_iter().map(build_tipper).collect()),
         Err(e) => {
             error!("Error getting all tippers: {}", e);
-            Err(format!("Error getting all tippers: {}", e))
+            Err(ModelError::from(e))
+        },
+    }
+}
+
+/// The tippers taking part in the season.
+pub async fn get_active(pool: &DbPool, season_id: i32) -> Result<Vec<crate::model::tipper::Tipper>, ModelError> {
+    let result = with_pool!(pool, |pool| {
+        sqlx::query_as::<_, (i32, String, String, bool)>(
+            "SELECT t.tipper_id, t.name, t.email, TRUE FROM tippers t \
+             JOIN season_tippers s ON s.tipper_id = t.tipper_id WHERE s.season_id = $1 ORDER BY t.name",
+        )
+            .bind(season_id)
+            .fetch_all(pool)
+            .await
+    });
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(build_tipper).collect()),
+        Err(e) => {
+            error!("Error getting active tippers: {}", e);
+            Err(ModelError::from(e))
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{competition, season};
+    use crate::util::test_utils::tests::memory_pool;
+    use async_std::task::block_on;
+
+    #[test]
+    fn test_insert_in_competition() {
+        block_on(async {
+            let pool = &memory_pool().await;
+            let competition = competition::insert(pool, "Office".to_string()).await.unwrap();
+            let trevor = insert_in_competition(pool, "Trevor".to_string(), "trevor@example.com".to_string(), competition.id).await.unwrap();
+            assert!(competition::get_members(pool, competition.id).await.unwrap().contains(&trevor.id()));
+
+            // Without the competition the tipper is not kept either
+            let season_id = season::get_all(pool).await.unwrap()[0].id;
+            let before = get_all(pool, season_id).await.unwrap().len();
+            assert!(insert_in_competition(pool, "Jane".to_string(), "jane@example.com".to_string(), competition.id + 100).await.is_err());
+            assert_eq!(get_all(pool, season_id).await.unwrap().len(), before);
+        });
+    }
+}
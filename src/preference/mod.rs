@@ -44,6 +44,13 @@ pub const APP_INFO: AppInfo = AppInfo {
 
 // Preference constants
 pub const DATABASE_URL: &str = "DB_URL";
+pub const DRAW_RULE: &str = "DRAW_RULE";
+pub const TIE_BREAKER: &str = "TIE_BREAKER";
+pub const DEFAULT_TIPS: &str = "DEFAULT_TIPS";
+pub const LADDER_WIN_POINTS: &str = "LADDER_WIN_POINTS";
+pub const LADDER_DRAW_POINTS: &str = "LADDER_DRAW_POINTS";
+pub const LADDER_LOSS_POINTS: &str = "LADDER_LOSS_POINTS";
+pub const LADDER_BYE_POINTS: &str = "LADDER_BYE_POINTS";
 
 static MANAGER: LazyLock<PreferenceManager> = LazyLock::new(|| PreferenceManager {
     preferences: {
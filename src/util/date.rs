@@ -22,9 +22,35 @@
  *
  */
 
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+
 pub fn to_dmy(date: &chrono::NaiveDate) -> String {
     date.format("%d/%m/%Y").to_string()
 }
 pub fn to_ymd(date: &chrono::NaiveDate) -> String {
     date.format("%Y-%m-%d").to_string()
-}
\ No newline at end of file
+}
+
+/// The local time of day of a kickoff, as it is entered.
+pub fn to_hm(kickoff: &DateTime<Utc>) -> String {
+    kickoff.with_timezone(&Local).format("%H:%M").to_string()
+}
+
+/// A kickoff at a local time such as "19:40" on the day of a game, or None if the time is not valid.
+pub fn kickoff_at(date: NaiveDate, time: &str) -> Option<DateTime<Utc>> {
+    let time = NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()?;
+    Local.from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|kickoff| kickoff.with_timezone(&Utc))
+}
+
+/// A date as it is written in a spreadsheet, such as 2025-03-15, 15/03/2025 or 15/03/25.
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    let text = text.trim();
+    if text.contains('/') {
+        let format = if text.rsplit('/').next()?.len() == 2 { "%d/%m/%y" } else { "%d/%m/%Y" };
+        NaiveDate::parse_from_str(text, format).ok()
+    } else {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+    }
+}
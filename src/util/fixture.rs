@@ -0,0 +1,291 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::util::game_allocator::games_per_day;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+
+/// Settings used to generate a season's draw.
+#[derive(Debug, Clone)]
+pub struct FixtureOptions {
+    pub first_round_number: i32,
+    pub start: NaiveDate,
+    pub rounds: usize,
+    pub play_days: u32,
+    pub max_consecutive_home: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureGame {
+    pub home_team_id: i32,
+    pub away_team_id: i32,
+    pub game_date: NaiveDate,
+    pub kickoff: Option<DateTime<Utc>>,
+    /// Where the game is played, when the fixture says.
+    pub venue: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureRound {
+    pub number: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub games: Vec<FixtureGame>,
+    pub byes: Vec<i32>,
+}
+
+/// Home and away history for a team while the draw is being built.
+#[derive(Default)]
+struct Venues {
+    home: i32,
+    away: i32,
+    home_streak: u32,
+    away_streak: u32,
+}
+
+impl Venues {
+    fn balance(&self) -> i32 {
+        self.home - self.away
+    }
+
+    fn play_home(&mut self) {
+        self.home += 1;
+        self.home_streak += 1;
+        self.away_streak = 0;
+    }
+
+    fn play_away(&mut self) {
+        self.away += 1;
+        self.away_streak += 1;
+        self.home_streak = 0;
+    }
+}
+
+/// The games and byes for one round of a round robin.
+type Pairings = (Vec<(i32, i32)>, Vec<i32>);
+
+/// Pair the teams for one complete round robin using the circle method.
+/// With an odd number of teams one team has the bye each round.
+fn round_robin(team_ids: &[i32]) -> Vec<Pairings> {
+    let mut slots: Vec<Option<i32>> = team_ids.iter().map(|id| Some(*id)).collect();
+    if slots.len() % 2 == 1 {
+        slots.push(None);
+    }
+    let n = slots.len();
+    let mut rounds = Vec::with_capacity(n - 1);
+    for _ in 0..n - 1 {
+        let mut pairs = Vec::with_capacity(n / 2);
+        let mut byes = Vec::new();
+        for i in 0..n / 2 {
+            match (slots[i], slots[n - 1 - i]) {
+                (Some(a), Some(b)) => pairs.push((a, b)),
+                (Some(a), None) | (None, Some(a)) => byes.push(a),
+                (None, None) => {}
+            }
+        }
+        rounds.push((pairs, byes));
+        // Keep the first slot fixed and rotate the rest
+        let last = slots.pop().unwrap();
+        slots.insert(1, last);
+    }
+    rounds
+}
+
+/// Work out the cost of playing a game at `home`'s ground; lower is better.
+fn venue_cost(
+    home: i32,
+    away: i32,
+    venues: &HashMap<i32, Venues>,
+    last_home: &HashMap<(i32, i32), i32>,
+    max_consecutive: u32,
+) -> i32 {
+    let home_venues = &venues[&home];
+    let away_venues = &venues[&away];
+    let mut cost = home_venues.balance() - away_venues.balance();
+    if home_venues.home_streak >= max_consecutive {
+        cost += 1000;
+    }
+    if away_venues.away_streak >= max_consecutive {
+        cost += 1000;
+    }
+    if last_home.get(&(home.min(away), home.max(away))) == Some(&home) {
+        cost += 10;
+    }
+    cost
+}
+
+/// Generate a full season draw.
+///
+/// Every team plays every other team before any rematch, and with an odd number of teams each
+/// team has one bye per round robin. Each team's home and away games are kept
+/// within one of each other, runs of home or away games are kept within `max_consecutive_home`, and
+/// rematches are played at the other ground wherever that does not break the run limit.
+/// Teams are drawn in the order given.
+pub fn generate_season(team_ids: &[i32], options: &FixtureOptions) -> Result<Vec<FixtureRound>, String> {
+    if team_ids.len() < 2 {
+        return Err("At least two teams are needed to generate a draw".to_string());
+    }
+    if options.rounds == 0 {
+        return Err("The season must have at least one round".to_string());
+    }
+    if !(1..=7).contains(&options.play_days) {
+        return Err("Each round must be played over 1 to 7 days".to_string());
+    }
+    if options.max_consecutive_home == 0 {
+        return Err("The consecutive home game limit must be at least 1".to_string());
+    }
+
+    let cycle = round_robin(team_ids);
+    let mut venues: HashMap<i32, Venues> = team_ids.iter().map(|id| (*id, Venues::default())).collect();
+    let mut last_home: HashMap<(i32, i32), i32> = HashMap::new();
+    let mut rounds = Vec::with_capacity(options.rounds);
+
+    for index in 0..options.rounds {
+        let start_date = options.start + Duration::weeks(index as i64);
+        let end_date = start_date + Duration::days(options.play_days as i64 - 1);
+
+        let (cycle_pairs, byes) = &cycle[index % cycle.len()];
+        let mut pairs = Vec::new();
+        for &(a, b) in cycle_pairs {
+            let a_cost = venue_cost(a, b, &venues, &last_home, options.max_consecutive_home);
+            let b_cost = venue_cost(b, a, &venues, &last_home, options.max_consecutive_home);
+            let (home, away) = if b_cost < a_cost { (b, a) } else { (a, b) };
+            venues.get_mut(&home).unwrap().play_home();
+            venues.get_mut(&away).unwrap().play_away();
+            last_home.insert((home.min(away), home.max(away)), home);
+            pairs.push((home, away));
+        }
+
+        let mut games = Vec::with_capacity(pairs.len());
+        let mut pair_iter = pairs.into_iter();
+        for (day, count) in games_per_day(pair_iter.len(), options.play_days as usize).into_iter().enumerate() {
+            let game_date = start_date + Duration::days(day as i64);
+            for (home_team_id, away_team_id) in pair_iter.by_ref().take(count) {
+                games.push(FixtureGame { home_team_id, away_team_id, game_date, kickoff: None, venue: None });
+            }
+        }
+
+        rounds.push(FixtureRound {
+            number: options.first_round_number + index as i32,
+            start_date,
+            end_date,
+            games,
+            byes: byes.clone(),
+        });
+    }
+    Ok(rounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn options(rounds: usize) -> FixtureOptions {
+        FixtureOptions {
+            first_round_number: 1,
+            start: NaiveDate::from_ymd_opt(2025, 3, 13).unwrap(),
+            rounds,
+            play_days: 4,
+            max_consecutive_home: 2,
+        }
+    }
+
+    fn pair(game: &FixtureGame) -> (i32, i32) {
+        (game.home_team_id.min(game.away_team_id), game.home_team_id.max(game.away_team_id))
+    }
+
+    #[test]
+    fn test_everyone_plays_before_rematch() {
+        let teams: Vec<i32> = (1..=10).collect();
+        let season = generate_season(&teams, &options(18)).unwrap();
+        assert_eq!(season.len(), 18);
+
+        let first_half: HashSet<(i32, i32)> = season[..9].iter().flat_map(|r| r.games.iter().map(pair)).collect();
+        assert_eq!(first_half.len(), 45);
+        let second_half: HashSet<(i32, i32)> = season[9..].iter().flat_map(|r| r.games.iter().map(pair)).collect();
+        assert_eq!(second_half, first_half);
+
+        for round in &season {
+            assert_eq!(round.games.len(), 5);
+            assert!(round.byes.is_empty());
+            let mut playing: Vec<i32> = round.games.iter().flat_map(|g| [g.home_team_id, g.away_team_id]).collect();
+            playing.sort();
+            assert_eq!(playing, teams);
+        }
+    }
+
+    #[test]
+    fn test_home_and_away_balanced() {
+        let teams: Vec<i32> = (1..=10).collect();
+        let season = generate_season(&teams, &options(18)).unwrap();
+
+        for team in &teams {
+            let sequence: Vec<bool> = season.iter()
+                .flat_map(|r| r.games.iter())
+                .filter(|g| g.home_team_id == *team || g.away_team_id == *team)
+                .map(|g| g.home_team_id == *team)
+                .collect();
+            assert_eq!(sequence.len(), 18);
+            let home_games = sequence.iter().filter(|home| **home).count();
+            assert!((8..=10).contains(&home_games), "team {} has {} home games", team, home_games);
+            let longest_home = sequence.split(|home| !*home).map(|run| run.len()).max().unwrap();
+            assert!(longest_home <= 2, "team {} has {} home games in a row", team, longest_home);
+        }
+
+        // Most rematches are played at the other ground
+        let mut reversed = 0;
+        for (first, second) in season[..9].iter().zip(season[9..].iter()) {
+            for game in &first.games {
+                let rematch = second.games.iter().find(|g| pair(g) == pair(game)).unwrap();
+                if rematch.home_team_id == game.away_team_id {
+                    reversed += 1;
+                }
+            }
+        }
+        assert!(reversed >= 40, "only {} rematches were reversed", reversed);
+    }
+
+    #[test]
+    fn test_round_dates() {
+        let season = generate_season(&[1, 2, 3, 4, 5], &options(3)).unwrap();
+        assert_eq!(season[1].number, 2);
+        assert_eq!(season[1].start_date, NaiveDate::from_ymd_opt(2025, 3, 20).unwrap());
+        assert_eq!(season[1].end_date, NaiveDate::from_ymd_opt(2025, 3, 23).unwrap());
+        let mut bye_teams: Vec<i32> = season.iter().flat_map(|r| r.byes.iter().copied()).collect();
+        bye_teams.sort();
+        bye_teams.dedup();
+        assert_eq!(bye_teams.len(), 3);
+        for round in &season {
+            assert_eq!(round.games.len(), 2);
+            assert_eq!(round.byes.len(), 1);
+            assert!(round.games.iter().all(|g| g.game_date >= round.start_date && g.game_date <= round.end_date));
+        }
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(generate_season(&[1], &options(1)).is_err());
+        assert!(generate_season(&[1, 2], &options(0)).is_err());
+    }
+}
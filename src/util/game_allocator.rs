@@ -44,7 +44,7 @@ pub(crate) fn allocate_games(round_id: i32, teams: &Vec<Team>, start: NaiveDate,
     let mut shuffled_teams = teams.clone();
     shuffled_teams.shuffle(&mut rng);
 
-    // Pair teams into games
+    // Pair teams into games. With an odd number of teams the one left over has the bye.
     let mut games = Vec::new();
     for pair in shuffled_teams.chunks(2) {
         if let [a, b] = pair {
@@ -52,19 +52,7 @@ pub(crate) fn allocate_games(round_id: i32, teams: &Vec<Team>, start: NaiveDate,
         }
     }
 
-    let num_games = games.len();
-    let num_days = days.len();
-    let base_games_per_day = num_games / num_days;
-    let extra_games = num_games % num_days;
-
-    // Determine day order: prioritize middle days for extra games
-    let mut day_indices: Vec<usize> = (0..num_days).collect();
-    day_indices.sort_by_key(|&i| (i as isize - (num_days as isize / 2)).abs());
-
-    let mut games_per_day = vec![base_games_per_day; num_days];
-    for &i in day_indices.iter().take(extra_games) {
-        games_per_day[i] += 1;
-    }
+    let games_per_day = games_per_day(games.len(), days.len());
 
     // Assign games to days
     let mut schedule: BTreeMap<NaiveDate, Vec<(Team, Team)>> = BTreeMap::new();
@@ -96,6 +84,22 @@ pub(crate) fn allocate_games(round_id: i32, teams: &Vec<Team>, start: NaiveDate,
     game_objects
 }
 
+/// Spread games evenly over the days of a round, giving any extra games to the middle days.
+pub(crate) fn games_per_day(num_games: usize, num_days: usize) -> Vec<usize> {
+    let base_games_per_day = num_games / num_days;
+    let extra_games = num_games % num_days;
+
+    // Determine day order: prioritize middle days for extra games
+    let mut day_indices: Vec<usize> = (0..num_days).collect();
+    day_indices.sort_by_key(|&i| (i as isize - (num_days as isize / 2)).abs());
+
+    let mut games_per_day = vec![base_games_per_day; num_days];
+    for &i in day_indices.iter().take(extra_games) {
+        games_per_day[i] += 1;
+    }
+    games_per_day
+}
+
 pub(crate) fn add_extra_game(
     round_id: i32,
     teams: &Vec<Team>,
@@ -131,8 +135,8 @@ pub(crate) fn add_extra_game(
         }
         let mid_day = days[days.len() / 2];
 
-        // Generate a new negative game ID
-        let new_id = -1;
+        // Generate a new negative game ID, below any other unsaved game so each can be told apart
+        let new_id = existing_games.iter().map(|g| g.id()).min().unwrap_or(0).min(0) - 1;
 
         existing_games.push(Game::new(
             new_id,
@@ -0,0 +1,755 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::model::game::Game;
+use crate::model::tip::Tip;
+use crate::util::date::{kickoff_at, parse_date, to_dmy};
+use crate::util::fixture::{FixtureGame, FixtureRound};
+use std::collections::{BTreeMap, HashMap};
+
+/// Something a name in an import can be matched to: its id and the names it goes by.
+pub type Names = (i32, Vec<String>);
+
+/// Split one line of CSV into its fields, trimming each one.
+/// Fields may be quoted, with a doubled quote standing for a quote inside the field.
+pub fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Lower case letters and digits only, so "St. Kilda" matches "st kilda".
+fn normalise(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The one id in `ids`, or None if there are none or several.
+fn only(mut ids: Vec<i32>) -> Option<i32> {
+    ids.sort_unstable();
+    ids.dedup();
+    match ids.as_slice() {
+        [id] => Some(*id),
+        _ => None,
+    }
+}
+
+/// Find the candidate a name refers to, forgiving case, punctuation and small typos.
+///
+/// An exact match wins, then a name that starts or ends with the text ("Pies" for "Magpies"),
+/// then the closest name within one or two typing mistakes. If more than one candidate
+/// fits equally well there is no match.
+pub fn find_match(text: &str, candidates: &[Names]) -> Option<i32> {
+    let wanted = normalise(text);
+    if wanted.is_empty() {
+        return None;
+    }
+    let matching = |test: &dyn Fn(&str) -> bool| -> Vec<i32> {
+        candidates.iter()
+            .filter(|(_, names)| names.iter().any(|name| test(&normalise(name))))
+            .map(|(id, _)| *id)
+            .collect()
+    };
+
+    let exact = matching(&|name| name == wanted);
+    if !exact.is_empty() {
+        return only(exact);
+    }
+    if wanted.len() >= 3 {
+        let partial = matching(&|name| name.starts_with(&wanted) || name.ends_with(&wanted));
+        if !partial.is_empty() {
+            return only(partial);
+        }
+    }
+
+    let allowed = if wanted.len() <= 4 { 1 } else { 2 };
+    let mut closest = Vec::new();
+    let mut best = allowed + 1;
+    for (id, names) in candidates {
+        let Some(distance) = names.iter().map(|name| edit_distance(&wanted, &normalise(name))).min() else {
+            continue;
+        };
+        if distance > allowed {
+            continue;
+        }
+        if distance < best {
+            best = distance;
+            closest.clear();
+        }
+        if distance == best {
+            closest.push(*id);
+        }
+    }
+    only(closest)
+}
+
+/// One tip read from an import, with what it was matched to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedTip {
+    /// The line the tip was read from, counting from 1.
+    pub line: usize,
+    pub text: String,
+    pub tipper_id: Option<i32>,
+    pub game_id: Option<i32>,
+    /// The predicted home and away scores.
+    pub scores: Option<(i32, i32)>,
+    /// Why the tip cannot be imported, if it cannot.
+    pub problem: Option<String>,
+}
+
+impl ImportedTip {
+    fn new(line: usize, text: &str, tipper_id: Option<i32>) -> ImportedTip {
+        ImportedTip {
+            line,
+            text: text.to_string(),
+            tipper_id,
+            game_id: None,
+            scores: None,
+            problem: None,
+        }
+    }
+
+    fn fail(mut self, problem: String) -> ImportedTip {
+        self.problem.get_or_insert(problem);
+        self
+    }
+
+    /// The tip to save, if everything in the line was matched.
+    pub fn to_tip(&self) -> Option<Tip> {
+        match (self.tipper_id, self.game_id, self.scores, &self.problem) {
+            (Some(tipper_id), Some(game_id), Some((home, away)), None) => {
+                Some(Tip::new(-1, tipper_id, game_id, home, away, None))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Split "Cats v Swans" into the two team names.
+fn split_game(text: &str) -> Option<(&str, &str)> {
+    let lower = text.to_ascii_lowercase();
+    [" vs. ", " vs ", " v "].iter().find_map(|separator| {
+        lower.find(separator).map(|at| (text[..at].trim(), text[at + separator.len()..].trim()))
+    })
+}
+
+/// Scores written as "80-60", or the numbers from two fields.
+fn parse_scores(fields: &[String]) -> Option<Vec<i32>> {
+    let mut numbers = Vec::new();
+    for field in fields {
+        for part in field.split('-') {
+            numbers.push(part.trim().parse::<i32>().ok()?);
+        }
+    }
+    Some(numbers)
+}
+
+/// A team name with an optional margin after it, as in "Cats 12" or "Cats by 12".
+fn split_margin(pick: &str) -> (&str, Option<i32>) {
+    if let Some((name, last)) = pick.rsplit_once(char::is_whitespace)
+        && let Ok(margin) = last.parse::<i32>() {
+        let name = name.trim_end();
+        let name = name.strip_suffix(" by").unwrap_or(name);
+        return (name.trim_end(), Some(margin));
+    }
+    (pick, None)
+}
+
+/// The game a team plays in, and whether it is the home team.
+fn game_for_team(games: &[Game], team_id: i32) -> Option<(&Game, bool)> {
+    games.iter().find_map(|g| {
+        if g.home_team_id() == team_id {
+            Some((g, true))
+        } else if g.away_team_id() == team_id {
+            Some((g, false))
+        } else {
+            None
+        }
+    })
+}
+
+/// A CSV line is tipper, team or game, then the predicted scores or margin.
+fn csv_tip(line: usize, fields: &[String], tippers: &[Names], teams: &[Names], games: &[Game]) -> ImportedTip {
+    let tip = ImportedTip::new(line, &fields.join(", "), find_match(&fields[0], tippers));
+    let tip = match tip.tipper_id {
+        Some(_) => tip,
+        None => tip.fail(format!("No tipper matches \"{}\"", fields[0])),
+    };
+    let Some(numbers) = parse_scores(&fields[2..]) else {
+        return tip.fail("The scores are not numbers".to_string());
+    };
+
+    match split_game(&fields[1]) {
+        Some((first, second)) => {
+            let (Some(first_id), Some(second_id)) = (find_match(first, teams), find_match(second, teams)) else {
+                return tip.fail(format!("No game matches \"{}\"", fields[1]));
+            };
+            let Some((game, home)) = game_for_team(games, first_id)
+                .filter(|(g, _)| g.home_team_id() == second_id || g.away_team_id() == second_id) else {
+                return tip.fail(format!("{} is not a game in this round", fields[1]));
+            };
+            let [first_score, second_score] = numbers[..] else {
+                return tip.fail("Give a score for each team".to_string());
+            };
+            let scores = if home { (first_score, second_score) } else { (second_score, first_score) };
+            ImportedTip { game_id: Some(game.id()), scores: Some(scores), ..tip }
+        }
+        None => {
+            let Some(team_id) = find_match(&fields[1], teams) else {
+                return tip.fail(format!("No team matches \"{}\"", fields[1]));
+            };
+            let Some((game, home)) = game_for_team(games, team_id) else {
+                return tip.fail(format!("{} is not playing in this round", fields[1]));
+            };
+            // The tipped team's score comes first, and a single number is its winning margin
+            let (team_score, other_score) = match numbers[..] {
+                [margin] => (margin, 0),
+                [team_score, other_score] => (team_score, other_score),
+                _ => return tip.fail("Give a margin or a score for each team".to_string()),
+            };
+            let scores = if home { (team_score, other_score) } else { (other_score, team_score) };
+            ImportedTip { game_id: Some(game.id()), scores: Some(scores), ..tip }
+        }
+    }
+}
+
+/// A pick written as a team name, maybe with a margin, tipped to win.
+fn text_tip(line: usize, pick: &str, tipper: Result<i32, String>, teams: &[Names], games: &[Game]) -> ImportedTip {
+    let tip = ImportedTip::new(line, pick, tipper.as_ref().ok().copied());
+    let tip = match tipper {
+        Ok(_) => tip,
+        Err(problem) => tip.fail(problem),
+    };
+    let (name, margin) = split_margin(pick);
+    let margin = margin.unwrap_or(1).max(1);
+    let Some(team_id) = find_match(name, teams) else {
+        return tip.fail(format!("No team matches \"{}\"", name));
+    };
+    let Some((game, home)) = game_for_team(games, team_id) else {
+        return tip.fail(format!("{} is not playing in this round", name));
+    };
+    let scores = if home { (margin, 0) } else { (0, margin) };
+    ImportedTip { game_id: Some(game.id()), scores: Some(scores), ..tip }
+}
+
+/// Read tips for one round from CSV or from picks typed or pasted as text.
+///
+/// CSV lines hold the tipper, a team or game ("Cats v Swans"), then either the scores or the
+/// tipped team's margin. Any other line is a list of team names, each tipped to win by one or by
+/// the number after it. Such a list belongs to the tipper named before a colon ("Sam: Cats, Swans"),
+/// to the tipper named alone on an earlier line, or else to `default_tipper`.
+/// A game tipped twice by the same tipper keeps the first tip and flags the rest.
+pub fn parse_tips(text: &str, default_tipper: Option<i32>, tippers: &[Names], teams: &[Names],
+                  games: &[Game]) -> Vec<ImportedTip> {
+    let mut imported = Vec::new();
+    let mut tipper = default_tipper.ok_or_else(|| "No tipper was named".to_string());
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        if index == 0 && normalise(&fields[0]) == "tipper" {
+            continue;
+        }
+        if fields.len() >= 3 && parse_scores(&fields[2..]).is_some() {
+            imported.push(csv_tip(index + 1, &fields, tippers, teams, games));
+            continue;
+        }
+
+        let mut picks = line;
+        if let Some((name, rest)) = line.split_once(':') {
+            tipper = find_match(name, tippers).ok_or_else(|| format!("No tipper matches \"{}\"", name.trim()));
+            picks = rest;
+        } else if fields.len() == 1 && find_match(line, teams).is_none()
+            && let Some(tipper_id) = find_match(line, tippers) {
+            tipper = Ok(tipper_id);
+            continue;
+        }
+        for pick in picks.split([',', ';', '\t']).map(str::trim).filter(|p| !p.is_empty()) {
+            imported.push(text_tip(index + 1, pick, tipper.clone(), teams, games));
+        }
+    }
+
+    let mut first_lines: HashMap<(i32, i32), usize> = HashMap::new();
+    for tip in imported.iter_mut().filter(|t| t.problem.is_none()) {
+        if let (Some(tipper_id), Some(game_id)) = (tip.tipper_id, tip.game_id) {
+            let first = *first_lines.entry((tipper_id, game_id)).or_insert(tip.line);
+            if first != tip.line {
+                tip.problem = Some(format!("This game was already tipped on line {}", first));
+            }
+        }
+    }
+    imported
+}
+
+/// One row of a results file, with the game it was matched to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedResult {
+    /// The line the result was read from, counting from 1.
+    pub line: usize,
+    pub text: String,
+    pub game_id: Option<i32>,
+    /// The home and away scores.
+    pub scores: Option<(i32, i32)>,
+    /// The game already has this result, so there is nothing to save.
+    pub unchanged: bool,
+    /// Why the result cannot be imported, if it cannot.
+    pub problem: Option<String>,
+}
+
+impl ImportedResult {
+    fn fail(mut self, problem: String) -> ImportedResult {
+        self.problem = Some(problem);
+        self
+    }
+
+    /// The game id and scores to save, if the result was matched and changes the game.
+    pub fn to_result(&self) -> Option<(i32, i32, i32)> {
+        match (self.game_id, self.scores, &self.problem) {
+            (Some(game_id), Some((home, away)), None) if !self.unchanged => Some((game_id, home, away)),
+            _ => None,
+        }
+    }
+}
+
+/// The round number each line of a results or fixture file is for, so its games can be fetched.
+pub fn round_numbers(text: &str) -> Vec<i32> {
+    let mut numbers: Vec<i32> = text.lines()
+        .filter_map(|line| split_csv_line(line)[0].parse::<i32>().ok())
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+fn read_result(line: usize, fields: &[String], teams: &[Names], games: &HashMap<i32, Vec<Game>>,
+               replace: bool) -> ImportedResult {
+    let result = ImportedResult {
+        line,
+        text: fields.join(", "),
+        game_id: None,
+        scores: None,
+        unchanged: false,
+        problem: None,
+    };
+    let [round, date, home, away, home_score, away_score, ..] = fields else {
+        return result.fail("Expected round, date, home team, away team, home score and away score".to_string());
+    };
+    let Ok(round_number) = round.parse::<i32>() else {
+        return result.fail(format!("\"{}\" is not a round number", round));
+    };
+    let Some(round_games) = games.get(&round_number) else {
+        return result.fail(format!("There is no round {}", round_number));
+    };
+    let date = match date.as_str() {
+        "" => None,
+        text => match parse_date(text) {
+            Some(date) => Some(date),
+            None => return result.fail(format!("\"{}\" is not a date", text)),
+        },
+    };
+    let (Ok(home_score), Ok(away_score)) = (home_score.parse::<i32>(), away_score.parse::<i32>()) else {
+        return result.fail("The scores are not numbers".to_string());
+    };
+    let Some(home_id) = find_match(home, teams) else {
+        return result.fail(format!("No team matches \"{}\"", home));
+    };
+    let Some(away_id) = find_match(away, teams) else {
+        return result.fail(format!("No team matches \"{}\"", away));
+    };
+
+    // A game listed the other way around is still the same game
+    let found = round_games.iter().find_map(|g| {
+        match (g.home_team_id(), g.away_team_id()) {
+            (h, a) if (h, a) == (home_id, away_id) => Some((g, (home_score, away_score))),
+            (h, a) if (h, a) == (away_id, home_id) => Some((g, (away_score, home_score))),
+            _ => None,
+        }
+    });
+    let Some((game, scores)) = found else {
+        return result.fail(format!("{} v {} is not a game in round {}", home, away, round_number));
+    };
+    let result = ImportedResult { game_id: Some(game.id()), scores: Some(scores), ..result };
+    if let Some(date) = date && date != game.game_date() {
+        return result.fail(format!("The game is on {} in the fixture", to_dmy(&game.game_date())));
+    }
+    match (game.home_team_score(), game.away_team_score()) {
+        (Some(home), Some(away)) if (home, away) == scores => ImportedResult { unchanged: true, ..result },
+        (Some(home), Some(away)) if !replace => result.fail(format!("The game already has a result of {}-{}", home, away)),
+        _ => result,
+    }
+}
+
+/// Read a results file of round, date, home team, away team, home score and away score, matching
+/// each line to a game in `games`, which holds the games of each round by round number.
+///
+/// A line is not imported if its game cannot be found, if the date differs from the fixture, or
+/// if the game already has a different result and `replace` is not set. A game on more than one
+/// line keeps the first result and the rest are flagged. A heading line is skipped.
+pub fn parse_results(text: &str, teams: &[Names], games: &HashMap<i32, Vec<Game>>, replace: bool) -> Vec<ImportedResult> {
+    let mut imported: Vec<ImportedResult> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        if index == 0 && fields[0].parse::<i32>().is_err() {
+            continue;
+        }
+        let mut result = read_result(index + 1, &fields, teams, games, replace);
+        if result.problem.is_none()
+            && let Some(first) = imported.iter().find(|r| r.game_id.is_some() && r.game_id == result.game_id) {
+            result.problem = Some(format!("This game is also on line {}", first.line));
+        }
+        imported.push(result);
+    }
+    imported
+}
+
+/// One game read from a fixture file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedGame {
+    /// The line the game was read from, counting from 1.
+    pub line: usize,
+    pub text: String,
+    pub round_number: Option<i32>,
+    pub game: Option<FixtureGame>,
+    /// The game is already in the fixture, so there is nothing to add.
+    pub existing: bool,
+    /// Why the game cannot be imported, if it cannot.
+    pub problem: Option<String>,
+}
+
+impl ImportedGame {
+    fn fail(mut self, problem: String) -> ImportedGame {
+        self.problem = Some(problem);
+        self
+    }
+
+    fn plays(&self, team_id: i32) -> bool {
+        self.game.as_ref().is_some_and(|g| g.home_team_id == team_id || g.away_team_id == team_id)
+    }
+}
+
+fn read_fixture_game(line: usize, fields: &[String], teams: &[Names], games: &HashMap<i32, Vec<Game>>) -> ImportedGame {
+    let imported = ImportedGame {
+        line,
+        text: fields.join(", "),
+        round_number: None,
+        game: None,
+        existing: false,
+        problem: None,
+    };
+    let [round, date, home, away, rest @ ..] = fields else {
+        return imported.fail("Expected round, date, home team and away team".to_string());
+    };
+    let Ok(round_number) = round.parse::<i32>() else {
+        return imported.fail(format!("\"{}\" is not a round number", round));
+    };
+    let imported = ImportedGame { round_number: Some(round_number), ..imported };
+    let Some(game_date) = parse_date(date) else {
+        return imported.fail(format!("\"{}\" is not a date", date));
+    };
+    let Some(home_team_id) = find_match(home, teams) else {
+        return imported.fail(format!("No team matches \"{}\"", home));
+    };
+    let Some(away_team_id) = find_match(away, teams) else {
+        return imported.fail(format!("No team matches \"{}\"", away));
+    };
+    if home_team_id == away_team_id {
+        return imported.fail(format!("{} cannot play itself", home));
+    }
+
+    // The venue and time may come in either order, and either may be left out
+    let mut kickoff = None;
+    let mut venue = None;
+    for field in rest.iter().filter(|f| !f.is_empty()) {
+        match kickoff_at(game_date, field) {
+            Some(time) => kickoff = Some(time),
+            None if venue.is_none() => venue = Some(field.clone()),
+            None => {}
+        }
+    }
+    let imported = ImportedGame {
+        game: Some(FixtureGame { home_team_id, away_team_id, game_date, kickoff, venue }),
+        ..imported
+    };
+
+    let round_games = games.get(&round_number).map(|g| g.as_slice()).unwrap_or_default();
+    let playing = |team_id: i32| round_games.iter().find(|g| g.home_team_id() == team_id || g.away_team_id() == team_id);
+    match (playing(home_team_id), playing(away_team_id)) {
+        (Some(game), _) if game.away_team_id() == away_team_id || game.home_team_id() == away_team_id => {
+            ImportedGame { existing: true, ..imported }
+        }
+        (Some(_), _) => imported.fail(format!("{} already plays in round {}", home, round_number)),
+        (_, Some(_)) => imported.fail(format!("{} already plays in round {}", away, round_number)),
+        _ => imported,
+    }
+}
+
+/// Read a fixture file of round, date, home team and away team, with the venue and the local
+/// kickoff time ("19:40") after them if they are known. `games` holds the games already in each
+/// round by round number.
+///
+/// Games already in the fixture are marked as existing. A line is not imported if a team cannot
+/// be matched or would play twice in a round. A heading line is skipped.
+pub fn parse_fixture(text: &str, teams: &[Names], games: &HashMap<i32, Vec<Game>>) -> Vec<ImportedGame> {
+    let mut imported: Vec<ImportedGame> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        if index == 0 && fields[0].parse::<i32>().is_err() {
+            continue;
+        }
+        let mut game = read_fixture_game(index + 1, &fields, teams, games);
+        if game.problem.is_none() && !game.existing
+            && let Some(new_game) = &game.game {
+            let clash = imported.iter()
+                .filter(|other| other.problem.is_none() && !other.existing && other.round_number == game.round_number)
+                .find_map(|other| [(new_game.home_team_id, &fields[2]), (new_game.away_team_id, &fields[3])]
+                    .into_iter()
+                    .find(|(team_id, _)| other.plays(*team_id))
+                    .map(|(_, name)| (other.line, name.clone())));
+            if let Some((line, name)) = clash {
+                game.problem = Some(format!("{} also plays in this round on line {}", name, line));
+            }
+        }
+        imported.push(game);
+    }
+    imported
+}
+
+/// Gather the new games into rounds to be saved, each round running from its first game to its
+/// last. Teams in `team_ids` without a game in a new round have the bye; rounds in
+/// `existing_rounds` only have games added.
+pub fn build_fixture(imported: &[ImportedGame], team_ids: &[i32], existing_rounds: &[i32]) -> Vec<FixtureRound> {
+    let mut rounds: BTreeMap<i32, Vec<FixtureGame>> = BTreeMap::new();
+    for imported in imported.iter().filter(|g| g.problem.is_none() && !g.existing) {
+        if let (Some(number), Some(game)) = (imported.round_number, &imported.game) {
+            rounds.entry(number).or_default().push(game.clone());
+        }
+    }
+    rounds.into_iter()
+        .map(|(number, mut games)| {
+            games.sort_by_key(|g| (g.game_date, g.kickoff));
+            let start_date = games.iter().map(|g| g.game_date).min().expect("A round has at least one game");
+            let end_date = games.iter().map(|g| g.game_date).max().expect("A round has at least one game");
+            let byes = if existing_rounds.contains(&number) {
+                vec![]
+            } else {
+                team_ids.iter()
+                    .copied()
+                    .filter(|id| !games.iter().any(|g| g.home_team_id == *id || g.away_team_id == *id))
+                    .collect()
+            };
+            FixtureRound { number, start_date, end_date, games, byes }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn names(id: i32, names: &[&str]) -> Names {
+        (id, names.iter().map(|n| n.to_string()).collect())
+    }
+
+    fn teams() -> Vec<Names> {
+        vec![
+            names(1, &["Geelong", "Cats"]),
+            names(2, &["Sydney", "Swans"]),
+            names(3, &["Collingwood", "Magpies"]),
+            names(4, &["St Kilda", "Saints"]),
+            names(5, &["Adelaide", "Crows"]),
+        ]
+    }
+
+    fn tippers() -> Vec<Names> {
+        vec![names(10, &["Sam"]), names(11, &["Alex"])]
+    }
+
+    fn games() -> Vec<Game> {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        vec![Game::new(100, 1, 1, 2, date, None, None), Game::new(101, 1, 4, 3, date, None, None)]
+    }
+
+    #[test]
+    fn test_split_csv_line() {
+        assert_eq!(split_csv_line("Sam, Cats , 12"), vec!["Sam", "Cats", "12"]);
+        assert_eq!(split_csv_line("\"Smith, Sam\",\"The \"\"Cats\"\"\",12"), vec!["Smith, Sam", "The \"Cats\"", "12"]);
+        assert_eq!(split_csv_line("a,,b,"), vec!["a", "", "b", ""]);
+    }
+
+    #[test]
+    fn test_find_match() {
+        let teams = teams();
+        assert_eq!(find_match("cats", &teams), Some(1));
+        assert_eq!(find_match("St. Kilda", &teams), Some(4));
+        assert_eq!(find_match("Pies", &teams), Some(3));
+        assert_eq!(find_match("Colling", &teams), Some(3));
+        assert_eq!(find_match("Swnas", &teams), Some(2));
+        assert_eq!(find_match("Crowz", &teams), Some(5));
+        assert_eq!(find_match("S", &teams), None);
+        assert_eq!(find_match("Hawks", &teams), None);
+    }
+
+    #[test]
+    fn test_parse_text_tips() {
+        let text = "Sam: Cats, Pies 12\nAlex\nSwans; Saints by 3\nDogs";
+        let tips = parse_tips(text, None, &tippers(), &teams(), &games());
+        let read: Vec<_> = tips.iter().map(|t| (t.line, t.tipper_id, t.game_id, t.scores)).collect();
+        assert_eq!(read, vec![
+            (1, Some(10), Some(100), Some((1, 0))),
+            (1, Some(10), Some(101), Some((0, 12))),
+            (3, Some(11), Some(100), Some((0, 1))),
+            (3, Some(11), Some(101), Some((3, 0))),
+            (4, Some(11), None, None),
+        ]);
+        assert!(tips[..4].iter().all(|t| t.to_tip().is_some()));
+        assert_eq!(tips[4].problem.as_deref(), Some("No team matches \"Dogs\""));
+
+        // Without a named tipper the picks are kept but cannot be saved
+        let tips = parse_tips("Cats, Crows", None, &tippers(), &teams(), &games());
+        assert_eq!(tips[0].problem.as_deref(), Some("No tipper was named"));
+        assert_eq!(tips[1].problem.as_deref(), Some("No tipper was named"));
+        let tips = parse_tips("Cats", Some(11), &tippers(), &teams(), &games());
+        assert_eq!(tips[0].to_tip().map(|t| t.user_id), Some(11));
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let played = Game::new(102, 2, 5, 1, NaiveDate::from_ymd_opt(2025, 3, 22).unwrap(), Some(50), Some(40));
+        let games = HashMap::from([(1, games()), (2, vec![played])]);
+        let text = "Round,Date,Home,Away,Home Score,Away Score\n\
+            1,15/03/2025,Cats,Swans,80,60\n\
+            1,,Pies,Saints,70,75\n\
+            1,2025-03-15,Cats,Swans,81,60\n\
+            2,22/03/25,Crows,Cats,55,40\n\
+            3,29/03/25,Crows,Cats,55,40\n\
+            1,16/03/2025,Swans,Pies,1,2";
+        assert_eq!(round_numbers(text), vec![1, 2, 3]);
+
+        let results = parse_results(text, &teams(), &games, false);
+        let read: Vec<_> = results.iter().map(|r| (r.line, r.to_result())).collect();
+        assert_eq!(read, vec![(2, Some((100, 80, 60))), (3, Some((101, 75, 70))), (4, None), (5, None),
+                              (6, None), (7, None)]);
+        assert_eq!(results[2].problem.as_deref(), Some("This game is also on line 2"));
+        assert_eq!(results[3].problem.as_deref(), Some("The game already has a result of 50-40"));
+        assert_eq!(results[4].problem.as_deref(), Some("There is no round 3"));
+        assert_eq!(results[5].problem.as_deref(), Some("Swans v Pies is not a game in round 1"));
+
+        let results = parse_results(text, &teams(), &games, true);
+        assert_eq!(results[3].to_result(), Some((102, 55, 40)));
+        let same = parse_results("2,22/03/25,Crows,Cats,50,40", &teams(), &games, false);
+        assert!(same[0].unchanged && same[0].problem.is_none() && same[0].to_result().is_none());
+        let moved = parse_results("1,14/03/2025,Cats,Swans,80,60", &teams(), &games, false);
+        assert_eq!(moved[0].problem.as_deref(), Some("The game is on 15/03/2025 in the fixture"));
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let existing = HashMap::from([(1, games())]);
+        let text = "Round,Date,Home,Away,Venue,Time\n\
+            1,15/03/2025,Crows,Cats\n\
+            1,15/03/2025,Swans,Cats\n\
+            2,22/03/2025,Cats,Crows,Kardinia Park,19:40\n\
+            2,23/03/2025,Pies,Swans,13:10\n\
+            2,23/03/2025,Saints,Pies\n\
+            3,2025-03-29,Cats,Hawks";
+        let imported = parse_fixture(text, &teams(), &existing);
+        let problems: Vec<_> = imported.iter().map(|g| (g.line, g.problem.as_deref())).collect();
+        assert_eq!(problems, vec![
+            (2, Some("Cats already plays in round 1")),
+            (3, None),
+            (4, None),
+            (5, None),
+            (6, Some("Pies also plays in this round on line 5")),
+            (7, Some("No team matches \"Hawks\"")),
+        ]);
+        assert!(imported[1].existing);
+        let date = NaiveDate::from_ymd_opt(2025, 3, 22).unwrap();
+        assert_eq!(imported[2].game, Some(FixtureGame { home_team_id: 1, away_team_id: 5, game_date: date,
+            kickoff: kickoff_at(date, "19:40"), venue: Some("Kardinia Park".to_string()) }));
+        assert!(imported[3].game.as_ref().is_some_and(|g| g.kickoff.is_some() && g.venue.is_none()));
+
+        let fixture = build_fixture(&imported, &[1, 2, 3, 4, 5], &[1]);
+        assert_eq!(fixture.len(), 1);
+        assert_eq!((fixture[0].number, fixture[0].start_date, fixture[0].end_date), (2, date, date + chrono::Duration::days(1)));
+        assert_eq!(fixture[0].games.len(), 2);
+        assert_eq!(fixture[0].byes, vec![4]);
+    }
+
+    #[test]
+    fn test_parse_csv_tips() {
+        let text = "Tipper,Game,Scores\nSam,Pies v Saints,80-60\nAlex,Swans,20\nAlex,Cats,70,50\nSam,Cats,10\nBob,Cats,5";
+        let tips = parse_tips(text, None, &tippers(), &teams(), &games());
+        let read: Vec<_> = tips.iter().map(|t| (t.tipper_id, t.game_id, t.scores)).collect();
+        assert_eq!(read, vec![
+            (Some(10), Some(101), Some((60, 80))),
+            (Some(11), Some(100), Some((0, 20))),
+            (Some(11), Some(100), Some((70, 50))),
+            (Some(10), Some(100), Some((10, 0))),
+            (None, Some(100), Some((5, 0))),
+        ]);
+        assert_eq!(tips[2].problem.as_deref(), Some("This game was already tipped on line 3"));
+        assert_eq!(tips[4].problem.as_deref(), Some("No tipper matches \"Bob\""));
+        assert!(tips[4].to_tip().is_none());
+    }
+}
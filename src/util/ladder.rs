@@ -0,0 +1,248 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::model::bye::Bye;
+use crate::model::game::Game;
+use crate::preference;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// Premiership points awarded for each result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LadderPoints {
+    pub win: i32,
+    pub draw: i32,
+    pub loss: i32,
+    pub bye: i32,
+}
+
+impl Default for LadderPoints {
+    fn default() -> Self {
+        LadderPoints { win: 4, draw: 2, loss: 0, bye: 4 }
+    }
+}
+
+impl LadderPoints {
+    pub fn from_preferences() -> LadderPoints {
+        let pref = preference::manager();
+        let default = LadderPoints::default();
+        LadderPoints {
+            win: pref.get::<i32>(preference::LADDER_WIN_POINTS).unwrap_or(default.win),
+            draw: pref.get::<i32>(preference::LADDER_DRAW_POINTS).unwrap_or(default.draw),
+            loss: pref.get::<i32>(preference::LADDER_LOSS_POINTS).unwrap_or(default.loss),
+            bye: pref.get::<i32>(preference::LADDER_BYE_POINTS).unwrap_or(default.bye),
+        }
+    }
+}
+
+/// A team's line on the ladder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LadderEntry {
+    pub team_id: i32,
+    pub position: usize,
+    pub played: u32,
+    pub won: u32,
+    pub lost: u32,
+    pub drawn: u32,
+    pub byes: u32,
+    pub points_for: i32,
+    pub points_against: i32,
+    pub premiership_points: i32,
+}
+
+impl LadderEntry {
+    fn new(team_id: i32) -> LadderEntry {
+        LadderEntry {
+            team_id,
+            position: 0,
+            played: 0,
+            won: 0,
+            lost: 0,
+            drawn: 0,
+            byes: 0,
+            points_for: 0,
+            points_against: 0,
+            premiership_points: 0,
+        }
+    }
+
+    /// Points for as a percentage of points against. A team that has scored without conceding
+    /// anything is above every percentage.
+    pub fn percentage(&self) -> f64 {
+        if self.points_against == 0 {
+            if self.points_for > 0 { f64::INFINITY } else { 0.0 }
+        } else {
+            self.points_for as f64 * 100.0 / self.points_against as f64
+        }
+    }
+
+    fn add_result(&mut self, scored: i32, conceded: i32, points: &LadderPoints) {
+        self.played += 1;
+        self.points_for += scored;
+        self.points_against += conceded;
+        match scored.cmp(&conceded) {
+            Ordering::Greater => {
+                self.won += 1;
+                self.premiership_points += points.win;
+            }
+            Ordering::Less => {
+                self.lost += 1;
+                self.premiership_points += points.loss;
+            }
+            Ordering::Equal => {
+                self.drawn += 1;
+                self.premiership_points += points.draw;
+            }
+        }
+    }
+}
+
+/// Build the ladder from every game with a result, ordered by premiership points then percentage.
+/// A bye earns its points once any game in the round has a result.
+pub fn ladder(team_ids: &[i32], games: &[Game], byes: &[Bye], points: &LadderPoints) -> Vec<LadderEntry> {
+    let mut entries: HashMap<i32, LadderEntry> = team_ids
+        .iter()
+        .map(|id| (*id, LadderEntry::new(*id)))
+        .collect();
+
+    for game in games {
+        let (Some(home_score), Some(away_score)) = (game.home_team_score(), game.away_team_score()) else {
+            continue;
+        };
+        entries.entry(game.home_team_id())
+            .or_insert_with(|| LadderEntry::new(game.home_team_id()))
+            .add_result(home_score, away_score, points);
+        entries.entry(game.away_team_id())
+            .or_insert_with(|| LadderEntry::new(game.away_team_id()))
+            .add_result(away_score, home_score, points);
+    }
+
+    let played_rounds: HashSet<i32> = games.iter()
+        .filter(|g| g.has_result())
+        .map(|g| g.round_id())
+        .collect();
+    for bye in byes.iter().filter(|b| played_rounds.contains(&b.round_id)) {
+        let entry = entries.entry(bye.team_id)
+            .or_insert_with(|| LadderEntry::new(bye.team_id));
+        entry.byes += 1;
+        entry.premiership_points += points.bye;
+    }
+
+    let mut ladder: Vec<LadderEntry> = entries.into_values().collect();
+    ladder.sort_by(|a, b| {
+        b.premiership_points.cmp(&a.premiership_points)
+            .then(b.percentage().total_cmp(&a.percentage()))
+            .then(a.team_id.cmp(&b.team_id))
+    });
+    for (i, entry) in ladder.iter_mut().enumerate() {
+        entry.position = i + 1;
+    }
+    ladder
+}
+
+/// Each team's position going into a round, 1 being top, from the results of the given earlier rounds.
+pub fn positions_before(team_ids: &[i32], games: &[Game], byes: &[Bye], earlier_rounds: &HashSet<i32>,
+                        points: &LadderPoints) -> HashMap<i32, usize> {
+    let games: Vec<Game> = games.iter().filter(|g| earlier_rounds.contains(&g.round_id())).cloned().collect();
+    let byes: Vec<Bye> = byes.iter().filter(|b| earlier_rounds.contains(&b.round_id)).cloned().collect();
+    ladder(team_ids, &games, &byes, points).into_iter()
+        .map(|entry| (entry.team_id, entry.position))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn game(game_id: i32, home: i32, away: i32, home_score: Option<i32>, away_score: Option<i32>) -> Game {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        Game::new(game_id, 1, home, away, date, home_score, away_score)
+    }
+
+    #[test]
+    fn test_ladder() {
+        let games = vec![
+            game(1, 1, 2, Some(100), Some(50)),
+            game(2, 3, 4, Some(60), Some(60)),
+            game(3, 2, 3, Some(80), Some(70)),
+            game(4, 1, 4, None, None),
+        ];
+        let ladder = ladder(&[1, 2, 3, 4, 5], &games, &[], &LadderPoints::default());
+
+        let ids: Vec<i32> = ladder.iter().map(|e| e.team_id).collect();
+        assert_eq!(ids, vec![1, 2, 4, 3, 5]);
+
+        let first = &ladder[0];
+        assert_eq!((first.played, first.won, first.lost, first.drawn), (1, 1, 0, 0));
+        assert_eq!((first.points_for, first.points_against), (100, 50));
+        assert_eq!(first.premiership_points, 4);
+        assert_eq!(first.percentage(), 200.0);
+
+        let second = &ladder[1];
+        assert_eq!((second.played, second.won, second.lost), (2, 1, 1));
+        assert_eq!(second.premiership_points, 4);
+
+        let drawn = &ladder[2];
+        assert_eq!((drawn.played, drawn.drawn), (1, 1));
+        assert_eq!(drawn.premiership_points, 2);
+
+        assert_eq!(ladder[4].played, 0);
+        assert_eq!(ladder[4].position, 5);
+    }
+
+    #[test]
+    fn test_nothing_conceded() {
+        let games = vec![game(1, 1, 3, Some(200), Some(10)), game(2, 2, 4, Some(50), Some(0))];
+        let ladder = ladder(&[1, 2, 3, 4, 5], &games, &[], &LadderPoints::default());
+
+        let ids: Vec<i32> = ladder.iter().map(|e| e.team_id).collect();
+        assert_eq!(ids, vec![2, 1, 3, 4, 5]);
+        assert_eq!(ladder[0].percentage(), f64::INFINITY);
+        assert_eq!(ladder[4].percentage(), 0.0);
+    }
+
+    #[test]
+    fn test_custom_points() {
+        let games = vec![game(1, 1, 2, Some(50), Some(50))];
+        let points = LadderPoints { win: 2, draw: 1, loss: 0, bye: 2 };
+        let ladder = ladder(&[1, 2], &games, &[], &points);
+        assert!(ladder.iter().all(|e| e.premiership_points == 1));
+    }
+
+    #[test]
+    fn test_byes() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 22).unwrap();
+        let second_round = Game::new(3, 2, 1, 3, date, None, None);
+        let games = vec![game(1, 1, 2, Some(50), Some(70)), second_round];
+        let byes = vec![Bye::new(1, 3), Bye::new(2, 2)];
+        let ladder = ladder(&[1, 2, 3], &games, &byes, &LadderPoints::default());
+
+        let ids: Vec<i32> = ladder.iter().map(|e| e.team_id).collect();
+        assert_eq!(ids, vec![2, 3, 1]);
+        let bye_team = &ladder[1];
+        assert_eq!((bye_team.played, bye_team.byes, bye_team.premiership_points), (0, 1, 4));
+        // The second round has no results yet so its bye does not count
+        assert_eq!((ladder[0].byes, ladder[0].premiership_points), (0, 4));
+    }
+}
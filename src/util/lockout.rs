@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::model::game::Game;
+use crate::model::tip::Tip;
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// When tips close in a competition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lockout {
+    /// Tips can be entered at any time.
+    None,
+    /// Tips on a game close when that game kicks off.
+    Game,
+    /// Tips on every game in a round close when its first game kicks off.
+    Round,
+}
+
+impl Lockout {
+    pub const ALL: [Lockout; 3] = [Lockout::None, Lockout::Game, Lockout::Round];
+}
+
+impl fmt::Display for Lockout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Lockout::None => "none",
+            Lockout::Game => "game",
+            Lockout::Round => "round",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Lockout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Lockout::None),
+            "game" => Ok(Lockout::Game),
+            "round" => Ok(Lockout::Round),
+            _ => Err(format!("Unknown lockout: {}", s)),
+        }
+    }
+}
+
+/// What happens to a tip entered after its game has closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LateTips {
+    Reject,
+    Flag,
+}
+
+impl LateTips {
+    pub const ALL: [LateTips; 2] = [LateTips::Reject, LateTips::Flag];
+}
+
+impl fmt::Display for LateTips {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LateTips::Reject => "reject",
+            LateTips::Flag => "flag",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for LateTips {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(LateTips::Reject),
+            "flag" => Ok(LateTips::Flag),
+            _ => Err(format!("Unknown late tips rule: {}", s)),
+        }
+    }
+}
+
+/// When tips on a game close, given the games of its round. Games without a kickoff time never close.
+pub fn closes_at(lockout: Lockout, game_id: i32, games: &[Game]) -> Option<DateTime<Utc>> {
+    match lockout {
+        Lockout::None => None,
+        Lockout::Game => games.iter().find(|g| g.id() == game_id).and_then(|g| g.kickoff()),
+        Lockout::Round => games.iter().filter_map(|g| g.kickoff()).min(),
+    }
+}
+
+/// Check tips made at `now` against the lockout. Late tips are flagged when they are allowed,
+/// otherwise the ids of the games they were for are returned so they can be reported. A tip marked
+/// auto is checked like any other; default tips are worked out when scoring, not saved through here.
+pub fn check_tips(tips: Vec<Tip>, games: &[Game], lockout: Lockout, late_tips: LateTips,
+                  now: DateTime<Utc>) -> Result<Vec<Tip>, Vec<i32>> {
+    let mut checked = Vec::with_capacity(tips.len());
+    let mut refused = Vec::new();
+    for mut tip in tips {
+        let late = closes_at(lockout, tip.game_id, games).is_some_and(|closed| now >= closed);
+        match (late, late_tips) {
+            (true, LateTips::Reject) => refused.push(tip.game_id),
+            _ => {
+                tip.late = late;
+                checked.push(tip);
+            }
+        }
+    }
+    if refused.is_empty() {
+        Ok(checked)
+    } else {
+        refused.dedup();
+        Err(refused)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    fn game(game_id: i32, kickoff_hour: Option<u32>) -> Game {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        let game = Game::new(game_id, 1, 1, 2, date, None, None);
+        game.set_kickoff(kickoff_hour.map(|h| Utc.with_ymd_and_hms(2025, 3, 15, h, 0, 0).unwrap()));
+        game
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 15, hour, 0, 0).unwrap()
+    }
+
+    fn tip(game_id: i32) -> Tip {
+        Tip::new(-1, 1, game_id, 10, 0, None)
+    }
+
+    #[test]
+    fn test_closes_at() {
+        let games = vec![game(1, Some(9)), game(2, Some(7)), game(3, None)];
+        assert_eq!(closes_at(Lockout::None, 1, &games), None);
+        assert_eq!(closes_at(Lockout::Game, 1, &games), Some(at(9)));
+        assert_eq!(closes_at(Lockout::Game, 3, &games), None);
+        assert_eq!(closes_at(Lockout::Round, 1, &games), Some(at(7)));
+        assert_eq!(closes_at(Lockout::Round, 3, &games), Some(at(7)));
+    }
+
+    #[test]
+    fn test_check_tips() {
+        let games = vec![game(1, Some(9)), game(2, Some(7))];
+        let tips = || vec![tip(1), tip(2)];
+
+        // Between the two kickoffs only the early game is closed
+        assert_eq!(check_tips(tips(), &games, Lockout::Game, LateTips::Reject, at(8)).unwrap_err(), vec![2]);
+        let flagged = check_tips(tips(), &games, Lockout::Game, LateTips::Flag, at(8)).unwrap();
+        assert_eq!(flagged.iter().map(|t| t.late).collect::<Vec<_>>(), vec![false, true]);
+
+        // The whole round closes with its first game
+        assert_eq!(check_tips(tips(), &games, Lockout::Round, LateTips::Reject, at(8)).unwrap_err(), vec![1, 2]);
+        assert!(check_tips(tips(), &games, Lockout::Round, LateTips::Reject, at(6)).is_ok());
+        assert!(check_tips(tips(), &games, Lockout::None, LateTips::Reject, at(10)).is_ok());
+
+        // Marking a tip auto does not get it past the lockout
+        let mut auto = tip(1);
+        auto.auto = true;
+        assert_eq!(check_tips(vec![auto], &games, Lockout::Round, LateTips::Reject, at(10)).unwrap_err(), vec![1]);
+    }
+
+    #[test]
+    fn test_round_trip_names() {
+        for lockout in Lockout::ALL {
+            assert_eq!(lockout.to_string().parse::<Lockout>(), Ok(lockout));
+        }
+        for late_tips in LateTips::ALL {
+            assert_eq!(late_tips.to_string().parse::<LateTips>(), Ok(late_tips));
+        }
+    }
+}
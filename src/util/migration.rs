@@ -0,0 +1,222 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use chrono::NaiveDateTime;
+use crate::model::error::ModelError;
+use crate::util::db::{with_pool, Backend, DbPool};
+use log::{error, info};
+use std::collections::HashMap;
+
+/// A change to the database schema. Migrations are applied in version order and each is only
+/// ever applied once; add new changes as a new migration rather than editing an old one.
+/// Each migration is written once for each backend, and both must produce the same schema.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    postgres: &'static str,
+    sqlite: &'static str,
+}
+
+impl Migration {
+    fn sql(&self, backend: Backend) -> &'static str {
+        match backend {
+            Backend::Postgres => self.postgres,
+            Backend::Sqlite => self.sqlite,
+        }
+    }
+}
+
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create tables",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0001_create_tables.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0001_create_tables.sql"),
+    },
+    Migration {
+        version: 2,
+        description: "Create byes",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0002_create_byes.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0002_create_byes.sql"),
+    },
+    Migration {
+        version: 3,
+        description: "Archive teams and tippers",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0003_archive_teams_tippers.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0003_archive_teams_tippers.sql"),
+    },
+    Migration {
+        version: 4,
+        description: "Create seasons",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0004_create_seasons.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0004_create_seasons.sql"),
+    },
+    Migration {
+        version: 5,
+        description: "Create competitions",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0005_create_competitions.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0005_create_competitions.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "Add kickoff times and tip lockout",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0006_add_kickoff_lockout.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0006_add_kickoff_lockout.sql"),
+    },
+    Migration {
+        version: 7,
+        description: "Add automatic tips",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0007_add_auto_tips.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0007_add_auto_tips.sql"),
+    },
+    Migration {
+        version: 8,
+        description: "Make tips unique per tipper and game",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0008_unique_tips.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0008_unique_tips.sql"),
+    },
+    Migration {
+        version: 9,
+        description: "Add game venues",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0009_add_game_venue.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0009_add_game_venue.sql"),
+    },
+    Migration {
+        version: 10,
+        description: "Add season teams and tippers",
+        postgres: include_str!("../../resources/sql/migrations/postgres/0010_season_members.sql"),
+        sqlite: include_str!("../../resources/sql/migrations/sqlite/0010_season_members.sql"),
+    },
+];
+
+/// Whether a migration has been applied to the database, and when.
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: &'static str,
+    pub applied_on: Option<NaiveDateTime>,
+}
+
+async fn applied(pool: &DbPool) -> Result<HashMap<i64, NaiveDateTime>, sqlx::Error> {
+    let rows = with_pool!(pool, |pool| async {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (\
+                version BIGINT PRIMARY KEY, \
+                description VARCHAR(100) NOT NULL, \
+                applied_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP)",
+        )
+            .execute(pool)
+            .await?;
+
+        sqlx::query_as::<_, (i64, NaiveDateTime)>("SELECT version, applied_on FROM schema_migrations")
+            .fetch_all(pool)
+            .await
+    }.await)?;
+    Ok(rows.into_iter().collect())
+}
+
+/// List every known migration and whether it has been applied.
+pub async fn status(pool: &DbPool) -> Result<Vec<MigrationStatus>, ModelError> {
+    match applied(pool).await {
+        Ok(applied) => Ok(MIGRATIONS.iter()
+            .map(|m| MigrationStatus {
+                version: m.version,
+                description: m.description,
+                applied_on: applied.get(&m.version).copied(),
+            })
+            .collect()),
+        Err(e) => {
+            error!("Error reading schema migrations: {}", e);
+            Err(ModelError::from(e))
+        }
+    }
+}
+
+/// Apply any pending migrations, each in its own transaction. Returns the versions applied.
+pub async fn run(pool: &DbPool) -> Result<Vec<i64>, ModelError> {
+    let pending: Vec<&Migration> = status(pool).await?
+        .iter()
+        .filter(|s| s.applied_on.is_none())
+        .filter_map(|s| MIGRATIONS.iter().find(|m| m.version == s.version))
+        .collect();
+    if pending.is_empty() {
+        info!("Database schema is up to date");
+        return Ok(vec![]);
+    }
+    info!("{} schema migration(s) pending: {:?}", pending.len(), pending.iter().map(|m| m.version).collect::<Vec<_>>());
+
+    let backend = pool.backend();
+    let mut versions = Vec::with_capacity(pending.len());
+    for migration in pending {
+        let result = with_pool!(pool, |pool| async {
+            let mut tx = pool.begin().await?;
+            sqlx::raw_sql(migration.sql(backend))
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("INSERT INTO schema_migrations (version, description) VALUES ($1, $2)")
+                .bind(migration.version)
+                .bind(migration.description)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(())
+        }.await);
+
+        match result {
+            Ok(_) => {
+                info!("Applied schema migration {}: {}", migration.version, migration.description);
+                versions.push(migration.version);
+            }
+            Err(e) => {
+                error!("Error applying schema migration {}: {}", migration.version, e);
+                return Err(ModelError::from(e));
+            }
+        }
+    }
+    Ok(versions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task::block_on;
+    use sqlx::SqlitePool;
+
+    #[test]
+    fn test_migrations_in_order() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert!(MIGRATIONS.iter().all(|m| !m.postgres.trim().is_empty() && !m.sqlite.trim().is_empty()));
+    }
+
+    #[test]
+    fn test_migrations_run_once() {
+        block_on(async {
+            let pool = DbPool::Sqlite(SqlitePool::connect("sqlite::memory:").await.unwrap());
+            let versions: Vec<i64> = MIGRATIONS.iter().map(|m| m.version).collect();
+            assert_eq!(run(&pool).await.unwrap(), versions);
+            // Running again at the next start up applies nothing and leaves the schema as it was
+            assert!(run(&pool).await.unwrap().is_empty());
+            assert!(status(&pool).await.unwrap().iter().all(|s| s.applied_on.is_some()));
+        });
+    }
+}
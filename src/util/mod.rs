@@ -23,9 +23,15 @@
  */
 pub(crate) mod info;
 pub(crate) mod db;
+pub(crate) mod migration;
 pub(crate) mod test_utils;
 pub(crate) mod date;
 pub(crate) mod game_allocator;
+pub(crate) mod scoring;
+pub(crate) mod ladder;
+pub(crate) mod fixture;
+pub(crate) mod lockout;
+pub(crate) mod import;
 
 use std::error::Error;
 use log::LevelFilter;
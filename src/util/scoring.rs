@@ -0,0 +1,584 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+use crate::model::game::Game;
+use crate::model::tip::Tip;
+use crate::preference;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// How tips are scored when a game ends in a draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawRule {
+    AllCorrect,
+    NoneCorrect,
+    HalfPoint,
+}
+
+impl DrawRule {
+    pub const ALL: [DrawRule; 3] = [DrawRule::AllCorrect, DrawRule::NoneCorrect, DrawRule::HalfPoint];
+
+    pub fn from_preferences() -> DrawRule {
+        preference::manager()
+            .get::<DrawRule>(preference::DRAW_RULE)
+            .unwrap_or(DrawRule::AllCorrect)
+    }
+
+    /// The points each tipper gets for a drawn game they did not tip as a draw.
+    pub fn points(&self) -> f64 {
+        match self {
+            DrawRule::AllCorrect => 1.0,
+            DrawRule::NoneCorrect => 0.0,
+            DrawRule::HalfPoint => 0.5,
+        }
+    }
+}
+
+impl fmt::Display for DrawRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DrawRule::AllCorrect => "all",
+            DrawRule::NoneCorrect => "none",
+            DrawRule::HalfPoint => "half",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for DrawRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(DrawRule::AllCorrect),
+            "none" => Ok(DrawRule::NoneCorrect),
+            "half" => Ok(DrawRule::HalfPoint),
+            _ => Err(format!("Unknown draw rule: {}", s)),
+        }
+    }
+}
+
+/// Which games count towards the margin tie-breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreaker {
+    None,
+    FirstGame,
+    AllGames,
+}
+
+impl TieBreaker {
+    pub const ALL: [TieBreaker; 3] = [TieBreaker::None, TieBreaker::FirstGame, TieBreaker::AllGames];
+
+    pub fn from_preferences() -> TieBreaker {
+        preference::manager()
+            .get::<TieBreaker>(preference::TIE_BREAKER)
+            .unwrap_or(TieBreaker::None)
+    }
+}
+
+impl fmt::Display for TieBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TieBreaker::None => "none",
+            TieBreaker::FirstGame => "first",
+            TieBreaker::AllGames => "all",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for TieBreaker {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(TieBreaker::None),
+            "first" => Ok(TieBreaker::FirstGame),
+            "all" => Ok(TieBreaker::AllGames),
+            _ => Err(format!("Unknown tie-breaker: {}", s)),
+        }
+    }
+}
+
+/// What a tipper who tips nothing in a round is given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultTips {
+    /// No points for the round.
+    None,
+    HomeTeam,
+    AwayTeam,
+    /// The team higher on the ladder, or the home team when they are level.
+    LadderLeader,
+    /// The score of the lowest scoring tipper in the round.
+    LowestScore,
+}
+
+impl DefaultTips {
+    pub const ALL: [DefaultTips; 5] = [DefaultTips::None, DefaultTips::HomeTeam, DefaultTips::AwayTeam,
+        DefaultTips::LadderLeader, DefaultTips::LowestScore];
+
+    pub fn from_preferences() -> DefaultTips {
+        preference::manager()
+            .get::<DefaultTips>(preference::DEFAULT_TIPS)
+            .unwrap_or(DefaultTips::None)
+    }
+}
+
+impl fmt::Display for DefaultTips {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DefaultTips::None => "none",
+            DefaultTips::HomeTeam => "home",
+            DefaultTips::AwayTeam => "away",
+            DefaultTips::LadderLeader => "ladder",
+            DefaultTips::LowestScore => "lowest",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for DefaultTips {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(DefaultTips::None),
+            "home" => Ok(DefaultTips::HomeTeam),
+            "away" => Ok(DefaultTips::AwayTeam),
+            "ladder" => Ok(DefaultTips::LadderLeader),
+            "lowest" => Ok(DefaultTips::LowestScore),
+            _ => Err(format!("Unknown default tips: {}", s)),
+        }
+    }
+}
+
+/// The rules of the competition used to build the standings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoringRules {
+    pub draw_rule: DrawRule,
+    pub tie_breaker: TieBreaker,
+    pub default_tips: DefaultTips,
+}
+
+impl ScoringRules {
+    pub fn from_preferences() -> ScoringRules {
+        ScoringRules {
+            draw_rule: DrawRule::from_preferences(),
+            tie_breaker: TieBreaker::from_preferences(),
+            default_tips: DefaultTips::from_preferences(),
+        }
+    }
+}
+
+/// A tipper's points for each round, keyed by round id, and for the season, along with
+/// the margin tie-breakers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub tipper_id: i32,
+    pub rank: usize,
+    pub round_points: BTreeMap<i32, f64>,
+    pub total: f64,
+    pub margin_error: i32,
+    pub exact_margins: u32,
+}
+
+impl Standing {
+    fn new(tipper_id: i32) -> Standing {
+        Standing {
+            tipper_id,
+            rank: 0,
+            round_points: BTreeMap::new(),
+            total: 0.0,
+            margin_error: 0,
+            exact_margins: 0,
+        }
+    }
+
+    /// Order by points, then the lowest margin error, then the most exact margins.
+    fn compare(&self, other: &Standing, tie_breaker: TieBreaker) -> Ordering {
+        let by_points = other.total.total_cmp(&self.total);
+        if tie_breaker == TieBreaker::None {
+            by_points
+        } else {
+            by_points
+                .then(self.margin_error.cmp(&other.margin_error))
+                .then(other.exact_margins.cmp(&self.exact_margins))
+        }
+    }
+}
+
+/// Tips for the tippers who tipped none of the games in a round, made by the default tips rule
+/// and marked as automatic. `ladder_positions` gives each team's place on the ladder, 1 being top.
+/// No tips are made for the lowest score rule, which is applied to the standings instead.
+pub fn default_tips(rule: DefaultTips, tipper_ids: &[i32], round_games: &[Game], tips: &[Tip],
+                    ladder_positions: &HashMap<i32, usize>) -> Vec<Tip> {
+    if matches!(rule, DefaultTips::None | DefaultTips::LowestScore) {
+        return vec![];
+    }
+    let position = |team_id| ladder_positions.get(&team_id).copied().unwrap_or(usize::MAX);
+    let mut defaults = Vec::new();
+    for tipper_id in tipper_ids {
+        let tipped = tips.iter().any(|t| t.user_id == *tipper_id && round_games.iter().any(|g| g.id() == t.game_id));
+        if tipped {
+            continue;
+        }
+        for game in round_games {
+            let home = match rule {
+                DefaultTips::AwayTeam => false,
+                DefaultTips::LadderLeader => position(game.home_team_id()) <= position(game.away_team_id()),
+                _ => true,
+            };
+            let (predicted_home, predicted_away) = if home { (1, 0) } else { (0, 1) };
+            let mut tip = Tip::new(-1, *tipper_id, game.id(), predicted_home, predicted_away, None);
+            tip.auto = true;
+            defaults.push(tip);
+        }
+    }
+    defaults
+}
+
+/// The points for a tip, or None if the game does not have a result yet.
+pub fn score_tip(game: &Game, tip: &Tip, rule: DrawRule) -> Option<f64> {
+    let home = game.home_team_score()?;
+    let away = game.away_team_score()?;
+    let result = (home - away).signum();
+    let tipped = (tip.predicted_home_score - tip.predicted_away_score).signum();
+
+    let points = if tipped == result {
+        1.0
+    } else if result == 0 {
+        rule.points()
+    } else {
+        0.0
+    };
+    Some(points)
+}
+
+/// The completed games that count towards the margin tie-breaker.
+fn tie_break_games<'a>(games: &[&'a Game], tie_breaker: TieBreaker) -> Vec<&'a Game> {
+    match tie_breaker {
+        TieBreaker::None => vec![],
+        TieBreaker::AllGames => games.iter().copied().filter(|g| g.has_result()).collect(),
+        TieBreaker::FirstGame => {
+            let mut first_games: HashMap<i32, &Game> = HashMap::new();
+            for game in games.iter().copied() {
+                let first = first_games.entry(game.round_id()).or_insert(game);
+                if (game.game_date(), game.id()) < (first.game_date(), first.id()) {
+                    *first = game;
+                }
+            }
+            first_games.into_values().filter(|g| g.has_result()).collect()
+        }
+    }
+}
+
+/// Score every tip on a completed game and rank the tippers by their total points.
+/// When a tie-breaker is in use, tippers on the same points are separated by their total
+/// margin error on the tie-break games, then by the number of exact margins they picked.
+/// A missed tip on a tie-break game counts as tipping a draw. Tippers still level share a rank.
+/// Only the active tippers are given a default score for a round they missed.
+pub fn standings(tipper_ids: &[i32], active_ids: &[i32], games: &[Game], tips: &[Tip], rules: &ScoringRules) -> Vec<Standing> {
+    let game_list: Vec<&Game> = games.iter().collect();
+    let games: HashMap<i32, &Game> = game_list.iter().map(|g| (g.id(), *g)).collect();
+    let mut standings: HashMap<i32, Standing> = tipper_ids
+        .iter()
+        .map(|id| (*id, Standing::new(*id)))
+        .collect();
+
+    for tip in tips {
+        let Some(game) = games.get(&tip.game_id) else {
+            continue;
+        };
+        let Some(points) = score_tip(game, tip, rules.draw_rule) else {
+            continue;
+        };
+        if let Some(standing) = standings.get_mut(&tip.user_id) {
+            *standing.round_points.entry(game.round_id()).or_insert(0.0) += points;
+            standing.total += points;
+        }
+    }
+
+    // Tippers who tipped nothing in a round get the lowest score of those who did
+    if rules.default_tips == DefaultTips::LowestScore {
+        let scored_rounds: BTreeSet<i32> = game_list.iter()
+            .filter(|g| g.has_result())
+            .map(|g| g.round_id())
+            .collect();
+        for round_id in &scored_rounds {
+            let tipped = |tipper_id: i32| tips.iter()
+                .any(|t| t.user_id == tipper_id && games.get(&t.game_id).is_some_and(|g| g.round_id() == *round_id));
+            let lowest = standings.values()
+                .filter(|s| tipped(s.tipper_id))
+                .map(|s| s.round_points.get(round_id).copied().unwrap_or(0.0))
+                .min_by(|a, b| a.total_cmp(b));
+            let Some(lowest) = lowest else {
+                continue;
+            };
+            for standing in standings.values_mut().filter(|s| active_ids.contains(&s.tipper_id) && !tipped(s.tipper_id)) {
+                standing.round_points.insert(*round_id, lowest);
+                standing.total += lowest;
+            }
+        }
+    }
+
+    let tie_break_games = tie_break_games(&game_list, rules.tie_breaker);
+    let tips: HashMap<(i32, i32), &Tip> = tips.iter().map(|t| ((t.user_id, t.game_id), t)).collect();
+    for standing in standings.values_mut() {
+        for game in &tie_break_games {
+            let actual = game.home_team_score().unwrap_or(0) - game.away_team_score().unwrap_or(0);
+            let tip = tips.get(&(standing.tipper_id, game.id()));
+            let predicted = tip.map(|t| t.predicted_home_score - t.predicted_away_score).unwrap_or(0);
+            let error = (predicted - actual).abs();
+            standing.margin_error += error;
+            if tip.is_some() && error == 0 {
+                standing.exact_margins += 1;
+            }
+        }
+    }
+
+    let mut standings: Vec<Standing> = standings.into_values().collect();
+    standings.sort_by(|a, b| a.compare(b, rules.tie_breaker).then(a.tipper_id.cmp(&b.tipper_id)));
+    for i in 0..standings.len() {
+        standings[i].rank = if i > 0 && standings[i].compare(&standings[i - 1], rules.tie_breaker) == Ordering::Equal {
+            standings[i - 1].rank
+        } else {
+            i + 1
+        };
+    }
+    standings
+}
+
+/// Show whole points without a decimal place, and half points with one.
+pub fn format_points(points: f64) -> String {
+    if points.fract() == 0.0 {
+        format!("{:.0}", points)
+    } else {
+        format!("{:.1}", points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn game(game_id: i32, round_id: i32, home: Option<i32>, away: Option<i32>) -> Game {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        Game::new(game_id, round_id, 1, 2, date, home, away)
+    }
+
+    fn tip(user_id: i32, game_id: i32, home: i32, away: i32) -> Tip {
+        Tip::new(-1, user_id, game_id, home, away, None)
+    }
+
+    #[test]
+    fn test_score_tip() {
+        let won = game(1, 1, Some(80), Some(60));
+        assert_eq!(score_tip(&won, &tip(1, 1, 10, 0), DrawRule::AllCorrect), Some(1.0));
+        assert_eq!(score_tip(&won, &tip(1, 1, 0, 10), DrawRule::AllCorrect), Some(0.0));
+
+        let unplayed = game(2, 1, None, None);
+        assert_eq!(score_tip(&unplayed, &tip(1, 2, 10, 0), DrawRule::AllCorrect), None);
+    }
+
+    #[test]
+    fn test_draw_rules() {
+        let drawn = game(1, 1, Some(70), Some(70));
+        let home_tip = tip(1, 1, 10, 0);
+        assert_eq!(score_tip(&drawn, &home_tip, DrawRule::AllCorrect), Some(1.0));
+        assert_eq!(score_tip(&drawn, &home_tip, DrawRule::NoneCorrect), Some(0.0));
+        assert_eq!(score_tip(&drawn, &home_tip, DrawRule::HalfPoint), Some(0.5));
+        // Tipping the draw is always correct
+        assert_eq!(score_tip(&drawn, &tip(1, 1, 5, 5), DrawRule::NoneCorrect), Some(1.0));
+    }
+
+    #[test]
+    fn test_rule_round_trip() {
+        for rule in DrawRule::ALL {
+            assert_eq!(rule.to_string().parse::<DrawRule>(), Ok(rule));
+        }
+        for tie_breaker in TieBreaker::ALL {
+            assert_eq!(tie_breaker.to_string().parse::<TieBreaker>(), Ok(tie_breaker));
+        }
+        for default_tips in DefaultTips::ALL {
+            assert_eq!(default_tips.to_string().parse::<DefaultTips>(), Ok(default_tips));
+        }
+    }
+
+    #[test]
+    fn test_standings() {
+        let games = vec![
+            game(1, 1, Some(80), Some(60)),
+            game(2, 1, Some(50), Some(90)),
+            game(3, 2, Some(70), Some(70)),
+            game(4, 2, None, None),
+        ];
+        let tips = vec![
+            tip(1, 1, 10, 0),
+            tip(1, 2, 10, 0),
+            tip(1, 3, 10, 0),
+            tip(2, 1, 10, 0),
+            tip(2, 2, 0, 10),
+            tip(2, 4, 0, 10),
+            tip(3, 1, 0, 10),
+            tip(3, 3, 0, 10),
+        ];
+        let rules = ScoringRules { draw_rule: DrawRule::HalfPoint, tie_breaker: TieBreaker::None,
+            default_tips: DefaultTips::None };
+        let standings = standings(&[1, 2, 3, 4], &[1, 2, 3, 4], &games, &tips, &rules);
+
+        let ids: Vec<i32> = standings.iter().map(|s| s.tipper_id).collect();
+        assert_eq!(ids, vec![2, 1, 3, 4]);
+        assert_eq!(standings[0].total, 2.0);
+        assert_eq!(standings[1].total, 1.5);
+        assert_eq!(standings[1].round_points.get(&1), Some(&1.0));
+        assert_eq!(standings[1].round_points.get(&2), Some(&0.5));
+        assert_eq!(standings[2].total, 0.5);
+        assert_eq!(standings[3].total, 0.0);
+        assert!(standings[3].round_points.is_empty());
+    }
+
+    #[test]
+    fn test_tied_tippers_share_rank() {
+        let games = vec![game(1, 1, Some(80), Some(60))];
+        let tips = vec![tip(1, 1, 10, 0), tip(2, 1, 10, 0)];
+        let rules = ScoringRules { draw_rule: DrawRule::AllCorrect, tie_breaker: TieBreaker::None,
+            default_tips: DefaultTips::None };
+        let standings = standings(&[1, 2, 3], &[1, 2, 3], &games, &tips, &rules);
+
+        let ranks: Vec<usize> = standings.iter().map(|s| s.rank).collect();
+        assert_eq!(ranks, vec![1, 1, 3]);
+    }
+
+    #[test]
+    fn test_first_game_tie_breaker() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let games = vec![
+            game(2, 1, Some(80), Some(60)),
+            Game::new(1, 1, 3, 4, date, Some(90), Some(70)),
+        ];
+        let tips = vec![
+            // Tipper 1 is 5 off the margin of the first game
+            tip(1, 1, 15, 0),
+            tip(1, 2, 0, 10),
+            // Tipper 2 picked the margin exactly
+            tip(2, 1, 20, 0),
+            tip(2, 2, 0, 10),
+            // Tipper 3 missed the first game, and picked a different winner in the second
+            tip(3, 2, 10, 0),
+        ];
+        let rules = ScoringRules { draw_rule: DrawRule::AllCorrect, tie_breaker: TieBreaker::FirstGame,
+            default_tips: DefaultTips::None };
+        let standings = standings(&[1, 2, 3], &[1, 2, 3], &games, &tips, &rules);
+
+        let ids: Vec<i32> = standings.iter().map(|s| s.tipper_id).collect();
+        assert_eq!(ids, vec![2, 1, 3]);
+        let ranks: Vec<usize> = standings.iter().map(|s| s.rank).collect();
+        assert_eq!(ranks, vec![1, 2, 3]);
+        assert_eq!(standings[0].margin_error, 0);
+        assert_eq!(standings[0].exact_margins, 1);
+        assert_eq!(standings[1].margin_error, 5);
+        assert_eq!(standings[2].margin_error, 20);
+    }
+
+    #[test]
+    fn test_all_games_tie_breaker() {
+        let games = vec![
+            game(1, 1, Some(80), Some(60)),
+            game(2, 1, Some(50), Some(60)),
+            game(3, 2, None, None),
+        ];
+        let tips = vec![
+            tip(1, 1, 20, 0),
+            tip(1, 2, 0, 30),
+            tip(2, 1, 10, 0),
+            tip(2, 2, 0, 10),
+        ];
+        let rules = ScoringRules { draw_rule: DrawRule::AllCorrect, tie_breaker: TieBreaker::AllGames,
+            default_tips: DefaultTips::None };
+        let standings = standings(&[1, 2], &[1, 2], &games, &tips, &rules);
+
+        assert_eq!(standings[0].tipper_id, 2);
+        assert_eq!(standings[0].margin_error, 10);
+        assert_eq!(standings[0].exact_margins, 1);
+        assert_eq!(standings[1].tipper_id, 1);
+        assert_eq!(standings[1].margin_error, 20);
+        assert_eq!(standings[1].exact_margins, 1);
+    }
+
+    #[test]
+    fn test_default_tips() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        // Team 3 is top of the ladder and plays away to team 4
+        let games = vec![Game::new(1, 1, 1, 2, date, None, None), Game::new(2, 1, 4, 3, date, None, None)];
+        let tips = vec![tip(1, 1, 10, 0)];
+        let ladder: HashMap<i32, usize> = [(3, 1), (1, 2), (2, 3), (4, 4)].into_iter().collect();
+
+        // Tipper 1 tipped, so only tipper 2 is given tips
+        let made = default_tips(DefaultTips::AwayTeam, &[1, 2], &games, &tips, &ladder);
+        assert_eq!(made.len(), 2);
+        assert!(made.iter().all(|t| t.user_id == 2 && t.auto && t.predicted_away_score > t.predicted_home_score));
+
+        let made = default_tips(DefaultTips::LadderLeader, &[1, 2], &games, &tips, &ladder);
+        let home_tipped: Vec<bool> = made.iter().map(|t| t.predicted_home_score > t.predicted_away_score).collect();
+        assert_eq!(home_tipped, vec![true, false]);
+
+        assert!(default_tips(DefaultTips::None, &[1, 2], &games, &tips, &ladder).is_empty());
+        assert!(default_tips(DefaultTips::LowestScore, &[1, 2], &games, &tips, &ladder).is_empty());
+    }
+
+    #[test]
+    fn test_lowest_score_for_missed_round() {
+        let games = vec![game(1, 1, Some(80), Some(60)), game(2, 1, Some(50), Some(90)), game(3, 2, None, None)];
+        // Tipper 1 gets both right, tipper 2 one, and tipper 3 tips nothing
+        let tips = vec![tip(1, 1, 10, 0), tip(1, 2, 0, 10), tip(2, 1, 10, 0), tip(2, 2, 10, 0)];
+        let rules = ScoringRules { draw_rule: DrawRule::AllCorrect, tie_breaker: TieBreaker::None,
+            default_tips: DefaultTips::LowestScore };
+        let standings = standings(&[1, 2, 3], &[1, 2, 3], &games, &tips, &rules);
+        let absent = standings.iter().find(|s| s.tipper_id == 3).unwrap();
+        assert_eq!(absent.total, 1.0);
+        // The round without results is not filled in
+        assert_eq!(absent.round_points.keys().copied().collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn test_lowest_score_skips_archived_tippers() {
+        let games = vec![game(1, 1, Some(80), Some(60)), game(2, 1, Some(50), Some(90))];
+        let tips = vec![tip(1, 1, 10, 0), tip(1, 2, 0, 10), tip(2, 1, 10, 0), tip(2, 2, 10, 0)];
+        let rules = ScoringRules { draw_rule: DrawRule::AllCorrect, tie_breaker: TieBreaker::None,
+            default_tips: DefaultTips::LowestScore };
+        // Tipper 3 has been archived, so still shows but is not given the lowest score
+        let standings = standings(&[1, 2, 3], &[1, 2], &games, &tips, &rules);
+        let archived = standings.iter().find(|s| s.tipper_id == 3).unwrap();
+        assert_eq!(archived.total, 0.0);
+        assert!(archived.round_points.is_empty());
+    }
+
+    #[test]
+    fn test_format_points() {
+        assert_eq!(format_points(3.0), "3");
+        assert_eq!(format_points(2.5), "2.5");
+    }
+}
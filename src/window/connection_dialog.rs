@@ -0,0 +1,281 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use adw::subclass::prelude::ObjectSubclassIsExt;
+use gtk::{gio, glib};
+
+mod imp {
+    use crate::preference;
+    use crate::util::db;
+    use crate::util::db::{Backend, ConnectionSettings};
+    use crate::window::util::connect_escape;
+    use adw::prelude::{ButtonExt, EditableExt, FileExt, GtkWindowExt, WidgetExt};
+    use adw::subclass::prelude::{CompositeTemplate, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt, WidgetClassExt, WindowImpl};
+    use gtk::glib::clone;
+    use gtk::glib::subclass::InitializingObject;
+    use gtk::subclass::widget::{CompositeTemplateInitializingExt, WidgetImpl};
+    use gtk::{gio, glib, Button, CompositeTemplate, DropDown, Entry, Label, PasswordEntry, SpinButton, Stack, TemplateChild};
+    use std::cell::RefCell;
+    use std::ops::Deref;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/connection_dialog.ui")]
+    pub struct ConnectionDialog {
+        #[template_child]
+        pub connection_view: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub backend: TemplateChild<DropDown>,
+        #[template_child]
+        pub settings_stack: TemplateChild<Stack>,
+        #[template_child]
+        pub host: TemplateChild<Entry>,
+        #[template_child]
+        pub port: TemplateChild<SpinButton>,
+        #[template_child]
+        pub database: TemplateChild<Entry>,
+        #[template_child]
+        pub user: TemplateChild<Entry>,
+        #[template_child]
+        pub password: TemplateChild<PasswordEntry>,
+        #[template_child]
+        pub file: TemplateChild<Entry>,
+        #[template_child]
+        pub btn_browse: TemplateChild<Button>,
+        #[template_child]
+        pub status: TemplateChild<Label>,
+        #[template_child]
+        pub btn_test: TemplateChild<Button>,
+        #[template_child]
+        pub btn_ok: TemplateChild<Button>,
+        #[template_child]
+        pub btn_cancel: TemplateChild<Button>,
+
+        pub on_connected: RefCell<Option<Box<dyn Fn()>>>,
+    }
+
+    impl ConnectionDialog {
+        pub fn set_settings(&self, settings: &ConnectionSettings) {
+            self.backend.set_selected(match settings.backend {
+                Backend::Postgres => 0,
+                Backend::Sqlite => 1,
+            });
+            self.show_backend();
+            self.host.set_text(&settings.host);
+            self.port.set_value(settings.port as f64);
+            self.database.set_text(&settings.database);
+            self.user.set_text(&settings.user);
+            self.password.set_text(&settings.password);
+            self.file.set_text(&settings.file);
+        }
+
+        fn selected_backend(&self) -> Backend {
+            match self.backend.selected() {
+                1 => Backend::Sqlite,
+                _ => Backend::Postgres,
+            }
+        }
+
+        fn show_backend(&self) {
+            self.settings_stack.set_visible_child_name(match self.selected_backend() {
+                Backend::Postgres => "server",
+                Backend::Sqlite => "file",
+            });
+        }
+
+        fn settings(&self) -> ConnectionSettings {
+            ConnectionSettings {
+                backend: self.selected_backend(),
+                host: self.host.text().trim().to_string(),
+                port: self.port.value_as_int() as u16,
+                database: self.database.text().trim().to_string(),
+                user: self.user.text().trim().to_string(),
+                password: self.password.text().to_string(),
+                file: self.file.text().trim().to_string(),
+            }
+        }
+
+        pub fn set_status(&self, message: &str) {
+            self.status.set_label(message);
+        }
+
+        fn set_busy(&self, busy: bool) {
+            self.btn_test.set_sensitive(!busy);
+            self.btn_ok.set_sensitive(!busy);
+        }
+
+        fn validate(&self) -> bool {
+            let settings = self.settings();
+            match settings.backend {
+                Backend::Postgres if settings.host.is_empty() || settings.database.is_empty() => {
+                    self.set_status("Please enter a host and database");
+                    false
+                }
+                Backend::Sqlite if settings.file.is_empty() => {
+                    self.set_status("Please enter a database file");
+                    false
+                }
+                _ => true,
+            }
+        }
+
+        /// Pick an existing database file. A new file can be typed in and is created on connecting.
+        fn browse(&self) {
+            let dialog = gtk::FileDialog::builder()
+                .title("Database File")
+                .modal(true)
+                .build();
+            dialog.open(Some(&*self.obj()), gio::Cancellable::NONE, clone!(#[weak(rename_to = window)] self, move |result| {
+                if let Some(path) = result.ok().and_then(|file| file.path()) {
+                    window.file.set_text(&path.to_string_lossy());
+                }
+            }));
+        }
+
+        fn test_connection(&self) {
+            let url = self.settings().url();
+            self.set_busy(true);
+            self.set_status("Connecting…");
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                match db::test_connection(&url).await {
+                    Ok(_) => window.set_status("Connection succeeded"),
+                    Err(message) => window.set_status(&message),
+                }
+                window.set_busy(false);
+            }));
+        }
+
+        fn connect(&self) {
+            let url = self.settings().url();
+            self.set_busy(true);
+            self.set_status("Connecting…");
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                // Only switch the application over once we know the new database can be reached
+                let result = match db::test_connection(&url).await {
+                    Ok(_) => db::initialize_manager(url.clone()).await,
+                    Err(message) => Err(message),
+                };
+                match result {
+                    Ok(_) => {
+                        preference::manager().put(preference::DATABASE_URL, url);
+                        if let Some(on_connected) = window.on_connected.borrow().as_ref() {
+                            on_connected();
+                        }
+                        window.obj().close();
+                    }
+                    Err(message) => {
+                        window.set_status(&message);
+                        window.set_busy(false);
+                    }
+                }
+            }));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ConnectionDialog {
+        const NAME: &'static str = "ConnectionDialog";
+        type Type = super::ConnectionDialog;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ConnectionDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let settings = db::configured_url(None)
+                .and_then(|url| ConnectionSettings::from_url(&url))
+                .unwrap_or_default();
+            self.set_settings(&settings);
+
+            self.backend.connect_selected_notify(clone!(#[weak(rename_to = window)] self, move |_| {
+                window.show_backend();
+            }));
+
+            self.btn_browse.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.browse();
+            }));
+
+            self.btn_test.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                if window.validate() {
+                    window.test_connection();
+                }
+            }));
+
+            self.btn_cancel.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.obj().close();
+            }));
+
+            self.btn_ok.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                if window.validate() {
+                    window.connect();
+                }
+            }));
+
+            connect_escape(self.connection_view.deref(), self.btn_cancel.deref());
+        }
+    }
+
+    impl WidgetImpl for ConnectionDialog {}
+
+    impl WindowImpl for ConnectionDialog {}
+}
+
+glib::wrapper! {
+    pub struct ConnectionDialog(ObjectSubclass<imp::ConnectionDialog>)
+        @extends gtk::Window, gtk::Widget,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl ConnectionDialog {
+    /// Create the dialog, showing why a connection is needed if there is a message.
+    pub fn new(message: Option<&str>) -> Self {
+        let obj = glib::Object::new::<ConnectionDialog>();
+        if let Some(message) = message {
+            obj.imp().set_status(message);
+        }
+        obj
+    }
+
+    /// Call `f` once the database is connected and the settings are saved.
+    pub fn connect_connected<F: Fn() + 'static>(&self, f: F) {
+        self.imp().on_connected.replace(Some(Box::new(f)));
+    }
+}
+
+impl Default for ConnectionDialog {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
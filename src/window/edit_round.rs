@@ -1,45 +1,59 @@
 use crate::model::round::{Playday, Playdays, Round};
 use crate::model::team::Team;
-use crate::window::util::build_column_factory;
+use crate::window::util::{build_column_factory, confirm_delete, show_error_dialog};
 use adw::gio::ListModel;
 use adw::glib::{clone, Object};
-use adw::prelude::{Cast, CastNone, IsA};
+use adw::prelude::{Cast, CastNone, IsA, StaticType};
 use adw::subclass::prelude::ObjectSubclassIsExt;
-use gtk::prelude::{ActionableExt, ListItemExt, WidgetExt};
-use gtk::{gio, glib, Button, DropDown, Expression, Label, ListItem, SignalListItemFactory};
+use gtk::prelude::{ActionableExt, EditableExt, EntryExt, ListItemExt, WidgetExt};
+use gtk::{gio, glib, Button, ColumnView, DropDown, Entry, Expression, InputPurpose, Label, ListItem, NoSelection, SignalListItemFactory};
 use std::cell::RefCell;
+use std::rc::Rc;
 use crate::event;
 use crate::event::Event;
-use crate::model::game;
-use crate::util::db;
+use crate::model::game::{Game, Games};
+use crate::model::repository::repository;
+use crate::util::date::{kickoff_at, to_hm};
 
 mod imp {
     use crate::event;
-    use crate::event::Event;
+    use crate::event::{while_loading, Event};
     use crate::model::game::{Game, Games};
     use crate::model::round::{Playday, Playdays, Round};
     use crate::model::team::{Team, Teams};
-    use crate::model::{game, round, team};
-    use crate::util::date::to_ymd;
-    use crate::util::{db, game_allocator};
-    use crate::window::edit_round::{build_column_factory_playday, build_column_factory_team, delete_game};
-    use crate::window::util::{build_del_column_factory, connect_escape, show_error_dialog};
-    use adw::glib::{closure_local, GString, TimeZone};
+    use crate::model::season;
+    use crate::model::repository::{repository, GameRecord};
+    use crate::util::date::{kickoff_at, to_hm, to_ymd};
+    use crate::util::game_allocator;
+    use crate::window::edit_round::{build_column_factory_kickoff, build_column_factory_playday, build_column_factory_score, build_column_factory_team, delete_game};
+    use crate::window::util::{build_del_column_factory, connect_escape, setup_date_selector, show_error_dialog};
+    use adw::glib::{closure_local, GString};
     use adw::prelude::{ButtonExt, Cast, CastNone, EditableExt, GtkWindowExt, ListModelExt, ObjectExt, WidgetExt};
     use adw::subclass::prelude::{CompositeTemplate, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt, WidgetClassExt, WindowImpl};
-    use chrono::{Datelike, Local, NaiveDate};
+    use chrono::{Local, NaiveDate};
     use gtk::glib::clone;
     use gtk::glib::subclass::InitializingObject;
-    use gtk::prelude::{ActionableExtManual, EntryExt, PopoverExt, SelectionModelExt};
+    use gtk::prelude::{ActionableExtManual, CheckButtonExt, EntryExt, PopoverExt, SelectionModelExt};
     use gtk::subclass::prelude::BoxImpl;
     use gtk::subclass::widget::{CompositeTemplateInitializingExt, WidgetImpl};
-    use gtk::{glib, AlertDialog, Button, Calendar, ColumnView, ColumnViewColumn, CompositeTemplate, DropDown, Entry, NoSelection, Popover, SignalListItemFactory, SpinButton, TemplateChild};
+    use gtk::{glib, AlertDialog, Button, Calendar, CheckButton, ColumnView, ColumnViewColumn, CompositeTemplate, DropDown, Entry, FlowBox, NoSelection, Popover, SignalListItemFactory, SpinButton, TemplateChild};
     use log::{info, warn};
     use std::cell::RefCell;
+    use std::collections::HashSet;
     use std::ops::{Add, Deref, Sub};
     use std::str::FromStr;
     use std::sync::Arc;
 
+    /// The round as it was last loaded or saved, to tell whether it has been edited since.
+    #[derive(PartialEq)]
+    struct Snapshot {
+        number: String,
+        start_date: String,
+        end_date: String,
+        games: Vec<(i32, GameRecord)>,
+        byes: Vec<i32>,
+    }
+
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/com/shartrec/kelpie_tipping/round_panel.ui")]
     pub struct RoundDialog {
@@ -65,6 +79,8 @@ mod imp {
         #[template_child]
         pub col_date: TemplateChild<ColumnViewColumn>,
         #[template_child]
+        pub col_kickoff: TemplateChild<ColumnViewColumn>,
+        #[template_child]
         pub col_home: TemplateChild<ColumnViewColumn>,
         #[template_child]
         pub col_home_score: TemplateChild<ColumnViewColumn>,
@@ -75,6 +91,9 @@ mod imp {
         #[template_child]
         pub col_delete: TemplateChild<ColumnViewColumn>,
 
+        #[template_child]
+        pub bye_box: TemplateChild<FlowBox>,
+
         #[template_child]
         pub btn_add_game: TemplateChild<Button>,
         #[template_child]
@@ -85,22 +104,18 @@ mod imp {
         round_id: RefCell<Option<i32>>,
         team_model: RefCell<Option<Teams>>,
         playday_model: RefCell<Option<Playdays>>,
+        bye_buttons: RefCell<Vec<(i32, CheckButton)>>,
+        /// None for a new round that has never been saved.
+        saved: RefCell<Option<Snapshot>>,
     }
 
     impl RoundDialog {
         pub fn initialise(&self) {
-            // Set the default values for the entries
-            let teams = Teams::new();
-            self.team_model.replace(Some(teams));
-
             if let Some(rx) = event::manager().register_listener() {
                 glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
                     while let Ok(ev) = rx.recv().await {
                         if let Event::GamesChanged = ev {
-                            let games = Games::for_round(view.round_id.borrow().unwrap_or(-1));
-                            let selection_model = NoSelection::new(Some(games));
-                            view.game_list.set_model(Some(&selection_model));
-                            view.game_list.queue_draw();
+                            view.games_changed().await;
                         }
                     }
                 }));
@@ -108,9 +123,83 @@ mod imp {
 
         }
 
+        /// Reload the games after they were changed elsewhere. Edits that have not been saved are
+        /// kept, and only the games that were deleted are taken out.
+        async fn games_changed(&self) {
+            let round_id = *self.round_id.borrow();
+            let Some(round_id) = round_id else {
+                return;
+            };
+            if !self.has_unsaved_changes() {
+                let games = Games::for_round(round_id).await;
+                let selection_model = NoSelection::new(Some(games));
+                self.game_list.set_model(Some(&selection_model));
+                self.game_list.queue_draw();
+                self.saved.replace(Some(self.snapshot()));
+                return;
+            }
+            let Ok(saved_games) = while_loading(repository().get_games_for_round(round_id)).await else {
+                return;
+            };
+            let saved_ids: HashSet<i32> = saved_games.iter().map(|g| g.id()).collect();
+            if let Some(model) = self.game_list.model().and_downcast_ref::<NoSelection>()
+                && let Some(games) = model.model().and_downcast_ref::<Games>() {
+                let mut game_list = games.imp().games.write().unwrap();
+                let size = game_list.len() as u32;
+                // Games not saved yet have negative ids of their own
+                game_list.retain(|g| g.id() < 0 || saved_ids.contains(&g.id()));
+                let remaining = game_list.len() as u32;
+                drop(game_list);
+                if remaining != size {
+                    model.items_changed(0, size, remaining);
+                }
+            }
+        }
+
+        fn snapshot(&self) -> Snapshot {
+            let mut games = vec![];
+            if let Some(model) = self.game_list.model().and_downcast_ref::<NoSelection>()
+                && let Some(games_model) = model.model().and_downcast_ref::<Games>() {
+                games = games_model.imp().games.read().unwrap().iter()
+                    .map(|game| (game.id(), GameRecord::of(game)))
+                    .collect();
+            }
+            Snapshot {
+                number: self.round_number.text().to_string(),
+                start_date: self.start_date.text().to_string(),
+                end_date: self.end_date.text().to_string(),
+                games,
+                byes: self.bye_team_ids(),
+            }
+        }
+
+        fn has_unsaved_changes(&self) -> bool {
+            self.saved.borrow().as_ref() != Some(&self.snapshot())
+        }
+
+        /// Show a round, or a new round following on from the last one. The teams, games and byes
+        /// are fetched without blocking the window.
         pub fn set_round(&self, round: Option<Round>) {
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                window.show_round(round).await;
+            }));
+        }
+
+        async fn show_round(&self, round: Option<Round>) {
             match round {
                 Some(round) => {
+                    let games = Games::for_round(round.id()).await;
+                    let byes = while_loading(repository().get_byes_for_round(round.id())).await;
+                    let bye_team_ids: Vec<i32> = byes.unwrap_or_default().iter().map(|b| b.team_id).collect();
+
+                    // The team drop downs need every team before any game is shown, including
+                    // archived teams that played in this round
+                    let mut team_ids: Vec<i32> = games.imp().games.read().expect("Unable to get a lock on games").iter()
+                        .flat_map(|g| [g.home_team_id(), g.away_team_id()])
+                        .collect();
+                    team_ids.extend(&bye_team_ids);
+                    self.team_model.replace(Some(Teams::loaded_including(&team_ids).await));
+
                     self.round_id.replace(Some(round.id()));
                     self.round_number.set_text(&round.number().to_string());
                     self.start_date.set_text(&to_ymd(&round.start_date()));
@@ -118,17 +207,22 @@ mod imp {
 
                     self.playday_model.replace(Some(Playdays::new(round.start_date(), round.end_date())));
 
-                    let games = Games::for_round(self.round_id.borrow().unwrap_or(-1));
                     let selection_model = NoSelection::new(Some(games));
                     self.game_list.set_model(Some(&selection_model));
                     self.game_list.queue_draw();
+
+                    self.set_byes(&bye_team_ids);
+                    self.saved.replace(Some(self.snapshot()));
                 }
                 None => {
-                    // Get the last defined round and set it as the current round to one week later
-                    let lr = async_std::task::block_on( async move {
-                        let pool = db::manager().pool();
-                        round::get_last_round(pool).await
-                    });
+                    let teams = Teams::loaded().await;
+                    self.team_model.replace(Some(teams.clone()));
+
+                    // Get the last round of the season and set it as the current round to one week later
+                    let lr = match season::current() {
+                        Some(season_id) => while_loading(repository().get_last_round(season_id)).await,
+                        None => Ok(None),
+                    };
                     let (start, end) =
                         if let Ok(Some(last_round)) = lr {
                             self.round_number.set_text((&last_round.number() + 1).to_string().as_str());
@@ -143,18 +237,25 @@ mod imp {
                             (start, end)
                         };
                     self.round_id.replace(None);
+                    self.saved.replace(None);
                     self.start_date.set_text(&to_ymd(&start));
                     self.end_date.set_text(&to_ymd(&end));
                     self.playday_model.replace(Some(Playdays::new(start, end)));
 
-                    let binding = Teams::new();
-                    let teams  = binding.imp().teams.read().unwrap();
+                    let teams = teams.imp().teams.read().unwrap().clone();
                     let mut game_list = game_allocator::allocate_games(
                         -1, // No round ID yet
                         &teams,
                         start,
                         end,
                     );
+                    // Any team left over when the teams were paired has the bye
+                    let bye_team_ids: Vec<i32> = teams.iter()
+                        .map(|t| t.id())
+                        .filter(|id| !game_list.iter().any(|g| g.home_team_id() == *id || g.away_team_id() == *id))
+                        .collect();
+                    self.set_byes(&bye_team_ids);
+
                     let games = Games::new(start, end, &mut game_list);
                     let selection_model = NoSelection::new(Some(games));
                     self.game_list.set_model(Some(&selection_model));
@@ -179,11 +280,17 @@ mod imp {
                         if let Some(day) = selected.downcast_ref::<Playday>() {
                             game.imp().set_game_date(day.imp().date());
                             println!("Setting game date to: {:?}", day.imp().date());
+                            // The kickoff moves to the new day at the same time
+                            if let Some(kickoff) = game.kickoff() {
+                                game.set_kickoff(kickoff_at(day.imp().date(), &to_hm(&kickoff)));
+                            }
                         }
                     }
                 }));
             }))));
 
+            self.col_kickoff.set_factory(Some(&build_column_factory_kickoff()));
+
             self.col_home.set_factory(Some(&self.get_team_factory(
                 self.team_model.clone(),
                 |game| game.home_team_id(),
@@ -196,6 +303,16 @@ mod imp {
                 |game, id| game.imp().set_away_team_id(id),
             )));
 
+            self.col_home_score.set_factory(Some(&build_column_factory_score(
+                |game| game.home_team_score(),
+                |game, score| game.imp().set_home_team_score(score),
+            )));
+
+            self.col_away_score.set_factory(Some(&build_column_factory_score(
+                |game| game.away_team_score(),
+                |game, score| game.imp().set_away_team_score(score),
+            )));
+
             // Create a factory for the delete button column
             let f = build_del_column_factory(
                 |button: &Button, game: &Game| button.set_action_target(Some(game.id())),
@@ -204,6 +321,28 @@ mod imp {
 
         }
 
+        /// Show a check button for each team, ticking the teams that have the bye.
+        fn set_byes(&self, bye_team_ids: &[i32]) {
+            self.bye_box.remove_all();
+            let mut buttons = Vec::new();
+            if let Some(teams) = self.team_model.borrow().as_ref() {
+                for team in teams.imp().teams.read().unwrap().iter() {
+                    let button = CheckButton::with_label(&team.name());
+                    button.set_active(bye_team_ids.contains(&team.id()));
+                    self.bye_box.append(&button);
+                    buttons.push((team.id(), button));
+                }
+            }
+            self.bye_buttons.replace(buttons);
+        }
+
+        fn bye_team_ids(&self) -> Vec<i32> {
+            self.bye_buttons.borrow().iter()
+                .filter(|(_, button)| button.is_active())
+                .map(|(id, _)| *id)
+                .collect()
+        }
+
         fn get_team_factory<FGet: Fn(&Game) -> i32 + 'static, FSet: Fn(&Game, i32) + 'static>(
             &self,
             team_model: RefCell<Option<Teams>>,
@@ -240,66 +379,52 @@ mod imp {
             factory
         }
 
-        fn save_round(&self) -> bool {
-
-            let mut new_round_id = -1;
-            let pool = db::manager().pool();
-            if self.round_id.borrow().is_some() {
-                // Update the round
-                let id = self.round_id.borrow().unwrap();
-                let number: i32 = i32::from_str(self.round_number.text().to_string().as_str()).unwrap();
-                let start_date = self.start_date.text().to_string().clone();
-                let end_date = self.end_date.text().to_string().clone();
-                async_std::task::block_on(async move {
-                    let _ = round::update(pool, id, number, start_date.parse().unwrap(), end_date.parse().unwrap()).await;
-                });
-                new_round_id = id;
-            } else {
-                // Create a new round
-                let number = i32::from_str(self.round_number.text().to_string().as_str()).unwrap();
-                let start_date = self.start_date.text().to_string().clone();
-                let end_date = self.end_date.text().to_string().clone();
-                new_round_id = async_std::task::block_on(async move {
-                    if let Ok(round_id) = round::insert(pool, number, start_date.parse().unwrap(), end_date.parse().unwrap()).await {
-                        round_id
-                    } else {
-                        -1
-                    }
-                });
-            }
-
+        fn save_round(&self) {
+            let id = *self.round_id.borrow();
+            let Some(season_id) = season::current() else {
+                show_error_dialog(&self.game_list.root(), "Choose a season for the round first");
+                return;
+            };
+            let (Ok(number), Ok(start_date), Ok(end_date)) = (
+                i32::from_str(self.round_number.text().as_str()),
+                NaiveDate::from_str(self.start_date.text().as_str()),
+                NaiveDate::from_str(self.end_date.text().as_str()),
+            ) else {
+                show_error_dialog(&self.game_list.root(), "Round Number, Start Date and End Date must all be filled in");
+                return;
+            };
+
+            let mut saved_games = vec![];
+            let mut records = vec![];
             if let Some(m) = self.game_list.model().and_downcast_ref::<NoSelection>() {
                 if let Some(games) = m.model().and_downcast_ref::<Games>() {
-                    let gg = games.imp();
-                    for mut game in gg.games.write().unwrap().iter() {
-                        let round = game.round_id();
-                        let home_team = game.home_team_id();
-                        let away_team = game.away_team_id();
-                        let date = game.game_date();
-
-                        let id = game.id();
-                        if id > 0 {
-                            info!("Updating game {}", id);
-                            async_std::task::block_on(async move {
-                                let _rows = game::update(pool, id, round, home_team, away_team, date, None, None).await;
-                            });
-                        } else {
-                            let id = async_std::task::block_on(async move {
-                                if let Ok(id) = game::insert(pool, new_round_id, home_team, away_team, date, None, None).await {
-                                    id
-                                } else {
-                                    warn!("Failed to insert new game for round {}", new_round_id);
-                                    -1
-                                }
-                            });
-                            game.imp().set_id(id);
-                        }
+                    for game in games.imp().games.read().unwrap().iter() {
+                        records.push((game.id(), GameRecord::of(game)));
+                        saved_games.push(game.clone());
                     }
                 }
             }
-
-            event::manager().notify_listeners(Event::RoundsChanged{round_id: new_round_id});
-            true
+            let byes = self.bye_team_ids();
+
+            // The round, its games and its byes are saved together, so a failure leaves the round as it was
+            let repository = repository();
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                match repository.save_round(season_id, id, number, start_date, end_date, &records, &byes).await {
+                    Ok((round_id, game_ids)) => {
+                        info!("Saved round {} with {} games", round_id, game_ids.len());
+                        window.round_id.replace(Some(round_id));
+                        for (game, game_id) in saved_games.iter().zip(game_ids) {
+                            game.imp().set_id(game_id);
+                        }
+                        window.saved.replace(Some(window.snapshot()));
+                        event::manager().notify_listeners(Event::RoundsChanged{round_id});
+                        event::manager().notify_listeners(Event::GamesChanged);
+                    }
+                    Err(e) => {
+                        show_error_dialog(&window.game_list.root(), format!("The round was not saved. {}", e).as_str());
+                    }
+                }
+            }));
         }
 
         fn validate(&self) -> bool {
@@ -334,13 +459,7 @@ mod imp {
                     for game in game_list.iter() {
                         for id in [game.home_team_id(), game.away_team_id()] {
                             if !game_teams.insert(id) {
-                                let pool = db::manager().pool();
-                                let t = async_std::task::block_on(async { team::get(pool, id).await }).ok().flatten();
-                                let team_name = if let Some(t) = t {
-                                    t.name()
-                                } else {
-                                    game.home_team_id().to_string()
-                                };
+                                let team_name = self.team_name(id);
                                 show_error_dialog(&self.game_list.root(), format!("Team {} is used more than once in this round", team_name).as_str());
                                 return false;
                             }
@@ -352,6 +471,24 @@ mod imp {
                             show_error_dialog(&self.game_list.root(), message.as_str());
                             return false;
                         }
+                        if game.kickoff_error() {
+                            let message = format!("Enter the kickoff for {} as hh:mm, or leave it empty", self.game_name(game));
+                            show_error_dialog(&self.game_list.root(), message.as_str());
+                            return false;
+                        }
+                        // a result needs both scores; the score entries only accept digits
+                        if game.home_team_score().is_some() != game.away_team_score().is_some() {
+                            let message = format!("Enter both scores for {} or neither", self.game_name(game));
+                            show_error_dialog(&self.game_list.root(), message.as_str());
+                            return false;
+                        }
+                    }
+
+                    // A team with the bye cannot also be playing
+                    if let Some(id) = self.bye_team_ids().into_iter().find(|id| game_teams.contains(id)) {
+                        let message = format!("Team {} has the bye but is also playing in this round", self.team_name(id));
+                        show_error_dialog(&self.game_list.root(), message.as_str());
+                        return false;
                     }
                 }
             }
@@ -359,6 +496,20 @@ mod imp {
             true
         }
 
+        fn team_name(&self, id: i32) -> String {
+            self.team_model.borrow().as_ref()
+                .and_then(|teams| {
+                    teams.imp().teams.read().unwrap().iter()
+                        .find(|t| t.id() == id)
+                        .map(|t| t.name())
+                })
+                .unwrap_or_else(|| id.to_string())
+        }
+
+        fn game_name(&self, game: &Game) -> String {
+            format!("{} v {}", self.team_name(game.home_team_id()), self.team_name(game.away_team_id()))
+        }
+
         fn validate_not_empty(&self, field: &GString, message: &str) -> bool {
             if field.is_empty() {
                 let buttons = vec!["Ok".to_string()];
@@ -401,37 +552,6 @@ mod imp {
                 }
             }
         }
-
-        fn setup_date_selector(&self, entry: &Entry, popover: &Popover, calendar: &Calendar) {
-            // Show the popover when the button is clicked
-            entry.connect_icon_press(clone!(#[weak] entry, #[weak] popover, #[weak] calendar, move |_, icon| {
-
-                // set the Calendar to the date in the entry, if it is a valid date
-                if icon == gtk::EntryIconPosition::Secondary {
-                    let date_str = entry.text().to_string().clone();
-                    if let Ok(date) = date_str.parse::<NaiveDate>() {
-                        if let Ok(date) = glib::DateTime::new(&TimeZone::local(),
-                            date.year(),
-                            date.month() as i32,
-                            date.day() as i32,
-                            0i32, 0i32, 0f64)
-                        {
-                            calendar.select_day(&date);
-                        }
-                    }
-                    popover.popup();
-                }
-            }));
-
-            // Update the entry when a date is selected in the calendar
-            calendar.connect_day_selected(glib::clone!(#[weak] entry, #[weak] popover, move |calendar| {
-            let date = calendar.date();
-            let formatted_date = format!("{:04}-{:02}-{:02}", date.year(), date.month(), date.day_of_month());
-            entry.set_text(&formatted_date);
-
-            popover.popdown(); // Close the popover after selecting a date
-            }));
-        }
     }
 
     #[glib::object_subclass]
@@ -455,13 +575,13 @@ mod imp {
             self.parent_constructed();
             self.initialise();
 
-            self.setup_date_selector(
+            setup_date_selector(
                 &self.start_date,
                 &self.start_date_popover,
                 &self.start_date_calendar,
             );
 
-            self.setup_date_selector(
+            setup_date_selector(
                 &self.end_date,
                 &self.end_date_popover,
                 &self.end_date_calendar,
@@ -488,8 +608,9 @@ mod imp {
                     if let Some(games) = model.model().and_downcast_ref::<Games>() {
                         let mut game_list = games.imp().games.write().unwrap();
                         let size = game_list.len() as u32;
-                        let binding = Teams::new();
-                        let teams  = binding.imp().teams.read().unwrap();
+                        let teams = window.team_model.borrow().as_ref()
+                            .map(|teams| teams.imp().teams.read().unwrap().clone())
+                            .unwrap_or_default();
 
                         if let Some(playdays) = window.playday_model.borrow().as_ref() {
                             game_allocator::add_extra_game(
@@ -514,22 +635,15 @@ mod imp {
             }));
 
             self.btn_revert.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
-                let round_id = window.round_id.borrow().clone();
-                let read = async_std::task::block_on(
-                    async move {
-                        let pool = db::manager().pool();
-                        if let Some(id) = round_id {
-                            round::get(pool, id).await
-                        } else {
-                            warn!("No round ID set, cannot revert");
-                            Ok(None)
-                        }
-                    });
-                if let Ok(round) = read {
-                    glib::MainContext::default().spawn_local( async move {
-                        window.set_round(round);
-                    });
-                }
+                let Some(round_id) = *window.round_id.borrow() else {
+                    warn!("No round ID set, cannot revert");
+                    return;
+                };
+                glib::spawn_future_local(clone!(#[weak] window, async move {
+                    if let Ok(round) = while_loading(repository().get_round(round_id)).await {
+                        window.show_round(round).await;
+                    }
+                }));
             }));
         }
 
@@ -573,6 +687,105 @@ pub(super) fn build_column_factory_team<M: IsA<ListModel>, F: Fn(DropDown, &T) +
     factory
 }
 
+/// Build a factory for an editable score column. Only digits can be entered, and an empty
+/// entry means the game has no result yet.
+pub(super) fn build_column_factory_score<FGet: Fn(&Game) -> Option<i32> + 'static, FSet: Fn(&Game, Option<i32>) + 'static>(get_score: FGet, set_score: FSet) -> SignalListItemFactory {
+    let factory = SignalListItemFactory::new();
+    let set_score = Rc::new(set_score);
+    factory.connect_setup(move |_, list_item| {
+        let list_item = list_item
+            .downcast_ref::<ListItem>()
+            .expect("Needs to be ListItem");
+        let entry = Entry::new();
+        entry.set_width_chars(4);
+        entry.set_max_length(3);
+        entry.set_input_purpose(InputPurpose::Digits);
+
+        let set_score = Rc::clone(&set_score);
+        entry.connect_changed(clone!(#[weak] list_item, move |entry| {
+            let text = entry.text();
+            let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+            if digits != text.as_str() {
+                // This fires "changed" again with the cleaned up text
+                entry.set_text(&digits);
+                return;
+            }
+            if let Some(game) = list_item.item().and_downcast::<Game>() {
+                set_score(&game, digits.parse::<i32>().ok());
+            }
+        }));
+        list_item.set_child(Some(&entry));
+    });
+
+    factory.connect_bind(move |_, list_item| {
+        let list_item = list_item
+            .downcast_ref::<ListItem>()
+            .expect("Needs to be ListItem");
+        let game = list_item
+            .item()
+            .and_downcast::<Game>()
+            .expect("The item has to be a `Game`.");
+        let entry = list_item
+            .child()
+            .and_downcast::<Entry>()
+            .expect("The child has to be an `Entry`.");
+        entry.set_text(&get_score(&game).map(|s| s.to_string()).unwrap_or_default());
+    });
+    factory
+}
+
+/// Build a factory for the kickoff column, where the local time of the game is entered as "hh:mm".
+/// An empty entry means the time is not known yet.
+pub(super) fn build_column_factory_kickoff() -> SignalListItemFactory {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(move |_, list_item| {
+        let list_item = list_item
+            .downcast_ref::<ListItem>()
+            .expect("Needs to be ListItem");
+        let entry = Entry::new();
+        entry.set_width_chars(5);
+        entry.set_max_length(5);
+        entry.set_placeholder_text(Some("hh:mm"));
+
+        entry.connect_changed(clone!(#[weak] list_item, move |entry| {
+            let Some(game) = list_item.item().and_downcast::<Game>() else {
+                return;
+            };
+            let text = entry.text();
+            if text.trim().is_empty() {
+                entry.remove_css_class("error");
+                game.set_kickoff(None);
+                game.set_kickoff_error(false);
+            } else if let Some(kickoff) = kickoff_at(game.game_date(), &text) {
+                entry.remove_css_class("error");
+                game.set_kickoff(Some(kickoff));
+                game.set_kickoff_error(false);
+            } else {
+                // Keep the game from being saved with the kickoff it had before
+                entry.add_css_class("error");
+                game.set_kickoff_error(true);
+            }
+        }));
+        list_item.set_child(Some(&entry));
+    });
+
+    factory.connect_bind(move |_, list_item| {
+        let list_item = list_item
+            .downcast_ref::<ListItem>()
+            .expect("Needs to be ListItem");
+        let game = list_item
+            .item()
+            .and_downcast::<Game>()
+            .expect("The item has to be a `Game`.");
+        let entry = list_item
+            .child()
+            .and_downcast::<Entry>()
+            .expect("The child has to be an `Entry`.");
+        entry.set_text(&game.kickoff().map(|k| to_hm(&k)).unwrap_or_default());
+    });
+    factory
+}
+
 fn connect_bind<F: Fn(DropDown, &T) + 'static, T: IsA<Object>>(f: F, factory: &SignalListItemFactory) {
     factory.connect_bind(move |_, list_item| {
         // Get `StringObject` from `ListItem`
@@ -631,11 +844,49 @@ fn connect_drop_team<M: IsA<ListModel>>(model: RefCell<Option<M>>, factory: &Sig
 fn delete_game(button: &Button) {
     if let Some(value) = button.action_target_value() {
         if let Some(id) = value.get::<i32>() {
-            let pool = db::manager().pool();
-            glib::spawn_future_local(clone!(async move {
-                let _ = game::delete(pool, id).await;
-                event::manager().notify_listeners(Event::GamesChanged);
+            // A game that has not been saved is only in this dialog
+            if id <= 0 {
+                remove_unsaved_game(button, id);
+                return;
+            }
+            let repository = repository();
+            glib::spawn_future_local(clone!(#[weak] button, async move {
+                let dependents = match repository.get_game_dependents(id).await {
+                    Ok(dependents) => dependents,
+                    Err(e) => {
+                        show_error_dialog(&button.root(), e.to_string().as_str());
+                        return;
+                    }
+                };
+                let body = if dependents.is_empty() {
+                    "No tips have been made on this game.".to_string()
+                } else {
+                    format!("This game has {}, which will be deleted with it.", dependents.describe())
+                };
+                if !confirm_delete(&button.root(), "Delete game?", &body).await {
+                    return;
+                }
+                match repository.delete_game(id).await {
+                    Ok(_) => event::manager().notify_listeners(Event::GamesChanged),
+                    Err(e) => show_error_dialog(&button.root(), format!("The game was not deleted. {}", e).as_str()),
+                }
             }));
         }
     }
 }
+
+/// Take a game that has not been saved yet out of the games shown with the button.
+fn remove_unsaved_game(button: &Button, id: i32) {
+    let Some(column_view) = button.ancestor(ColumnView::static_type()).and_downcast::<ColumnView>() else {
+        return;
+    };
+    if let Some(model) = column_view.model().and_downcast_ref::<NoSelection>()
+        && let Some(games) = model.model().and_downcast_ref::<Games>() {
+        let mut game_list = games.imp().games.write().unwrap();
+        if let Some(position) = game_list.iter().position(|g| g.id() == id) {
+            game_list.remove(position);
+            drop(game_list);
+            model.items_changed(position as u32, 1, 0);
+        }
+    }
+}
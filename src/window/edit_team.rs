@@ -3,11 +3,10 @@ use gtk::{gio, glib};
 mod imp {
     use crate::event;
     use crate::event::Event;
-    use crate::model::team;
     use crate::model::team::Team;
-    use crate::util::db;
-    use crate::window::util::{connect_escape, validate_not_empty};
-    use adw::prelude::{ButtonExt, EditableExt, GtkWindowExt};
+    use crate::model::repository::repository;
+    use crate::window::util::{connect_escape, show_error_dialog, validate_not_empty};
+    use adw::prelude::{ButtonExt, EditableExt, GtkWindowExt, WidgetExt};
     use adw::subclass::prelude::{CompositeTemplate, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt, WidgetClassExt, WindowImpl};
     use gtk::glib::clone;
     use gtk::glib::subclass::InitializingObject;
@@ -74,27 +73,26 @@ mod imp {
                 alert.show(Some(&self.obj().clone()));
                 false
             } else {
-                let pool = db::manager().pool();
-                if self.team_id.borrow().is_some() {
-                    // Update the team
-                    let id = self.team_id.borrow().unwrap();
-                    let name = self.team_name.text().to_string().clone();
-                    let nickname = self.team_nickname.text().to_string().clone();
-                    glib::spawn_future_local(clone!( async move {
-                        info!("Updating team {}", id);
-                        let _ = team::update(pool, id, name, nickname).await;
-                        event::manager().notify_listeners(Event::TeamsChanged);
-                    }));
-
-                } else {
-                    // Create a new team
-                    let name = self.team_name.text().to_string().clone();
-                    let nickname = self.team_nickname.text().to_string().clone();
-                    glib::spawn_future_local(clone!( async move {
-                        let _ = team::insert(pool, name, nickname).await;
-                        event::manager().notify_listeners(Event::TeamsChanged);
-                    }));
-                }
+                let repository = repository();
+                let id = *self.team_id.borrow();
+                let name = self.team_name.text().to_string();
+                let nickname = self.team_nickname.text().to_string();
+                glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                    let result = match id {
+                        Some(id) => {
+                            info!("Updating team {}", id);
+                            repository.update_team(id, name, nickname).await.map(|_| ())
+                        }
+                        None => repository.insert_team(name, nickname).await.map(|_| ()),
+                    };
+                    match result {
+                        Ok(()) => {
+                            event::manager().notify_listeners(Event::TeamsChanged);
+                            window.obj().close();
+                        }
+                        Err(e) => show_error_dialog(&window.obj().root(), e.to_string().as_str()),
+                    }
+                }));
                 true
             }
         }
@@ -125,8 +123,9 @@ mod imp {
             }));
 
             self.btn_ok.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
-                if window.validate() && window.save_team() {
-                    window.obj().close();
+                // The dialog closes itself once the team has been saved
+                if window.validate() {
+                    window.save_team();
                 }
             }));
 
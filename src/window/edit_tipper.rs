@@ -3,11 +3,12 @@ use gtk::{gio, glib};
 mod imp {
     use crate::event;
     use crate::event::Event;
-    use crate::model::tipper;
+    use crate::model::competition;
+    use crate::model::error::ModelError;
     use crate::model::tipper::Tipper;
-    use crate::util::db;
-    use crate::window::util::{connect_escape, validate_not_empty};
-    use adw::prelude::{ButtonExt, GtkWindowExt};
+    use crate::model::repository::repository;
+    use crate::window::util::{connect_escape, show_error_dialog, validate_not_empty};
+    use adw::prelude::{ButtonExt, GtkWindowExt, WidgetExt};
     use adw::subclass::prelude::{CompositeTemplate, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt, WidgetClassExt, WindowImpl};
     use gtk::glib::clone;
     use gtk::glib::subclass::InitializingObject;
@@ -75,26 +76,33 @@ mod imp {
                 alert.show(Some(&self.obj().clone()));
                 false
             } else {
-                let pool = db::manager().pool();
-                if self.tipper_id.borrow().is_some() {
-                    // Update the tipper
-                    let id = self.tipper_id.borrow().unwrap();
-                    let name = self.tipper_name.text().to_string().clone();
-                    let email = self.tipper_email.text().to_string().clone();
-                    glib::spawn_future_local(clone!( async move {
-                        info!("Updating tipper {}", id);
-                        let _ = tipper::update(pool, id, name, email).await;
-                        event::manager().notify_listeners(Event::TippersChanged);
-                    }));
-                } else {
-                    // Create a new tipper
-                    let name = self.tipper_name.text().to_string().clone();
-                    let email = self.tipper_email.text().to_string().clone();
-                    glib::spawn_future_local(clone!( async move {
-                        let _ = tipper::insert(pool, name, email).await;
-                        event::manager().notify_listeners(Event::TippersChanged);
-                    }));
-                }
+                let repository = repository();
+                let id = *self.tipper_id.borrow();
+                let name = self.tipper_name.text().to_string();
+                let email = self.tipper_email.text().to_string();
+                glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                    let result = match id {
+                        Some(id) => {
+                            info!("Updating tipper {}", id);
+                            repository.update_tipper(id, name, email).await.map(|_| ())
+                        }
+                        // A new tipper joins the competition being shown
+                        None => match competition::current() {
+                            Some(competition_id) => repository.insert_tipper_in_competition(name, email, competition_id).await.map(|_| ()),
+                            None => repository.insert_tipper(name, email).await.map(|_| ()),
+                        },
+                    };
+                    match result {
+                        Ok(()) => {
+                            event::manager().notify_listeners(Event::TippersChanged);
+                            window.obj().close();
+                        }
+                        Err(ModelError::Duplicate(_)) => {
+                            show_error_dialog(&window.obj().root(), "Another tipper already has that name or email");
+                        }
+                        Err(e) => show_error_dialog(&window.obj().root(), e.to_string().as_str()),
+                    }
+                }));
                 true
             }
         }
@@ -126,8 +134,9 @@ mod imp {
             }));
 
             self.btn_ok.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
-                if window.validate() && window.save_tipper() {
-                    window.obj().close();
+                // The dialog closes itself once the tipper has been saved
+                if window.validate() {
+                    window.save_tipper();
                 }
             }));
 
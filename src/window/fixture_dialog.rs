@@ -0,0 +1,329 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use gtk::{gio, glib};
+
+mod imp {
+    use crate::event;
+    use crate::event::Event;
+    use crate::model::round::{Round, Rounds};
+    use crate::model::team::{Team, Teams};
+    use crate::model::season;
+    use crate::util::date::to_ymd;
+    use crate::model::repository::repository;
+    use crate::util::fixture::{generate_season, FixtureOptions, FixtureRound};
+    use crate::window::util::{build_column_factory, connect_escape, setup_date_selector, show_error_dialog};
+    use adw::prelude::{ButtonExt, EditableExt, GtkWindowExt, WidgetExt};
+    use adw::subclass::prelude::{CompositeTemplate, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt, WidgetClassExt, WindowImpl};
+    use chrono::{Duration, Local, NaiveDate};
+    use gtk::glib::{clone, BoxedAnyObject};
+    use gtk::glib::subclass::InitializingObject;
+    use gtk::subclass::widget::{CompositeTemplateInitializingExt, WidgetImpl};
+    use gtk::{gio, glib, Button, Calendar, ColumnView, ColumnViewColumn, CompositeTemplate, Entry, Label, NoSelection, Popover, SpinButton, TemplateChild};
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::ops::Deref;
+
+    /// One game in the draw preview.
+    pub struct PreviewRow {
+        round: i32,
+        date: NaiveDate,
+        home: String,
+        away: String,
+    }
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/fixture_dialog.ui")]
+    pub struct FixtureDialog {
+        #[template_child]
+        pub fixture_view: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub first_round: TemplateChild<SpinButton>,
+        #[template_child]
+        pub start_date: TemplateChild<Entry>,
+        #[template_child]
+        pub start_date_popover: TemplateChild<Popover>,
+        #[template_child]
+        pub start_date_calendar: TemplateChild<Calendar>,
+        #[template_child]
+        pub rounds: TemplateChild<SpinButton>,
+        #[template_child]
+        pub play_days: TemplateChild<SpinButton>,
+        #[template_child]
+        pub max_consecutive_home: TemplateChild<SpinButton>,
+        #[template_child]
+        pub btn_preview: TemplateChild<Button>,
+        #[template_child]
+        pub preview_list: TemplateChild<ColumnView>,
+        #[template_child]
+        pub col_round: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_date: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_home: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_away: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub btn_ok: TemplateChild<Button>,
+        #[template_child]
+        pub btn_cancel: TemplateChild<Button>,
+
+        teams: RefCell<Vec<Team>>,
+        existing_rounds: RefCell<Vec<Round>>,
+        fixture: RefCell<Option<Vec<FixtureRound>>>,
+    }
+
+    impl FixtureDialog {
+        /// Fetch the teams and rounds, then suggest settings that follow on from the last round.
+        async fn initialise(&self) {
+            self.btn_preview.set_sensitive(false);
+            let teams = Teams::loaded().await.imp().teams.read().expect("Unable to get a lock on teams").clone();
+            let rounds = Rounds::loaded().await.imp().rounds.read().expect("Failed to read rounds").clone();
+            self.teams.replace(teams);
+            self.existing_rounds.replace(rounds);
+
+            // Follow on from the last round, if there is one
+            let last_round = self.existing_rounds.borrow()
+                .iter()
+                .max_by_key(|r| r.number())
+                .cloned();
+            match last_round {
+                Some(last_round) => {
+                    self.first_round.set_value((last_round.number() + 1) as f64);
+                    self.start_date.set_text(&to_ymd(&(last_round.start_date() + Duration::days(7))));
+                }
+                None => {
+                    self.first_round.set_value(1.0);
+                    self.start_date.set_text(&to_ymd(&Local::now().date_naive()));
+                }
+            }
+
+            // Default to a single round robin
+            let team_count = self.teams.borrow().len();
+            let rounds_per_cycle = if team_count % 2 == 0 { team_count.saturating_sub(1) } else { team_count };
+            self.rounds.set_value(rounds_per_cycle.max(1) as f64);
+            self.btn_preview.set_sensitive(true);
+        }
+
+        fn options(&self) -> Option<FixtureOptions> {
+            match self.start_date.text().parse::<NaiveDate>() {
+                Ok(start) => Some(FixtureOptions {
+                    first_round_number: self.first_round.value_as_int(),
+                    start,
+                    rounds: self.rounds.value_as_int() as usize,
+                    play_days: self.play_days.value_as_int() as u32,
+                    max_consecutive_home: self.max_consecutive_home.value_as_int() as u32,
+                }),
+                Err(_) => {
+                    show_error_dialog(&self.obj().root(), "Start Date is not a valid date");
+                    None
+                }
+            }
+        }
+
+        /// Check the new rounds will not clash with round numbers already in use.
+        fn validate_round_numbers(&self, options: &FixtureOptions) -> bool {
+            let last_number = options.first_round_number + options.rounds as i32 - 1;
+            let clash = self.existing_rounds.borrow()
+                .iter()
+                .map(|r| r.number())
+                .find(|n| (options.first_round_number..=last_number).contains(n));
+            match clash {
+                Some(number) => {
+                    show_error_dialog(&self.obj().root(), format!("Round {} already exists", number).as_str());
+                    false
+                }
+                None => true,
+            }
+        }
+
+        fn preview(&self) {
+            self.invalidate();
+            let Some(options) = self.options() else {
+                return;
+            };
+            if !self.validate_round_numbers(&options) {
+                return;
+            }
+
+            let teams = self.teams.borrow().clone();
+            let names: HashMap<i32, String> = teams.iter().map(|t| (t.id(), t.name())).collect();
+            let mut team_ids: Vec<i32> = teams.iter().map(|t| t.id()).collect();
+            team_ids.shuffle(&mut thread_rng());
+
+            match generate_season(&team_ids, &options) {
+                Ok(fixture) => {
+                    let store = gio::ListStore::new::<BoxedAnyObject>();
+                    for round in &fixture {
+                        for game in &round.games {
+                            store.append(&BoxedAnyObject::new(PreviewRow {
+                                round: round.number,
+                                date: game.game_date,
+                                home: names.get(&game.home_team_id).cloned().unwrap_or_default(),
+                                away: names.get(&game.away_team_id).cloned().unwrap_or_default(),
+                            }));
+                        }
+                        for team_id in &round.byes {
+                            store.append(&BoxedAnyObject::new(PreviewRow {
+                                round: round.number,
+                                date: round.start_date,
+                                home: names.get(team_id).cloned().unwrap_or_default(),
+                                away: "Bye".to_string(),
+                            }));
+                        }
+                    }
+                    self.preview_list.set_model(Some(&NoSelection::new(Some(store))));
+                    self.fixture.replace(Some(fixture));
+                    self.btn_ok.set_sensitive(true);
+                }
+                Err(message) => show_error_dialog(&self.obj().root(), message.as_str()),
+            }
+        }
+
+        /// Forget the previewed draw once any of the settings change.
+        fn invalidate(&self) {
+            self.fixture.replace(None);
+            self.preview_list.set_model(None::<&NoSelection>);
+            self.btn_ok.set_sensitive(false);
+        }
+
+        fn save_fixture(&self) {
+            let Some(fixture) = self.fixture.borrow().clone() else {
+                return;
+            };
+            let Some(season_id) = season::current() else {
+                show_error_dialog(&self.obj().root(), "Choose a season for the draw first");
+                return;
+            };
+            let repository = repository();
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                match repository.insert_fixture(season_id, &fixture).await {
+                    Ok(round_ids) => {
+                        if let Some(round_id) = round_ids.first() {
+                            event::manager().notify_listeners(Event::RoundsChanged { round_id: *round_id });
+                        }
+                        event::manager().notify_listeners(Event::GamesChanged);
+                        window.obj().close();
+                    }
+                    Err(message) => show_error_dialog(&window.obj().root(), message.to_string().as_str()),
+                }
+            }));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FixtureDialog {
+        const NAME: &'static str = "FixtureDialog";
+        type Type = super::FixtureDialog;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for FixtureDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                window.initialise().await;
+            }));
+
+            setup_date_selector(
+                &self.start_date,
+                &self.start_date_popover,
+                &self.start_date_calendar,
+            );
+
+            self.col_round.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().round.to_string().as_str());
+            })));
+            self.col_date.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().date.format("%a %d %b").to_string().as_str());
+            })));
+            self.col_home.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().home.as_str());
+                label.set_xalign(0.0);
+            })));
+            self.col_away.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().away.as_str());
+                label.set_xalign(0.0);
+            })));
+
+            for spin in [&self.first_round, &self.rounds, &self.play_days, &self.max_consecutive_home] {
+                spin.connect_value_changed(clone!(#[weak(rename_to = window)] self, move |_| {
+                    window.invalidate();
+                }));
+            }
+            self.start_date.connect_changed(clone!(#[weak(rename_to = window)] self, move |_| {
+                window.invalidate();
+            }));
+
+            self.btn_preview.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.preview();
+            }));
+
+            self.btn_cancel.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.obj().close();
+            }));
+
+            self.btn_ok.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.save_fixture();
+            }));
+
+            connect_escape(self.fixture_view.deref(), self.btn_cancel.deref());
+        }
+    }
+
+    impl WidgetImpl for FixtureDialog {}
+
+    impl WindowImpl for FixtureDialog {}
+}
+
+glib::wrapper! {
+    pub struct FixtureDialog(ObjectSubclass<imp::FixtureDialog>)
+        @extends gtk::Window, gtk::Widget,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl FixtureDialog {
+    pub fn new() -> Self {
+        glib::Object::new::<FixtureDialog>()
+    }
+}
+
+impl Default for FixtureDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,323 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use gtk::{gio, glib};
+
+mod imp {
+    use crate::event;
+    use crate::event::{while_loading, Event};
+    use crate::model::error::ModelError;
+    use crate::model::game::Game;
+    use crate::model::repository::{connected_repository, repository};
+    use crate::model::round::Rounds;
+    use crate::model::season;
+    use crate::model::team::Teams;
+    use crate::util::date::{to_dmy, to_hm};
+    use crate::util::fixture::FixtureRound;
+    use crate::util::import::{build_fixture, parse_fixture, round_numbers, ImportedGame, Names};
+    use crate::window::util::{build_column_factory, connect_escape, open_import_file, show_error_dialog};
+    use adw::prelude::{ButtonExt, Cast, GtkWindowExt, TextBufferExt, TextViewExt, WidgetExt};
+    use adw::subclass::prelude::{CompositeTemplate, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt, WidgetClassExt, WindowImpl};
+    use gtk::glib::{clone, BoxedAnyObject};
+    use gtk::glib::subclass::InitializingObject;
+    use gtk::subclass::widget::{CompositeTemplateInitializingExt, WidgetImpl};
+    use gtk::{gio, glib, Button, ColumnView, ColumnViewColumn, CompositeTemplate, Label, NoSelection, TemplateChild, TextView};
+    use log::error;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::ops::Deref;
+
+    /// One game in the import preview.
+    pub struct PreviewRow {
+        line: usize,
+        round: String,
+        date: String,
+        game: String,
+        venue: String,
+        /// What importing the line will do, or why it cannot be imported.
+        status: String,
+        resolved: bool,
+    }
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/fixture_import_dialog.ui")]
+    pub struct FixtureImportDialog {
+        #[template_child]
+        pub import_view: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub btn_open: TemplateChild<Button>,
+        #[template_child]
+        pub import_text: TemplateChild<TextView>,
+        #[template_child]
+        pub btn_preview: TemplateChild<Button>,
+        #[template_child]
+        pub preview_list: TemplateChild<ColumnView>,
+        #[template_child]
+        pub col_line: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_round: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_date: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_game: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_venue: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_status: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub summary: TemplateChild<Label>,
+        #[template_child]
+        pub btn_ok: TemplateChild<Button>,
+        #[template_child]
+        pub btn_cancel: TemplateChild<Button>,
+
+        fixture: RefCell<Vec<FixtureRound>>,
+    }
+
+    impl FixtureImportDialog {
+        /// Fetch the games of the rounds already in the season, then read each line of the file.
+        async fn preview(&self) {
+            self.invalidate();
+            let buffer = self.import_text.buffer();
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string();
+            let teams: Vec<Names> = Teams::loaded().await.imp().teams.read().expect("Unable to get a lock on teams")
+                .iter()
+                .map(|t| (t.id(), vec![t.name(), t.nickname()]))
+                .collect();
+            let numbers = round_numbers(&text);
+            let rounds: Vec<(i32, i32)> = Rounds::loaded().await.imp().rounds.read().expect("Failed to read rounds")
+                .iter()
+                .filter(|r| numbers.contains(&r.number()))
+                .map(|r| (r.number(), r.id()))
+                .collect();
+            let Some(repository) = connected_repository() else {
+                return;
+            };
+            let loaded = while_loading(async move {
+                let mut games = HashMap::new();
+                for (number, round_id) in rounds {
+                    games.insert(number, repository.get_games_for_round(round_id).await?);
+                }
+                Ok::<_, ModelError>(games)
+            }).await;
+            let games: HashMap<i32, Vec<Game>> = match loaded {
+                Ok(games) => games,
+                Err(err) => {
+                    error!("Error loading games for the fixture: {}", err);
+                    show_error_dialog(&self.obj().root(), err.to_string().as_str());
+                    return;
+                }
+            };
+
+            let imported = parse_fixture(&text, &teams, &games);
+            let nickname = |team_id: i32| teams.iter()
+                .find(|(id, _)| *id == team_id)
+                .map(|(_, names)| names[1].clone())
+                .unwrap_or_default();
+
+            let store = gio::ListStore::new::<BoxedAnyObject>();
+            for imported in &imported {
+                let (game, date) = match &imported.game {
+                    Some(game) => (
+                        format!("{} v {}", nickname(game.home_team_id), nickname(game.away_team_id)),
+                        match &game.kickoff {
+                            Some(kickoff) => format!("{} {}", to_dmy(&game.game_date), to_hm(kickoff)),
+                            None => to_dmy(&game.game_date),
+                        },
+                    ),
+                    None => (imported.text.clone(), String::new()),
+                };
+                store.append(&BoxedAnyObject::new(PreviewRow {
+                    line: imported.line,
+                    round: imported.round_number.map(|n| n.to_string()).unwrap_or_default(),
+                    date,
+                    game,
+                    venue: imported.game.as_ref().and_then(|g| g.venue.clone()).unwrap_or_default(),
+                    status: Self::status(imported, &games),
+                    resolved: imported.problem.is_none(),
+                }));
+            }
+            self.preview_list.set_model(Some(&NoSelection::new(Some(store))));
+
+            let team_ids: Vec<i32> = teams.iter().map(|(id, _)| *id).collect();
+            let existing_rounds: Vec<i32> = games.keys().copied().collect();
+            let fixture = build_fixture(&imported, &team_ids, &existing_rounds);
+            let game_count: usize = fixture.iter().map(|r| r.games.len()).sum();
+            let new_rounds = fixture.iter().filter(|r| !existing_rounds.contains(&r.number)).count();
+            let problems = imported.iter().filter(|g| g.problem.is_some()).count();
+            let summary = match problems {
+                0 => format!("{} games to import, {} new rounds", game_count, new_rounds),
+                _ => format!("{} games to import, {} new rounds, {} lines unmatched or conflicting",
+                    game_count, new_rounds, problems),
+            };
+            self.summary.set_label(summary.as_str());
+            self.btn_ok.set_sensitive(game_count > 0);
+            self.fixture.replace(fixture);
+        }
+
+        /// What importing a line will do, such as "New round" or "Added to round 3".
+        fn status(imported: &ImportedGame, games: &HashMap<i32, Vec<Game>>) -> String {
+            if let Some(problem) = &imported.problem {
+                return problem.clone();
+            }
+            if imported.existing {
+                return "Already in the fixture".to_string();
+            }
+            match imported.round_number {
+                Some(number) if games.contains_key(&number) => format!("Added to round {}", number),
+                Some(_) => "New round".to_string(),
+                None => String::new(),
+            }
+        }
+
+        /// Forget the previewed fixture once the text changes.
+        fn invalidate(&self) {
+            self.fixture.borrow_mut().clear();
+            self.preview_list.set_model(None::<&NoSelection>);
+            self.summary.set_label("");
+            self.btn_ok.set_sensitive(false);
+        }
+
+        fn save_fixture(&self) {
+            let Some(season_id) = season::current() else {
+                show_error_dialog(&self.obj().root(), "Choose a season for the fixture first");
+                return;
+            };
+            let fixture = self.fixture.borrow().clone();
+            let repository = repository();
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                match repository.insert_fixture(season_id, &fixture).await {
+                    Ok(round_ids) => {
+                        if let Some(round_id) = round_ids.first() {
+                            event::manager().notify_listeners(Event::RoundsChanged { round_id: *round_id });
+                        }
+                        event::manager().notify_listeners(Event::GamesChanged);
+                        window.obj().close();
+                    }
+                    Err(err) => show_error_dialog(&window.obj().root(), err.to_string().as_str()),
+                }
+            }));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FixtureImportDialog {
+        const NAME: &'static str = "FixtureImportDialog";
+        type Type = super::FixtureImportDialog;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for FixtureImportDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.col_line.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().line.to_string().as_str());
+            })));
+            self.col_round.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().round.as_str());
+            })));
+            self.col_date.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().date.as_str());
+                label.set_xalign(0.0);
+            })));
+            self.col_game.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().game.as_str());
+                label.set_xalign(0.0);
+            })));
+            self.col_venue.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().venue.as_str());
+                label.set_xalign(0.0);
+            })));
+            // Unmatched and conflicting lines are shown as errors
+            self.col_status.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                let row = row.borrow::<PreviewRow>();
+                label.set_label(row.status.as_str());
+                label.set_xalign(0.0);
+                if row.resolved {
+                    label.remove_css_class("error");
+                } else {
+                    label.add_css_class("error");
+                }
+            })));
+
+            self.import_text.buffer().connect_changed(clone!(#[weak(rename_to = window)] self, move |_| {
+                window.invalidate();
+            }));
+
+            self.btn_open.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                open_import_file(window.obj().upcast_ref(), "Import Fixture", &window.import_text.buffer());
+            }));
+
+            self.btn_preview.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                glib::spawn_future_local(clone!(#[weak] window, async move {
+                    window.preview().await;
+                }));
+            }));
+
+            self.btn_cancel.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.obj().close();
+            }));
+
+            self.btn_ok.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.save_fixture();
+            }));
+
+            connect_escape(self.import_view.deref(), self.btn_cancel.deref());
+        }
+    }
+
+    impl WidgetImpl for FixtureImportDialog {}
+
+    impl WindowImpl for FixtureImportDialog {}
+}
+
+glib::wrapper! {
+    pub struct FixtureImportDialog(ObjectSubclass<imp::FixtureImportDialog>)
+        @extends gtk::Window, gtk::Widget,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl FixtureImportDialog {
+    pub fn new() -> Self {
+        glib::Object::new::<FixtureImportDialog>()
+    }
+}
+
+impl Default for FixtureImportDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -23,31 +23,340 @@
  */
 #![forbid(unsafe_code)]
 
+use crate::event;
+use crate::event::Event;
+use crate::model::competition::{self, Competition};
+use crate::model::error::ModelError;
+use crate::model::repository::{connected_repository, repository};
+use crate::model::season::{self, Season};
+use crate::util::db;
+use crate::util::lockout::{LateTips, Lockout};
+use crate::window::util::show_error_dialog;
+use crate::window::ladder_view::LadderView;
+use crate::window::leaderboard_view::LeaderboardView;
 use crate::window::round_view::RoundView;
 use crate::window::team_view::TeamView;
+use crate::window::tip_view::TipView;
 use crate::window::tipper_view::TipperView;
 use adw::subclass::prelude::AdwApplicationWindowImpl;
 use adw::subclass::prelude::*;
+use glib::clone;
 use glib::subclass::InitializingObject;
 use glib::Propagation;
+use adw::prelude::{AdwDialogExt, AlertDialogExt, AlertDialogExtManual};
+use gettextrs::gettext;
+use gtk::prelude::{BoxExt, CheckButtonExt, EditableExt, WidgetExt};
 use gtk::{glib, CompositeTemplate};
+use std::cell::{Cell, RefCell};
 
 // Object holding the state
 #[derive(CompositeTemplate, Default)]
 #[template(resource = "/com/shartrec/kelpie_tipping/window.ui")]
 pub struct Window {
+    #[template_child]
+    pub banner: TemplateChild<adw::Banner>,
+    #[template_child]
+    pub loading: TemplateChild<gtk::Spinner>,
+    #[template_child]
+    pub season_list: TemplateChild<gtk::DropDown>,
+    #[template_child]
+    pub competition_list: TemplateChild<gtk::DropDown>,
     #[template_child]
     pub team_view: TemplateChild<TeamView>,
     #[template_child]
+    pub ladder_view: TemplateChild<LadderView>,
+    #[template_child]
     pub tipper_view: TemplateChild<TipperView>,
     #[template_child]
     pub round_view: TemplateChild<RoundView>,
+    #[template_child]
+    pub tip_view: TemplateChild<TipView>,
+    #[template_child]
+    pub leaderboard_view: TemplateChild<LeaderboardView>,
 
+    /// How many database loads are still on their way.
+    loads_pending: Cell<u32>,
+    /// The seasons in the same order as the season list.
+    seasons: RefCell<Vec<Season>>,
+    /// The competitions in the same order as the competition list.
+    competitions: RefCell<Vec<Competition>>,
+    /// Set while a drop down list is being refilled, so the selection changes are not acted on.
+    filling_list: Cell<bool>,
 }
 
 impl Window {
+    fn initialise(&self) {
+        if let Some(rx) = event::manager().register_listener() {
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                while let Ok(ev) = rx.recv().await {
+                    match ev {
+                        Event::ConnectionChanged => window.connection_changed(),
+                        Event::LoadingStarted => window.loading_changed(1),
+                        Event::LoadingFinished => window.loading_changed(-1),
+                        _ => {}
+                    }
+                }
+            }));
+        }
+        self.banner.connect_button_clicked(clone!(#[weak(rename_to = window)] self, move |_banner| {
+            window.banner.set_button_label(None);
+            glib::spawn_future_local(async move {
+                let _ = db::connect().await;
+            });
+        }));
+        self.season_list.connect_selected_notify(clone!(#[weak(rename_to = window)] self, move |list| {
+            if window.filling_list.get() {
+                return;
+            }
+            if let Some(season) = window.seasons.borrow().get(list.selected() as usize) {
+                season::set_current(season.id);
+            }
+        }));
+        self.competition_list.connect_selected_notify(clone!(#[weak(rename_to = window)] self, move |list| {
+            if window.filling_list.get() {
+                return;
+            }
+            if let Some(competition) = window.competitions.borrow().get(list.selected() as usize) {
+                competition::set_current(competition.id);
+            }
+        }));
+
+        self.show_connection_status();
+        glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+            window.load_seasons(None).await;
+            window.load_competitions(None).await;
+        }));
+    }
+
+    /// Replace the names in a drop down list and select one of them, without acting on the selection.
+    fn fill_list(&self, list: &gtk::DropDown, names: &[&str], position: Option<usize>) {
+        self.filling_list.set(true);
+        list.set_model(Some(&gtk::StringList::new(names)));
+        if let Some(position) = position {
+            list.set_selected(position as u32);
+        }
+        self.filling_list.set(false);
+    }
+
+    /// Fill the season list and show the season asked for, otherwise the season already shown or
+    /// failing that the latest season.
+    async fn load_seasons(&self, select: Option<i32>) {
+        let seasons = match connected_repository() {
+            Some(repository) => repository.get_all_seasons().await.unwrap_or_default(),
+            None => vec![],
+        };
+        let wanted = select.or(season::current());
+        let position = wanted
+            .and_then(|id| seasons.iter().position(|s| s.id == id))
+            .or(seasons.len().checked_sub(1));
+
+        let names: Vec<&str> = seasons.iter().map(|s| s.name.as_str()).collect();
+        self.fill_list(&self.season_list, &names, position);
+
+        if let Some(position) = position {
+            season::set_current(seasons[position].id);
+        }
+        self.seasons.replace(seasons);
+    }
+
+    /// Fill the competition list and show the competition asked for, otherwise the competition
+    /// already shown or failing that the first competition.
+    async fn load_competitions(&self, select: Option<i32>) {
+        let competitions = match connected_repository() {
+            Some(repository) => repository.get_all_competitions().await.unwrap_or_default(),
+            None => vec![],
+        };
+        let wanted = select.or(competition::current());
+        let position = wanted
+            .and_then(|id| competitions.iter().position(|c| c.id == id))
+            .or((!competitions.is_empty()).then_some(0));
+
+        let names: Vec<&str> = competitions.iter().map(|c| c.name.as_str()).collect();
+        self.fill_list(&self.competition_list, &names, position);
+
+        if let Some(position) = position {
+            competition::set_current(competitions[position].id);
+        }
+        self.competitions.replace(competitions);
+    }
 
+    /// Choose when tips close in the competition being shown and what happens to late tips.
+    pub fn edit_competition_rules(&self) {
+        let Some(competition) = competition::current()
+            .and_then(|id| self.competitions.borrow().iter().find(|c| c.id == id).cloned())
+        else {
+            show_error_dialog(&self.obj().root(), "Choose a competition first");
+            return;
+        };
 
+        // In the order of Lockout::ALL and LateTips::ALL
+        let lockout = gtk::DropDown::from_strings(&[
+            gettext("Tips never close").as_str(),
+            gettext("Tips on each game close at its kickoff").as_str(),
+            gettext("Tips on the round close at its first kickoff").as_str(),
+        ]);
+        lockout.set_selected(Lockout::ALL.iter().position(|l| *l == competition.lockout).unwrap_or(0) as u32);
+        let late_tips = gtk::DropDown::from_strings(&[
+            gettext("Refuse late tips").as_str(),
+            gettext("Accept late tips and flag them").as_str(),
+        ]);
+        late_tips.set_selected(LateTips::ALL.iter().position(|l| *l == competition.late_tips).unwrap_or(0) as u32);
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        content.append(&lockout);
+        content.append(&late_tips);
+
+        let heading = gettext("Competition Rules");
+        let dialog = adw::AlertDialog::new(Some(heading.as_str()), Some(competition.name.as_str()));
+        dialog.set_extra_child(Some(&content));
+        dialog.add_responses(&[("cancel", gettext("_Cancel").as_str()), ("save", gettext("_Save").as_str())]);
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("save"));
+        dialog.set_close_response("cancel");
+
+        glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+            if dialog.choose_future(&*window.obj()).await != "save" {
+                return;
+            }
+            let lockout = Lockout::ALL.get(lockout.selected() as usize).copied().unwrap_or(Lockout::Game);
+            let late_tips = LateTips::ALL.get(late_tips.selected() as usize).copied().unwrap_or(LateTips::Reject);
+            match repository().update_competition_rules(competition.id, lockout, late_tips).await {
+                Ok(_) => window.load_competitions(Some(competition.id)).await,
+                Err(e) => {
+                    let message = format!("The competition was not updated. {}", e);
+                    show_error_dialog(&window.obj().root(), &message);
+                }
+            }
+        }));
+    }
+
+    /// Ask for the name of a new competition and show it once it has been added.
+    pub fn add_competition(&self) {
+        let name = gtk::Entry::new();
+        name.set_activates_default(true);
+
+        let heading = gettext("New Competition");
+        let body = gettext("The competition shares the rounds and results, but has its own tippers and leaderboard.");
+        let dialog = adw::AlertDialog::new(Some(heading.as_str()), Some(body.as_str()));
+        dialog.set_extra_child(Some(&name));
+        dialog.add_responses(&[("cancel", gettext("_Cancel").as_str()), ("add", gettext("_Add").as_str())]);
+        dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("add"));
+        dialog.set_close_response("cancel");
+
+        glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+            if dialog.choose_future(&*window.obj()).await != "add" {
+                return;
+            }
+            let competition_name = name.text().trim().to_string();
+            if competition_name.is_empty() {
+                show_error_dialog(&window.obj().root(), "The competition needs a name");
+                return;
+            }
+            match repository().insert_competition(competition_name.clone()).await {
+                Ok(competition) => window.load_competitions(Some(competition.id)).await,
+                Err(ModelError::Duplicate(_)) => {
+                    let message = format!("There is already a competition called {}", competition_name);
+                    show_error_dialog(&window.obj().root(), &message);
+                }
+                Err(e) => {
+                    let message = format!("The competition was not added. {}", e);
+                    show_error_dialog(&window.obj().root(), &message);
+                }
+            }
+        }));
+    }
+
+    /// Ask for the name of a new season and whether the teams and tippers carry on into it.
+    pub fn start_new_season(&self) {
+        let name = gtk::Entry::new();
+        name.set_activates_default(true);
+        // Suggest the year after the latest season when the seasons are named by year
+        if let Some(year) = self.seasons.borrow().last().and_then(|s| s.name.trim().parse::<i32>().ok()) {
+            name.set_text(&(year + 1).to_string());
+        }
+        let keep_teams = gtk::CheckButton::with_mnemonic(&gettext("Keep the _teams"));
+        keep_teams.set_active(true);
+        let keep_tippers = gtk::CheckButton::with_mnemonic(&gettext("Keep the ti_ppers"));
+        keep_tippers.set_active(true);
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        content.append(&name);
+        content.append(&keep_teams);
+        content.append(&keep_tippers);
+
+        let heading = gettext("New Season");
+        let body = gettext("Rounds, games and tips start afresh. Teams and tippers that are not kept are archived.");
+        let dialog = adw::AlertDialog::new(Some(heading.as_str()), Some(body.as_str()));
+        dialog.set_extra_child(Some(&content));
+        dialog.add_responses(&[("cancel", gettext("_Cancel").as_str()), ("start", gettext("_Start").as_str())]);
+        dialog.set_response_appearance("start", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("start"));
+        dialog.set_close_response("cancel");
+
+        glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+            if dialog.choose_future(&*window.obj()).await != "start" {
+                return;
+            }
+            let season_name = name.text().trim().to_string();
+            if season_name.is_empty() {
+                show_error_dialog(&window.obj().root(), "The season needs a name");
+                return;
+            }
+            let result = repository()
+                .start_season(season_name.clone(), keep_teams.is_active(), keep_tippers.is_active())
+                .await;
+            match result {
+                Ok(season) => {
+                    window.load_seasons(Some(season.id)).await;
+                    let events = event::manager();
+                    events.notify_listeners(Event::TeamsChanged);
+                    events.notify_listeners(Event::TippersChanged);
+                }
+                Err(ModelError::Duplicate(_)) => {
+                    let message = format!("There is already a season called {}", season_name);
+                    show_error_dialog(&window.obj().root(), &message);
+                }
+                Err(e) => {
+                    let message = format!("The season was not started. {}", e);
+                    show_error_dialog(&window.obj().root(), &message);
+                }
+            }
+        }));
+    }
+
+    fn show_connection_status(&self) {
+        match db::manager().error() {
+            Some(message) => {
+                let message = format!("Not connected to the database. {}", message);
+                self.banner.set_title(&glib::markup_escape_text(&message));
+                self.banner.set_button_label(Some("_Retry"));
+                self.banner.set_revealed(true);
+            }
+            None => self.banner.set_revealed(false),
+        }
+    }
+
+    /// Keep the spinner turning while any view is waiting on the database.
+    fn loading_changed(&self, change: i32) {
+        let pending = self.loads_pending.get().saturating_add_signed(change);
+        self.loads_pending.set(pending);
+        self.loading.set_visible(pending > 0);
+    }
+
+    fn connection_changed(&self) {
+        self.show_connection_status();
+        if db::manager().is_connected() {
+            // Reload everything from the database we are now connected to
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                window.load_seasons(None).await;
+                window.load_competitions(None).await;
+            }));
+            let events = event::manager();
+            events.notify_listeners(Event::TeamsChanged);
+            events.notify_listeners(Event::TippersChanged);
+            events.notify_listeners(Event::RoundsChanged { round_id: -1 });
+            events.notify_listeners(Event::GamesChanged);
+        }
+    }
 }
 
 // The central trait for subclassing a GObject
@@ -76,7 +385,7 @@ impl ObjectImpl for Window {
         let obj = self.obj();
         obj.setup_actions();
         obj.load_window_size();
-
+        self.initialise();
 
     }
 
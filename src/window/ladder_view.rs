@@ -0,0 +1,206 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use gtk::{self, glib, prelude::*, subclass::prelude::*, CompositeTemplate};
+
+mod imp {
+    use super::*;
+    use crate::event;
+    use crate::event::{while_loading, Event};
+    use crate::model::error::ModelError;
+    use crate::model::repository::connected_repository;
+    use crate::model::season;
+    use crate::model::team::Teams;
+    use crate::util::ladder::{ladder, LadderEntry, LadderPoints};
+    use crate::window::util::build_column_factory;
+    use adw::glib::clone;
+    use glib::subclass::InitializingObject;
+    use glib::BoxedAnyObject;
+    use gtk::{gio, ColumnView, ColumnViewColumn, Label, NoSelection};
+    use log::error;
+    use std::collections::HashMap;
+
+    /// One line of the ladder.
+    pub struct LadderRow {
+        name: String,
+        entry: LadderEntry,
+    }
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/ladder_view.ui")]
+    pub struct LadderView {
+        #[template_child]
+        pub ladder_list: TemplateChild<ColumnView>,
+        #[template_child]
+        pub col_position: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_team: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_played: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_won: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_lost: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_drawn: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_byes: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_points_for: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_points_against: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_percentage: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_points: TemplateChild<ColumnViewColumn>,
+    }
+
+    impl LadderView {
+        pub fn initialise(&self) {
+            if let Some(rx) = event::manager().register_listener() {
+                glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                    while let Ok(ev) = rx.recv().await {
+                        match ev {
+                            Event::GamesChanged | Event::TeamsChanged | Event::PreferencesChanged |
+                            Event::RoundsChanged { .. } | Event::SeasonChanged => view.refresh(),
+                            _ => {}
+                        }
+                    }
+                }));
+            }
+            self.refresh();
+        }
+
+        fn refresh(&self) {
+            glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                view.load().await;
+            }));
+        }
+
+        async fn load(&self) {
+            let results = match (connected_repository(), season::current()) {
+                (Some(repository), Some(season_id)) => while_loading(async move {
+                    let games = repository.get_games_for_season(season_id).await?;
+                    let byes = repository.get_byes_for_season(season_id).await?;
+                    Ok::<_, ModelError>((games, byes))
+                }).await,
+                _ => Ok((vec![], vec![])),
+            };
+            let (games, byes) = match results {
+                Ok(results) => results,
+                Err(err) => {
+                    error!("Error loading results for the ladder: {}", err);
+                    (vec![], vec![])
+                }
+            };
+
+            let teams = Teams::loaded_with_archived().await.imp().teams.read().expect("Unable to get a lock on teams").clone();
+            let team_ids: Vec<i32> = teams.iter().map(|t| t.id()).collect();
+            let names: HashMap<i32, String> = teams.iter().map(|t| (t.id(), t.name())).collect();
+
+            let store = gio::ListStore::new::<BoxedAnyObject>();
+            for entry in ladder(&team_ids, &games, &byes, &LadderPoints::from_preferences()) {
+                let name = names.get(&entry.team_id).cloned().unwrap_or_default();
+                store.append(&BoxedAnyObject::new(LadderRow { name, entry }));
+            }
+            let selection_model = NoSelection::new(Some(store));
+            self.ladder_list.set_model(Some(&selection_model));
+            self.ladder_list.queue_draw();
+        }
+
+        fn set_factory<F>(column: &ColumnViewColumn, value: F)
+        where
+            F: Fn(&LadderEntry) -> String + 'static,
+        {
+            column.set_factory(Some(&build_column_factory(move |label: Label, row: &BoxedAnyObject| {
+                label.set_label(value(&row.borrow::<LadderRow>().entry).as_str());
+            })));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LadderView {
+        const NAME: &'static str = "LadderView";
+        type Type = super::LadderView;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for LadderView {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.col_team.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<LadderRow>().name.as_str());
+                label.set_xalign(0.0);
+            })));
+
+            Self::set_factory(&self.col_position, |e| e.position.to_string());
+            Self::set_factory(&self.col_played, |e| e.played.to_string());
+            Self::set_factory(&self.col_won, |e| e.won.to_string());
+            Self::set_factory(&self.col_lost, |e| e.lost.to_string());
+            Self::set_factory(&self.col_drawn, |e| e.drawn.to_string());
+            Self::set_factory(&self.col_byes, |e| e.byes.to_string());
+            Self::set_factory(&self.col_points_for, |e| e.points_for.to_string());
+            Self::set_factory(&self.col_points_against, |e| e.points_against.to_string());
+            Self::set_factory(&self.col_percentage, |e| match e.percentage() {
+                p if p.is_infinite() => "∞".to_string(),
+                p => format!("{:.1}", p),
+            });
+            Self::set_factory(&self.col_points, |e| e.premiership_points.to_string());
+
+            self.initialise();
+        }
+    }
+
+    impl BoxImpl for LadderView {}
+
+    impl WidgetImpl for LadderView {}
+}
+
+glib::wrapper! {
+    pub struct LadderView(ObjectSubclass<imp::LadderView>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl LadderView {
+    pub fn new() -> Self {
+        glib::Object::new::<LadderView>()
+    }
+}
+
+impl Default for LadderView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
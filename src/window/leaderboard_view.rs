@@ -0,0 +1,244 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use gtk::{self, glib, prelude::*, subclass::prelude::*, CompositeTemplate};
+
+mod imp {
+    use super::*;
+    use crate::event;
+    use crate::event::{while_loading, Event};
+    use crate::model::error::ModelError;
+    use crate::model::game::Game;
+    use crate::model::repository::connected_repository;
+    use crate::model::round::Rounds;
+    use crate::model::team::Teams;
+    use crate::model::tipper::Tippers;
+    use crate::model::{competition, season};
+    use crate::util::ladder::{positions_before, LadderPoints};
+    use crate::util::scoring::{default_tips, format_points, standings, ScoringRules, Standing, TieBreaker};
+    use crate::window::util::build_column_factory;
+    use adw::glib::clone;
+    use glib::subclass::InitializingObject;
+    use glib::BoxedAnyObject;
+    use gtk::{gio, ColumnView, ColumnViewColumn, Label, NoSelection};
+    use log::error;
+    use std::cell::RefCell;
+    use std::collections::{HashMap, HashSet};
+
+    /// One line of the leaderboard.
+    pub struct LeaderboardRow {
+        name: String,
+        standing: Standing,
+    }
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/leaderboard_view.ui")]
+    pub struct LeaderboardView {
+        #[template_child]
+        pub leaderboard_list: TemplateChild<ColumnView>,
+        #[template_child]
+        pub col_rank: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_name: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_total: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_margin_error: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_exact_margins: TemplateChild<ColumnViewColumn>,
+
+        round_columns: RefCell<Vec<ColumnViewColumn>>,
+    }
+
+    impl LeaderboardView {
+        pub fn initialise(&self) {
+            if let Some(rx) = event::manager().register_listener() {
+                glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                    while let Ok(ev) = rx.recv().await {
+                        match ev {
+                            Event::GamesChanged | Event::TippersChanged | Event::PreferencesChanged |
+                            Event::RoundsChanged { .. } | Event::TipsChanged { .. } | Event::SeasonChanged |
+                            Event::CompetitionChanged => view.refresh(),
+                            _ => {}
+                        }
+                    }
+                }));
+            }
+            self.refresh();
+        }
+
+        fn refresh(&self) {
+            glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                view.load().await;
+            }));
+        }
+
+        async fn load(&self) {
+            let results = match (connected_repository(), season::current()) {
+                (Some(repository), Some(season_id)) => while_loading(async move {
+                    let games = repository.get_games_for_season(season_id).await?;
+                    let tips = repository.get_tips_for_season(season_id).await?;
+                    let byes = repository.get_byes_for_season(season_id).await?;
+                    Ok::<_, ModelError>((games, tips, byes))
+                }).await,
+                _ => Ok((vec![], vec![], vec![])),
+            };
+            let (games, mut tips, byes) = match results {
+                Ok(results) => results,
+                Err(err) => {
+                    error!("Error loading results for the leaderboard: {}", err);
+                    (vec![], vec![], vec![])
+                }
+            };
+
+            let tippers = Tippers::loaded_with_archived(competition::current()).await.imp().tippers.read().expect("Unable to get a lock on tippers").clone();
+            let rounds = Rounds::loaded().await;
+            let tipper_ids: Vec<i32> = tippers.iter().map(|t| t.id()).collect();
+            let names: HashMap<i32, String> = tippers.iter().map(|t| (t.id(), t.name())).collect();
+            let rules = ScoringRules::from_preferences();
+            let scored_rounds: HashSet<i32> = games.iter()
+                .filter(|g| g.has_result())
+                .map(|g| g.round_id())
+                .collect();
+
+            // Fill in tips for anyone who missed a round that has been played
+            let rounds = rounds.imp().rounds.read().expect("Failed to read rounds").clone();
+            let teams = Teams::loaded_with_archived().await.imp().teams.read().expect("Unable to get a lock on teams").clone();
+            let team_ids: Vec<i32> = teams.iter().map(|t| t.id()).collect();
+            let points = LadderPoints::from_preferences();
+            let active_ids: Vec<i32> = tippers.iter().filter(|t| t.is_active()).map(|t| t.id()).collect();
+            let mut defaults = Vec::new();
+            for round in rounds.iter().filter(|r| scored_rounds.contains(&r.id())) {
+                let earlier_rounds: HashSet<i32> = rounds.iter()
+                    .filter(|r| r.number() < round.number())
+                    .map(|r| r.id())
+                    .collect();
+                let positions = positions_before(&team_ids, &games, &byes, &earlier_rounds, &points);
+                let round_games: Vec<Game> = games.iter().filter(|g| g.round_id() == round.id()).cloned().collect();
+                defaults.extend(default_tips(rules.default_tips, &active_ids, &round_games, &tips, &positions));
+            }
+            tips.extend(defaults);
+
+            let standings = standings(&tipper_ids, &active_ids, &games, &tips, &rules);
+            let show_tie_breaker = rules.tie_breaker != TieBreaker::None;
+            self.col_margin_error.set_visible(show_tie_breaker);
+            self.col_exact_margins.set_visible(show_tie_breaker);
+
+            // Add a column for each round that has results
+            for column in self.round_columns.borrow_mut().drain(..) {
+                self.leaderboard_list.remove_column(&column);
+            }
+            for round in rounds.iter() {
+                if !scored_rounds.contains(&round.id()) {
+                    continue;
+                }
+                let round_id = round.id();
+                let factory = build_column_factory(move |label: Label, row: &BoxedAnyObject| {
+                    let row = row.borrow::<LeaderboardRow>();
+                    let points = row.standing.round_points.get(&round_id).copied().unwrap_or(0.0);
+                    label.set_label(format_points(points).as_str());
+                });
+                let column = ColumnViewColumn::new(Some(format!("R{}", round.number()).as_str()), Some(factory));
+                self.leaderboard_list.append_column(&column);
+                self.round_columns.borrow_mut().push(column);
+            }
+
+            let store = gio::ListStore::new::<BoxedAnyObject>();
+            for standing in standings {
+                let name = names.get(&standing.tipper_id).cloned().unwrap_or_default();
+                store.append(&BoxedAnyObject::new(LeaderboardRow { name, standing }));
+            }
+            let selection_model = NoSelection::new(Some(store));
+            self.leaderboard_list.set_model(Some(&selection_model));
+            self.leaderboard_list.queue_draw();
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LeaderboardView {
+        const NAME: &'static str = "LeaderboardView";
+        type Type = super::LeaderboardView;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for LeaderboardView {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.col_rank.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<LeaderboardRow>().standing.rank.to_string().as_str());
+            })));
+
+            self.col_name.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<LeaderboardRow>().name.as_str());
+                label.set_xalign(0.0);
+            })));
+
+            self.col_total.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(format_points(row.borrow::<LeaderboardRow>().standing.total).as_str());
+            })));
+
+            self.col_margin_error.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<LeaderboardRow>().standing.margin_error.to_string().as_str());
+            })));
+
+            self.col_exact_margins.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<LeaderboardRow>().standing.exact_margins.to_string().as_str());
+            })));
+
+            self.initialise();
+        }
+    }
+
+    impl BoxImpl for LeaderboardView {}
+
+    impl WidgetImpl for LeaderboardView {}
+}
+
+glib::wrapper! {
+    pub struct LeaderboardView(ObjectSubclass<imp::LeaderboardView>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl LeaderboardView {
+    pub fn new() -> Self {
+        glib::Object::new::<LeaderboardView>()
+    }
+}
+
+impl Default for LeaderboardView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
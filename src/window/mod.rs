@@ -29,11 +29,20 @@ use gtk::{gio, glib};
 pub(crate) mod imp;
 pub(crate) mod util;
 pub(crate) mod team_view;
+pub(crate) mod ladder_view;
 pub(crate) mod edit_team;
 pub(crate) mod tipper_view;
 pub(crate) mod edit_tipper;
 pub (crate) mod round_view;
 pub(crate) mod edit_round;
+pub(crate) mod fixture_dialog;
+pub(crate) mod tip_view;
+pub(crate) mod tip_import_dialog;
+pub(crate) mod result_import_dialog;
+pub(crate) mod fixture_import_dialog;
+pub(crate) mod leaderboard_view;
+pub(crate) mod preference_dialog;
+pub(crate) mod connection_dialog;
 
 glib::wrapper! {
     pub struct Window(ObjectSubclass<imp::Window>)
@@ -0,0 +1,176 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use gtk::{self, glib, subclass::prelude::*, CompositeTemplate};
+
+mod imp {
+    use super::*;
+    use crate::event::while_loading;
+    use crate::preference;
+    use crate::util::{db, migration};
+    use crate::util::ladder::LadderPoints;
+    use crate::util::scoring::{DefaultTips, DrawRule, TieBreaker};
+    use adw::prelude::{ComboRowExt, PreferencesGroupExt};
+    use adw::subclass::prelude::{AdwDialogImpl, PreferencesDialogImpl};
+    use glib::clone;
+    use glib::subclass::InitializingObject;
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/preference_dialog.ui")]
+    pub struct PreferenceDialog {
+        #[template_child]
+        pub draw_rule: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub tie_breaker: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub default_tips: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub ladder_win: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub ladder_draw: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub ladder_loss: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub ladder_bye: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub migrations: TemplateChild<adw::PreferencesGroup>,
+    }
+
+    impl PreferenceDialog {
+        fn bind_points(row: &adw::SpinRow, key: &'static str, value: i32) {
+            row.set_value(value as f64);
+            row.connect_value_notify(move |row| {
+                preference::manager().put(key, row.value() as i32);
+            });
+        }
+
+        /// Fill in the migrations once their status has been read, without blocking the dialog.
+        fn show_migrations(&self) {
+            let Some(pool) = db::connected_pool() else {
+                let message = db::manager().error().unwrap_or_default();
+                self.migrations.set_description(Some(message.as_str()));
+                return;
+            };
+            glib::spawn_future_local(clone!(#[weak(rename_to = dialog)] self, async move {
+                match while_loading(migration::status(&pool)).await {
+                    Ok(status) => {
+                        for migration in status {
+                            let subtitle = match migration.applied_on {
+                                Some(applied_on) => format!("Applied {}", applied_on.format("%Y-%m-%d %H:%M")),
+                                None => "Pending".to_string(),
+                            };
+                            let row = adw::ActionRow::builder()
+                                .title(format!("{}. {}", migration.version, migration.description))
+                                .subtitle(subtitle)
+                                .build();
+                            dialog.migrations.add(&row);
+                        }
+                    }
+                    Err(err) => dialog.migrations.set_description(Some(err.to_string().as_str())),
+                }
+            }));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PreferenceDialog {
+        const NAME: &'static str = "PreferenceDialog";
+        type Type = super::PreferenceDialog;
+        type ParentType = adw::PreferencesDialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PreferenceDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let rule = DrawRule::from_preferences();
+            let position = DrawRule::ALL.iter().position(|r| *r == rule).unwrap_or(0);
+            self.draw_rule.set_selected(position as u32);
+            self.draw_rule.connect_selected_notify(|row| {
+                if let Some(rule) = DrawRule::ALL.get(row.selected() as usize) {
+                    preference::manager().put(preference::DRAW_RULE, rule);
+                }
+            });
+
+            let tie_breaker = TieBreaker::from_preferences();
+            let position = TieBreaker::ALL.iter().position(|t| *t == tie_breaker).unwrap_or(0);
+            self.tie_breaker.set_selected(position as u32);
+            self.tie_breaker.connect_selected_notify(|row| {
+                if let Some(tie_breaker) = TieBreaker::ALL.get(row.selected() as usize) {
+                    preference::manager().put(preference::TIE_BREAKER, tie_breaker);
+                }
+            });
+
+            let default_tips = DefaultTips::from_preferences();
+            let position = DefaultTips::ALL.iter().position(|d| *d == default_tips).unwrap_or(0);
+            self.default_tips.set_selected(position as u32);
+            self.default_tips.connect_selected_notify(|row| {
+                if let Some(default_tips) = DefaultTips::ALL.get(row.selected() as usize) {
+                    preference::manager().put(preference::DEFAULT_TIPS, default_tips);
+                }
+            });
+
+            let points = LadderPoints::from_preferences();
+            Self::bind_points(&self.ladder_win, preference::LADDER_WIN_POINTS, points.win);
+            Self::bind_points(&self.ladder_draw, preference::LADDER_DRAW_POINTS, points.draw);
+            Self::bind_points(&self.ladder_loss, preference::LADDER_LOSS_POINTS, points.loss);
+            Self::bind_points(&self.ladder_bye, preference::LADDER_BYE_POINTS, points.bye);
+
+            self.show_migrations();
+        }
+    }
+
+    impl WidgetImpl for PreferenceDialog {}
+
+    impl AdwDialogImpl for PreferenceDialog {}
+
+    impl PreferencesDialogImpl for PreferenceDialog {}
+}
+
+glib::wrapper! {
+    pub struct PreferenceDialog(ObjectSubclass<imp::PreferenceDialog>)
+        @extends adw::PreferencesDialog, adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl PreferenceDialog {
+    pub fn new() -> Self {
+        glib::Object::new::<PreferenceDialog>()
+    }
+}
+
+impl Default for PreferenceDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,293 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use gtk::{gio, glib};
+
+mod imp {
+    use crate::event;
+    use crate::event::{while_loading, Event};
+    use crate::model::error::ModelError;
+    use crate::model::game::Game;
+    use crate::model::repository::{connected_repository, repository};
+    use crate::model::round::Rounds;
+    use crate::model::team::Teams;
+    use crate::util::import::{parse_results, round_numbers, ImportedResult, Names};
+    use crate::window::util::{build_column_factory, connect_escape, open_import_file, show_error_dialog};
+    use adw::prelude::{ButtonExt, Cast, CheckButtonExt, GtkWindowExt, TextBufferExt, TextViewExt, WidgetExt};
+    use adw::subclass::prelude::{CompositeTemplate, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt, WidgetClassExt, WindowImpl};
+    use gtk::glib::{clone, BoxedAnyObject};
+    use gtk::glib::subclass::InitializingObject;
+    use gtk::subclass::widget::{CompositeTemplateInitializingExt, WidgetImpl};
+    use gtk::{gio, glib, Button, CheckButton, ColumnView, ColumnViewColumn, CompositeTemplate, Label, NoSelection, TemplateChild, TextView};
+    use log::error;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::ops::Deref;
+
+    /// One result in the import preview.
+    pub struct PreviewRow {
+        line: usize,
+        game: String,
+        /// What importing the line will do, or why it cannot be imported.
+        status: String,
+        resolved: bool,
+    }
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/result_import_dialog.ui")]
+    pub struct ResultImportDialog {
+        #[template_child]
+        pub import_view: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub btn_open: TemplateChild<Button>,
+        #[template_child]
+        pub import_text: TemplateChild<TextView>,
+        #[template_child]
+        pub btn_preview: TemplateChild<Button>,
+        #[template_child]
+        pub replace: TemplateChild<CheckButton>,
+        #[template_child]
+        pub preview_list: TemplateChild<ColumnView>,
+        #[template_child]
+        pub col_line: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_game: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_status: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub summary: TemplateChild<Label>,
+        #[template_child]
+        pub btn_ok: TemplateChild<Button>,
+        #[template_child]
+        pub btn_cancel: TemplateChild<Button>,
+
+        results: RefCell<Vec<(i32, i32, i32)>>,
+    }
+
+    impl ResultImportDialog {
+        /// Fetch the games of every round in the file, then match each line to one of them.
+        async fn preview(&self) {
+            self.invalidate();
+            let buffer = self.import_text.buffer();
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string();
+            let teams: Vec<Names> = Teams::loaded_with_archived().await.imp().teams.read().expect("Unable to get a lock on teams")
+                .iter()
+                .map(|t| (t.id(), vec![t.name(), t.nickname()]))
+                .collect();
+            let numbers = round_numbers(&text);
+            let rounds: Vec<(i32, i32)> = Rounds::loaded().await.imp().rounds.read().expect("Failed to read rounds")
+                .iter()
+                .filter(|r| numbers.contains(&r.number()))
+                .map(|r| (r.number(), r.id()))
+                .collect();
+            let Some(repository) = connected_repository() else {
+                return;
+            };
+            let loaded = while_loading(async move {
+                let mut games = HashMap::new();
+                for (number, round_id) in rounds {
+                    games.insert(number, repository.get_games_for_round(round_id).await?);
+                }
+                Ok::<_, ModelError>(games)
+            }).await;
+            let games: HashMap<i32, Vec<Game>> = match loaded {
+                Ok(games) => games,
+                Err(err) => {
+                    error!("Error loading games for the results: {}", err);
+                    show_error_dialog(&self.obj().root(), err.to_string().as_str());
+                    return;
+                }
+            };
+
+            let imported = parse_results(&text, &teams, &games, self.replace.is_active());
+            let nickname = |team_id: i32| teams.iter()
+                .find(|(id, _)| *id == team_id)
+                .map(|(_, names)| names[1].clone())
+                .unwrap_or_default();
+            let by_id: HashMap<i32, (i32, &Game)> = games.iter()
+                .flat_map(|(number, round_games)| round_games.iter().map(move |g| (g.id(), (*number, g))))
+                .collect();
+
+            let store = gio::ListStore::new::<BoxedAnyObject>();
+            for result in &imported {
+                let game = match result.game_id.and_then(|id| by_id.get(&id)) {
+                    Some((number, game)) => format!("Round {}: {} v {}", number,
+                        nickname(game.home_team_id()), nickname(game.away_team_id())),
+                    None => result.text.clone(),
+                };
+                let replaced = result.game_id
+                    .and_then(|id| by_id.get(&id))
+                    .and_then(|(_, g)| g.home_team_score().zip(g.away_team_score()));
+                store.append(&BoxedAnyObject::new(PreviewRow {
+                    line: result.line,
+                    game,
+                    status: Self::status(result, replaced),
+                    resolved: result.problem.is_none(),
+                }));
+            }
+            self.preview_list.set_model(Some(&NoSelection::new(Some(store))));
+
+            let results: Vec<(i32, i32, i32)> = imported.iter().filter_map(|r| r.to_result()).collect();
+            let problems = imported.iter().filter(|r| r.problem.is_some()).count();
+            let summary = match problems {
+                0 => format!("{} results to import", results.len()),
+                _ => format!("{} results to import, {} lines unmatched or conflicting", results.len(), problems),
+            };
+            self.summary.set_label(summary.as_str());
+            self.btn_ok.set_sensitive(!results.is_empty());
+            self.results.replace(results);
+        }
+
+        /// What importing a line will do, such as "80-60 replacing 70-60".
+        fn status(result: &ImportedResult, replaced: Option<(i32, i32)>) -> String {
+            if let Some(problem) = &result.problem {
+                return problem.clone();
+            }
+            if result.unchanged {
+                return "Already entered".to_string();
+            }
+            let Some((home, away)) = result.scores else {
+                return String::new();
+            };
+            match replaced {
+                Some((old_home, old_away)) => format!("{}-{} replacing {}-{}", home, away, old_home, old_away),
+                None => format!("{}-{}", home, away),
+            }
+        }
+
+        /// Forget the previewed results once the text or options change.
+        fn invalidate(&self) {
+            self.results.borrow_mut().clear();
+            self.preview_list.set_model(None::<&NoSelection>);
+            self.summary.set_label("");
+            self.btn_ok.set_sensitive(false);
+        }
+
+        fn save_results(&self) {
+            let results = self.results.borrow().clone();
+            let repository = repository();
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                match repository.save_results(&results).await {
+                    Ok(_) => {
+                        event::manager().notify_listeners(Event::GamesChanged);
+                        window.obj().close();
+                    }
+                    Err(err) => show_error_dialog(&window.obj().root(), err.to_string().as_str()),
+                }
+            }));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResultImportDialog {
+        const NAME: &'static str = "ResultImportDialog";
+        type Type = super::ResultImportDialog;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResultImportDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.col_line.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().line.to_string().as_str());
+            })));
+            self.col_game.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().game.as_str());
+                label.set_xalign(0.0);
+            })));
+            // Unmatched and conflicting lines are shown as errors
+            self.col_status.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                let row = row.borrow::<PreviewRow>();
+                label.set_label(row.status.as_str());
+                label.set_xalign(0.0);
+                if row.resolved {
+                    label.remove_css_class("error");
+                } else {
+                    label.add_css_class("error");
+                }
+            })));
+
+            self.import_text.buffer().connect_changed(clone!(#[weak(rename_to = window)] self, move |_| {
+                window.invalidate();
+            }));
+            self.replace.connect_toggled(clone!(#[weak(rename_to = window)] self, move |_| {
+                window.invalidate();
+            }));
+
+            self.btn_open.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                open_import_file(window.obj().upcast_ref(), "Import Results", &window.import_text.buffer());
+            }));
+
+            self.btn_preview.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                glib::spawn_future_local(clone!(#[weak] window, async move {
+                    window.preview().await;
+                }));
+            }));
+
+            self.btn_cancel.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.obj().close();
+            }));
+
+            self.btn_ok.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.save_results();
+            }));
+
+            connect_escape(self.import_view.deref(), self.btn_cancel.deref());
+        }
+    }
+
+    impl WidgetImpl for ResultImportDialog {}
+
+    impl WindowImpl for ResultImportDialog {}
+}
+
+glib::wrapper! {
+    pub struct ResultImportDialog(ObjectSubclass<imp::ResultImportDialog>)
+        @extends gtk::Window, gtk::Widget,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl ResultImportDialog {
+    pub fn new() -> Self {
+        glib::Object::new::<ResultImportDialog>()
+    }
+}
+
+impl Default for ResultImportDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
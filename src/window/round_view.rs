@@ -32,12 +32,12 @@ mod imp {
     use crate::event;
     use crate::event::Event;
     use crate::model::round::{Round, Rounds};
-    use crate::util::db;
+    use crate::model::repository::repository;
     use crate::window::edit_round::RoundDialog;
-    use crate::window::util::build_column_factory;
+    use crate::window::util::{confirm_delete, show_error_dialog};
     use adw::glib::clone;
     use glib::subclass::InitializingObject;
-    use gtk::{Button, Label, ListView, ScrolledWindow, SingleSelection};
+    use gtk::{Button, Label, ListItem, ListView, ScrolledWindow, SignalListItemFactory, SingleSelection};
 
     #[derive(Default, CompositeTemplate)]
     #[template(resource = "/com/shartrec/kelpie_tipping/round_view.ui")]
@@ -55,8 +55,10 @@ mod imp {
             if let Some(rx) = event::manager().register_listener() {
                 glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
                     while let Ok(ev) = rx.recv().await {
-                        if let Event::RoundsChanged{round_id} = ev {
-                            view.refresh(Some(round_id));
+                        match ev {
+                            Event::RoundsChanged{round_id} => view.refresh(Some(round_id)),
+                            Event::SeasonChanged => view.refresh(None),
+                            _ => {}
                         }
                     }
                 }));
@@ -71,22 +73,28 @@ mod imp {
             let round_dialog = RoundDialog::new(&self.round_draw_box, None);
         }
 
+        /// Reload the rounds without blocking the window, then show the given round or the first.
         fn refresh(&self, round_id: Option<i32>) {
-            let selection_model = SingleSelection::new(Some(Rounds::new()));
-            self.round_list.set_model(Some(&selection_model));
-            // find the position in the model with the given round_id or 0 if not found
-            let position = if let Some(id) = round_id {
-                let rounds = selection_model.model().unwrap().downcast::<Rounds>().unwrap();
-                let x = rounds.imp().rounds.read().expect("Failed to read rounds");
-                x.iter().position(|r| r.id() == id)
-                    .unwrap_or(0) as u32
-            } else {
-                0
+            let Some(selection_model) = self.round_list.model().and_downcast::<SingleSelection>() else {
+                return;
             };
-
-            selection_model.select_item(position, true);
-            self.activate_round(position);
-            self.round_list.queue_draw();
+            let Some(rounds) = selection_model.model().and_downcast::<Rounds>() else {
+                return;
+            };
+            glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                rounds.load().await;
+                // find the position in the model with the given round_id or 0 if not found
+                let position = if let Some(id) = round_id {
+                    let x = rounds.imp().rounds.read().expect("Failed to read rounds");
+                    x.iter().position(|r| r.id() == id)
+                        .unwrap_or(0) as u32
+                } else {
+                    0
+                };
+
+                selection_model.select_item(position, true);
+                view.activate_round(position);
+            }));
         }
 
         fn get_model_round(&self, sel_ap: u32) -> Option<Round> {
@@ -98,10 +106,10 @@ mod imp {
             }
         }
         fn activate_round(&self, position: u32) {
+            while let Some(old_round) = self.round_draw_box.first_child() {
+                self.round_draw_box.get().remove(&old_round);
+            }
             if let Some(round) = self.get_model_round(position) {
-                while let Some(old_round) = self.round_draw_box.first_child() {
-                    self.round_draw_box.get().remove(&old_round);
-                }
                 let _round_dialog = RoundDialog::new(&self.round_draw_box, Some(round.clone()));
             }
         }
@@ -126,6 +134,7 @@ mod imp {
     impl ObjectImpl for RoundView {
         fn constructed(&self) {
             self.parent_constructed();
+            self.round_list.set_model(Some(&SingleSelection::new(Some(Rounds::new()))));
             self.initialise();
 
             self.round_list.connect_activate(
@@ -134,47 +143,44 @@ mod imp {
                 }),
             );
 
-            self.round_list.set_factory(Some(&build_column_factory(|label: Label, round: &Round| {
-                label.set_label(format!("{}", round.number()).as_str());
+            // Each row shows the round number with a button to delete the round
+            let factory = SignalListItemFactory::new();
+            factory.connect_setup(|_, list_item| {
+                let label = Label::new(None);
                 label.set_xalign(0.0);
-            })));
-
-            // let f = {
-            //     let factory = SignalListItemFactory::new();
-            //     factory.connect_setup(move |_, list_item| {
-            //         let button = Button::new();
-            //         button.set_icon_name("edit-delete");
-            //
-            //         button.connect_clicked(delete_round);
-            //         list_item
-            //             .downcast_ref::<ListItem>()
-            //             .expect("Needs to be ListItem")
-            //             .set_child(Some(&button));
-            //     });
-            //
-            //     factory.connect_bind(move |r, list_item| {
-            //         // Get `StringObject` from `ListItem`
-            //         // let obj = list_item
-            //         //     .downcast_ref::<ListItem>()
-            //         //     .expect("Needs to be ListItem")
-            //         //     .item()
-            //         //     .and_downcast::<Round>()
-            //         //     .expect("The item has to be an <T>.");
-            //         //
-            //         // // Get `Label` from `ListItem`
-            //         // let button = list_item
-            //         //     .downcast_ref::<ListItem>()
-            //         //     .expect("Needs to be ListItem")
-            //         //     .child()
-            //         //     .and_downcast::<Button>()
-            //         //     .expect("The child has to be a `Label`.");
-            //         //
-            //         // button.set_action_target(Some(obj.id()));
-            //
-            //     });
-            //     factory
-            // };
-            // self.col_delete.set_factory(Some(&f));
+                label.set_hexpand(true);
+                let button = Button::from_icon_name("list-remove-symbolic");
+                button.set_has_frame(false);
+                button.set_tooltip_text(Some("Delete round"));
+                button.connect_clicked(delete_round);
+                let row = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+                row.append(&label);
+                row.append(&button);
+                list_item
+                    .downcast_ref::<ListItem>()
+                    .expect("Needs to be ListItem")
+                    .set_child(Some(&row));
+            });
+            factory.connect_bind(|_, list_item| {
+                let list_item = list_item
+                    .downcast_ref::<ListItem>()
+                    .expect("Needs to be ListItem");
+                let round = list_item
+                    .item()
+                    .and_downcast::<Round>()
+                    .expect("The item has to be a `Round`.");
+                let row = list_item
+                    .child()
+                    .and_downcast::<gtk::Box>()
+                    .expect("The child has to be a `Box`.");
+                if let Some(label) = row.first_child().and_downcast::<Label>() {
+                    label.set_label(format!("{}", round.number()).as_str());
+                }
+                if let Some(button) = row.last_child().and_downcast::<Button>() {
+                    button.set_action_target(Some(round.id()));
+                }
+            });
+            self.round_list.set_factory(Some(&factory));
         }
 
     }
@@ -186,12 +192,32 @@ mod imp {
     fn delete_round(button: &Button) {
         if let Some(value) = button.action_target_value() {
             if let Some(id) = value.get::<i32>() {
-                let pool = db::manager().pool();
-                glib::spawn_future_local(clone!(async move {
-                    //TODO
-                // let _ = round::delete(pool, id).await;
-                // event::manager().notify_listeners(Event::RoundsChanged);
-            }));
+                let repository = repository();
+                glib::spawn_future_local(clone!(#[weak] button, async move {
+                    let dependents = match repository.get_round_dependents(id).await {
+                        Ok(dependents) => dependents,
+                        Err(e) => {
+                            show_error_dialog(&button.root(), e.to_string().as_str());
+                            return;
+                        }
+                    };
+                    let body = if dependents.is_empty() {
+                        "This round has no games or byes.".to_string()
+                    } else {
+                        format!("This round has {}, which will be deleted with it.", dependents.describe())
+                    };
+                    if !confirm_delete(&button.root(), "Delete round?", &body).await {
+                        return;
+                    }
+                    match repository.delete_round_cascade(id).await {
+                        Ok(0) => show_error_dialog(&button.root(), "The round had already been deleted"),
+                        Ok(_) => {
+                            event::manager().notify_listeners(Event::RoundsChanged { round_id: id });
+                            event::manager().notify_listeners(Event::GamesChanged);
+                        }
+                        Err(e) => show_error_dialog(&button.root(), format!("The round was not deleted. {}", e).as_str()),
+                    }
+                }));
             }
         }
     }
@@ -29,14 +29,14 @@ mod imp {
     use super::*;
     use crate::event;
     use crate::event::Event;
-    use crate::model::team;
+    use crate::model::season;
     use crate::model::team::{Team, Teams};
-    use crate::util::db;
+    use crate::model::repository::repository;
     use crate::window::edit_team::TeamDialog;
-    use crate::window::util::{build_column_factory, build_del_column_factory};
+    use crate::window::util::{build_check_column_factory, build_column_factory, build_del_column_factory, confirm_archive_or_delete, confirm_delete, show_error_dialog, DeleteChoice};
     use adw::glib::clone;
     use glib::subclass::InitializingObject;
-    use gtk::{Button, ColumnView, ColumnViewColumn, Label, ListItem, SignalListItemFactory, SingleSelection};
+    use gtk::{Button, CheckButton, ColumnView, ColumnViewColumn, Label, ListItem, SignalListItemFactory, SingleSelection};
 
     #[derive(Default, CompositeTemplate)]
     #[template(resource = "/com/shartrec/kelpie_tipping/team_view.ui")]
@@ -48,7 +48,11 @@ mod imp {
         #[template_child]
         pub col_nickname: TemplateChild<ColumnViewColumn>,
         #[template_child]
+        pub col_active: TemplateChild<ColumnViewColumn>,
+        #[template_child]
         pub col_delete: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub show_archived: TemplateChild<CheckButton>,
     }
 
     impl TeamView {
@@ -56,7 +60,7 @@ mod imp {
             if let Some(rx) = event::manager().register_listener() {
                 glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
                     while let Ok(ev) = rx.recv().await {
-                        if let Event::TeamsChanged = ev {
+                        if let Event::TeamsChanged | Event::SeasonChanged = ev {
                             view.refresh();
                         }
                     }
@@ -65,10 +69,19 @@ mod imp {
             self.refresh();
         }
 
+        fn teams(&self) -> Option<Teams> {
+            self.team_list.model().and_downcast_ref::<SingleSelection>()
+                .and_then(|selection| selection.model())
+                .and_downcast::<Teams>()
+        }
+
+        /// Reload the teams into the list already on screen, without blocking the window.
         fn refresh(&self) {
-            let selection_model = SingleSelection::new(Some(Teams::new()));
-            self.team_list.set_model(Some(&selection_model));
-            self.team_list.queue_draw();
+            if let Some(teams) = self.teams() {
+                glib::spawn_future_local(async move {
+                    teams.load().await;
+                });
+            }
         }
 
         fn get_model_team(&self, sel_ap: u32) -> Option<Team> {
@@ -101,6 +114,7 @@ mod imp {
     impl ObjectImpl for TeamView {
         fn constructed(&self) {
             self.parent_constructed();
+            self.team_list.set_model(Some(&SingleSelection::new(Some(Teams::new()))));
             self.initialise();
 
             self.team_list.connect_activate(
@@ -131,6 +145,17 @@ mod imp {
                 label.set_xalign(0.0);
             })));
 
+            self.col_active.set_factory(Some(&build_check_column_factory(
+                |team: &Team| team.is_active(),
+                set_team_active)));
+
+            self.show_archived.connect_toggled(clone!(#[weak(rename_to = view)] self, move |check| {
+                if let Some(teams) = view.teams() {
+                    teams.set_include_archived(check.is_active());
+                    view.refresh();
+                }
+            }));
+
             // Create a factory for the delete button column
             let f = build_del_column_factory(
                 |button: &Button, team: &Team| button.set_action_target(Some(team.id())),
@@ -143,14 +168,69 @@ mod imp {
 
     impl WidgetImpl for TeamView {}
 
+    /// Archive or restore a team in the season being shown when the Active box is changed.
+    fn set_team_active(check: &CheckButton, team: &Team, active: bool) {
+        let Some(season_id) = season::current() else {
+            return;
+        };
+        let repository = repository();
+        glib::spawn_future_local(clone!(#[weak] check, #[strong] team, async move {
+            match repository.set_team_active(season_id, team.id(), active).await {
+                Ok(_) => {
+                    team.set_active(active);
+                    event::manager().notify_listeners(Event::TeamsChanged);
+                }
+                Err(e) => {
+                    check.set_active(!active);
+                    show_error_dialog(&check.root(), format!("The team was not updated. {}", e).as_str());
+                }
+            }
+        }));
+    }
+
     fn delete_team(button: &Button) {
+        let Some(season_id) = season::current() else {
+            return;
+        };
         if let Some(value) = button.action_target_value() {
             if let Some(id) = value.get::<i32>() {
-                let pool = db::manager().pool();
-                glib::spawn_future_local(clone!(async move {
-                    let _ = team::delete(pool, id).await;
-                    event::manager().notify_listeners(Event::TeamsChanged);
-            }));
+                let repository = repository();
+                glib::spawn_future_local(clone!(#[weak] button, async move {
+                    let dependents = match repository.get_team_dependents(id).await {
+                        Ok(dependents) => dependents,
+                        Err(e) => {
+                            show_error_dialog(&button.root(), e.to_string().as_str());
+                            return;
+                        }
+                    };
+                    // With a history to lose, archiving is offered ahead of deleting
+                    let choice = if dependents.is_empty() {
+                        if confirm_delete(&button.root(), "Delete team?", "Nothing else refers to this team.").await {
+                            DeleteChoice::Delete
+                        } else {
+                            DeleteChoice::Cancel
+                        }
+                    } else {
+                        let body = format!("This team has {}. Archive it to keep its history, or delete it along with its games, byes and tips.",
+                                           dependents.describe());
+                        confirm_archive_or_delete(&button.root(), "Delete team?", &body).await
+                    };
+                    let result = match choice {
+                        DeleteChoice::Cancel => return,
+                        DeleteChoice::Archive => repository.set_team_active(season_id, id, false).await,
+                        DeleteChoice::Delete => repository.delete_team_cascade(id).await,
+                    };
+                    match result {
+                        Ok(0) if choice == DeleteChoice::Delete => show_error_dialog(&button.root(), "The team had already been deleted"),
+                        Ok(_) => {
+                            event::manager().notify_listeners(Event::TeamsChanged);
+                            if choice == DeleteChoice::Delete && !dependents.is_empty() {
+                                event::manager().notify_listeners(Event::GamesChanged);
+                            }
+                        }
+                        Err(e) => show_error_dialog(&button.root(), format!("The team was not changed. {}", e).as_str()),
+                    }
+                }));
             }
         }
     }
@@ -0,0 +1,338 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use gtk::{gio, glib};
+
+mod imp {
+    use crate::event;
+    use crate::event::Event;
+    use crate::model::game::{Game, Games};
+    use crate::model::repository::repository;
+    use crate::model::round::Round;
+    use crate::model::team::Teams;
+    use crate::model::tip::Tip;
+    use crate::model::tipper::{Tipper, Tippers};
+    use crate::model::competition;
+    use crate::util::import::{parse_tips, ImportedTip, Names};
+    use crate::util::lockout::{check_tips, LateTips, Lockout};
+    use crate::window::util::{build_column_factory, connect_escape, open_import_file, show_error_dialog};
+    use adw::prelude::{ButtonExt, Cast, GtkWindowExt, TextBufferExt, TextViewExt, WidgetExt};
+    use adw::subclass::prelude::{CompositeTemplate, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt, WidgetClassExt, WindowImpl};
+    use chrono::Utc;
+    use gtk::glib::{clone, BoxedAnyObject};
+    use gtk::glib::subclass::InitializingObject;
+    use gtk::subclass::widget::{CompositeTemplateInitializingExt, WidgetImpl};
+    use gtk::{gio, glib, Button, ColumnView, ColumnViewColumn, CompositeTemplate, DropDown, Label, NoSelection, StringList, TemplateChild, TextView};
+    use std::cell::RefCell;
+    use std::ops::Deref;
+
+    /// One tip in the import preview.
+    pub struct PreviewRow {
+        line: usize,
+        text: String,
+        tipper: String,
+        /// The tip as it will be saved, or why it cannot be.
+        tip: String,
+        resolved: bool,
+    }
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/tip_import_dialog.ui")]
+    pub struct TipImportDialog {
+        #[template_child]
+        pub import_view: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub round_label: TemplateChild<Label>,
+        #[template_child]
+        pub tipper_select: TemplateChild<DropDown>,
+        #[template_child]
+        pub btn_open: TemplateChild<Button>,
+        #[template_child]
+        pub import_text: TemplateChild<TextView>,
+        #[template_child]
+        pub btn_preview: TemplateChild<Button>,
+        #[template_child]
+        pub preview_list: TemplateChild<ColumnView>,
+        #[template_child]
+        pub col_line: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_text: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_tipper: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_tip: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub summary: TemplateChild<Label>,
+        #[template_child]
+        pub btn_ok: TemplateChild<Button>,
+        #[template_child]
+        pub btn_cancel: TemplateChild<Button>,
+
+        round: RefCell<Option<Round>>,
+        games: RefCell<Vec<Game>>,
+        teams: RefCell<Vec<Names>>,
+        tippers: RefCell<Vec<Tipper>>,
+        tips: RefCell<Vec<Tip>>,
+    }
+
+    impl TipImportDialog {
+        pub fn set_round(&self, round: Round) {
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                window.load(round).await;
+            }));
+        }
+
+        /// Fetch the games, teams and tippers the tips are matched against.
+        async fn load(&self, round: Round) {
+            self.round_label.set_label(format!("Round {}", round.number()).as_str());
+            self.btn_preview.set_sensitive(false);
+            let games = Games::for_round(round.id()).await.imp().games.read().expect("Unable to get a lock on games").clone();
+            let teams: Vec<Names> = Teams::loaded_with_archived().await.imp().teams.read().expect("Unable to get a lock on teams")
+                .iter()
+                .map(|t| (t.id(), vec![t.name(), t.nickname()]))
+                .collect();
+            let tippers: Vec<Tipper> = Tippers::loaded_with_archived(competition::current()).await.imp().tippers.read().expect("Unable to get a lock on tippers")
+                .iter()
+                .filter(|t| t.is_active())
+                .cloned()
+                .collect();
+
+            let mut choices = vec!["Named in the text".to_string()];
+            choices.extend(tippers.iter().map(|t| t.name()));
+            let choices: Vec<&str> = choices.iter().map(|c| c.as_str()).collect();
+            self.tipper_select.set_model(Some(&StringList::new(&choices)));
+
+            self.round.replace(Some(round));
+            self.games.replace(games);
+            self.teams.replace(teams);
+            self.tippers.replace(tippers);
+            self.btn_preview.set_sensitive(true);
+        }
+
+        fn preview(&self) {
+            let buffer = self.import_text.buffer();
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            let tippers = self.tippers.borrow();
+            let default_tipper = match self.tipper_select.selected() {
+                0 => None,
+                position => tippers.get(position as usize - 1).map(|t| t.id()),
+            };
+            let tipper_names: Vec<Names> = tippers.iter().map(|t| (t.id(), vec![t.name()])).collect();
+            let imported = parse_tips(text.as_str(), default_tipper, &tipper_names, &self.teams.borrow(), &self.games.borrow());
+
+            let store = gio::ListStore::new::<BoxedAnyObject>();
+            for tip in &imported {
+                let tipper = tippers.iter()
+                    .find(|t| Some(t.id()) == tip.tipper_id)
+                    .map(|t| t.name())
+                    .unwrap_or_default();
+                store.append(&BoxedAnyObject::new(PreviewRow {
+                    line: tip.line,
+                    text: tip.text.clone(),
+                    tipper,
+                    tip: self.describe(tip),
+                    resolved: tip.problem.is_none(),
+                }));
+            }
+            self.preview_list.set_model(Some(&NoSelection::new(Some(store))));
+
+            let tips: Vec<Tip> = imported.iter().filter_map(|t| t.to_tip()).collect();
+            let unresolved = imported.len() - tips.len();
+            let summary = match unresolved {
+                0 => format!("{} tips to import", tips.len()),
+                _ => format!("{} tips to import, {} could not be matched", tips.len(), unresolved),
+            };
+            self.summary.set_label(summary.as_str());
+            self.btn_ok.set_sensitive(!tips.is_empty());
+            self.tips.replace(tips);
+        }
+
+        /// The tip as it will be saved, such as "Cats by 12", or why it cannot be.
+        fn describe(&self, tip: &ImportedTip) -> String {
+            if let Some(problem) = &tip.problem {
+                return problem.clone();
+            }
+            let teams = self.teams.borrow();
+            let nickname = |team_id: i32| teams.iter()
+                .find(|(id, _)| *id == team_id)
+                .map(|(_, names)| names[1].clone())
+                .unwrap_or_default();
+            let games = self.games.borrow();
+            let (Some(game), Some((home, away))) = (games.iter().find(|g| Some(g.id()) == tip.game_id), tip.scores) else {
+                return String::new();
+            };
+            match (home, away) {
+                (margin, 0) => format!("{} by {}", nickname(game.home_team_id()), margin),
+                (0, margin) => format!("{} by {}", nickname(game.away_team_id()), margin),
+                _ => format!("{} {} v {} {}", nickname(game.home_team_id()), home, nickname(game.away_team_id()), away),
+            }
+        }
+
+        /// Forget the previewed tips once the text or tipper changes.
+        fn invalidate(&self) {
+            self.tips.borrow_mut().clear();
+            self.preview_list.set_model(None::<&NoSelection>);
+            self.summary.set_label("");
+            self.btn_ok.set_sensitive(false);
+        }
+
+        fn save_tips(&self) {
+            let Some(round_id) = self.round.borrow().as_ref().map(|r| r.id()) else {
+                return;
+            };
+            let tips = self.tips.borrow().clone();
+            let games = self.games.borrow().clone();
+            glib::spawn_future_local(clone!(#[weak(rename_to = window)] self, async move {
+                let competition = match competition::current() {
+                    Some(id) => repository().get_all_competitions().await
+                        .unwrap_or_default()
+                        .into_iter()
+                        .find(|c| c.id == id),
+                    None => None,
+                };
+                let (lockout, late_tips) = competition
+                    .map(|c| (c.lockout, c.late_tips))
+                    .unwrap_or((Lockout::None, LateTips::Flag));
+                let tips = match check_tips(tips, &games, lockout, late_tips, Utc::now()) {
+                    Ok(tips) => tips,
+                    Err(game_ids) => {
+                        let teams = window.teams.borrow().clone();
+                        let nickname = |team_id: i32| teams.iter()
+                            .find(|(id, _)| *id == team_id)
+                            .map(|(_, names)| names[1].clone())
+                            .unwrap_or_default();
+                        let names: Vec<String> = games.iter()
+                            .filter(|g| game_ids.contains(&g.id()))
+                            .map(|g| format!("{} v {}", nickname(g.home_team_id()), nickname(g.away_team_id())))
+                            .collect();
+                        let message = format!("Tips have closed for {}. No tips were imported.", names.join(", "));
+                        show_error_dialog(&window.obj().root(), message.as_str());
+                        return;
+                    }
+                };
+                match repository().save_tips(&tips).await {
+                    Ok(_) => {
+                        event::manager().notify_listeners(Event::TipsChanged { round_id });
+                        window.obj().close();
+                    }
+                    Err(err) => show_error_dialog(&window.obj().root(), err.to_string().as_str()),
+                }
+            }));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TipImportDialog {
+        const NAME: &'static str = "TipImportDialog";
+        type Type = super::TipImportDialog;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TipImportDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.col_line.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().line.to_string().as_str());
+            })));
+            self.col_text.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().text.as_str());
+                label.set_xalign(0.0);
+            })));
+            self.col_tipper.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                label.set_label(row.borrow::<PreviewRow>().tipper.as_str());
+                label.set_xalign(0.0);
+            })));
+            // Anything that could not be matched is shown as an error
+            self.col_tip.set_factory(Some(&build_column_factory(|label: Label, row: &BoxedAnyObject| {
+                let row = row.borrow::<PreviewRow>();
+                label.set_label(row.tip.as_str());
+                label.set_xalign(0.0);
+                if row.resolved {
+                    label.remove_css_class("error");
+                } else {
+                    label.add_css_class("error");
+                }
+            })));
+
+            self.import_text.buffer().connect_changed(clone!(#[weak(rename_to = window)] self, move |_| {
+                window.invalidate();
+            }));
+            self.tipper_select.connect_selected_notify(clone!(#[weak(rename_to = window)] self, move |_| {
+                window.invalidate();
+            }));
+
+            self.btn_open.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                open_import_file(window.obj().upcast_ref(), "Import Tips", &window.import_text.buffer());
+            }));
+
+            self.btn_preview.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.preview();
+            }));
+
+            self.btn_cancel.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.obj().close();
+            }));
+
+            self.btn_ok.connect_clicked(clone!(#[weak(rename_to = window)] self, move |_button| {
+                window.save_tips();
+            }));
+
+            connect_escape(self.import_view.deref(), self.btn_cancel.deref());
+        }
+    }
+
+    impl WidgetImpl for TipImportDialog {}
+
+    impl WindowImpl for TipImportDialog {}
+}
+
+glib::wrapper! {
+    pub struct TipImportDialog(ObjectSubclass<imp::TipImportDialog>)
+        @extends gtk::Window, gtk::Widget,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl TipImportDialog {
+    pub fn new() -> Self {
+        glib::Object::new::<TipImportDialog>()
+    }
+}
+
+impl Default for TipImportDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
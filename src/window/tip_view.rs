@@ -0,0 +1,544 @@
+/*
+ * Copyright (c) 2025. Trevor Campbell and others.
+ *
+ * This file is part of KelpieTipping.
+ *
+ * KelpieTipping is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License,or
+ * (at your option) any later version.
+ *
+ * KelpieTipping is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with KelpieTipping; if not, write to the Free Software
+ * Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
+ *
+ * Contributors:
+ *      Trevor Campbell
+ *
+ */
+#![forbid(unsafe_code)]
+
+use gtk::{self, glib, prelude::*, subclass::prelude::*, CompositeTemplate};
+
+mod imp {
+    use super::*;
+    use crate::event;
+    use crate::event::{while_loading, Event};
+    use crate::model::game::{Game, Games};
+    use crate::model::round::{Round, Rounds};
+    use crate::model::team::{Team, Teams};
+    use crate::model::error::ModelError;
+    use crate::model::{competition, season};
+    use crate::model::repository::{connected_repository, repository};
+    use crate::model::tip::Tip;
+    use crate::model::tipper::{Tipper, Tippers};
+    use crate::util::date::{to_dmy, to_hm};
+    use crate::util::ladder::{positions_before, LadderPoints};
+    use crate::util::lockout::{check_tips, LateTips, Lockout};
+    use crate::util::scoring::{default_tips, DefaultTips};
+    use crate::window::tip_import_dialog::TipImportDialog;
+    use crate::window::util::{build_column_factory, show_error_dialog};
+    use adw::gdk::{Key, ModifierType};
+    use adw::glib::{clone, Propagation};
+    use chrono::{Local, Utc};
+    use glib::subclass::InitializingObject;
+    use gtk::{Button, DropDown, EventControllerKey, Grid, Label, PropagationPhase, SpinButton, ToggleButton};
+    use log::error;
+    use std::cell::{Cell, RefCell};
+    use std::collections::{HashMap, HashSet};
+
+    /// The widgets in a cell, for keeping the same one focused when moving between cells.
+    #[derive(Clone, Copy, PartialEq)]
+    enum Part {
+        Home,
+        Away,
+        Margin,
+    }
+
+    /// The widgets holding one tipper's tip for one game.
+    pub struct TipCell {
+        user_id: i32,
+        game_id: i32,
+        container: gtk::Box,
+        home: ToggleButton,
+        away: ToggleButton,
+        margin: SpinButton,
+        original: Option<(i32, i32)>,
+        /// The default tip shown when the tipper missed the round.
+        default: Option<(i32, i32)>,
+    }
+
+    impl TipCell {
+        fn new(user_id: i32, game_id: i32, home_name: &str, away_name: &str, tip: Option<&Tip>,
+               default_tip: Option<&Tip>) -> TipCell {
+            let container = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+            let teams = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+            teams.add_css_class("linked");
+
+            let home = ToggleButton::with_label(home_name);
+            let away = ToggleButton::with_label(away_name);
+            away.set_group(Some(&home));
+            let margin = SpinButton::with_range(1.0, 200.0, 1.0);
+            margin.set_tooltip_text(Some("Margin"));
+
+            let original = tip.map(|t| (t.predicted_home_score, t.predicted_away_score));
+            let default = default_tip.map(|t| (t.predicted_home_score, t.predicted_away_score));
+            if let Some((home_score, away_score)) = original.or(default) {
+                if home_score > away_score {
+                    home.set_active(true);
+                } else if away_score > home_score {
+                    away.set_active(true);
+                }
+                margin.set_value((home_score - away_score).abs().max(1) as f64);
+            }
+
+            teams.append(&home);
+            teams.append(&away);
+            container.append(&teams);
+            container.append(&margin);
+            if tip.is_some_and(|t| t.late) {
+                container.add_css_class("late-tip");
+                container.set_tooltip_text(Some("Entered after tips closed"));
+            } else if tip.map_or(default.is_some(), |t| t.auto) {
+                container.add_css_class("auto-tip");
+                container.set_tooltip_text(Some("Tipped automatically"));
+            }
+
+            TipCell { user_id, game_id, container, home, away, margin, original, default }
+        }
+
+        fn focused_part(&self) -> Part {
+            if self.home.has_focus() {
+                Part::Home
+            } else if self.away.has_focus() {
+                Part::Away
+            } else {
+                Part::Margin
+            }
+        }
+
+        fn focus(&self, part: Part) -> bool {
+            match part {
+                Part::Home => self.home.grab_focus(),
+                Part::Away => self.away.grab_focus(),
+                Part::Margin => self.margin.grab_focus(),
+            }
+        }
+
+        /// The predicted scores for this cell, or None if no team has been tipped.
+        fn predicted_scores(&self) -> Option<(i32, i32)> {
+            let margin = self.margin.value_as_int();
+            let scores = if self.home.is_active() {
+                (margin, 0)
+            } else if self.away.is_active() {
+                (0, margin)
+            } else {
+                return None;
+            };
+            // Keep any scores entered elsewhere if the tipped team and margin are unchanged
+            match self.original {
+                Some((home, away)) if (home - away) == (scores.0 - scores.1) => Some((home, away)),
+                _ => Some(scores),
+            }
+        }
+    }
+
+    #[derive(Default, CompositeTemplate)]
+    #[template(resource = "/com/shartrec/kelpie_tipping/tip_view.ui")]
+    pub struct TipView {
+        #[template_child]
+        pub round_select: TemplateChild<DropDown>,
+        #[template_child]
+        pub tip_grid: TemplateChild<Grid>,
+        #[template_child]
+        pub btn_save: TemplateChild<Button>,
+        #[template_child]
+        pub btn_revert: TemplateChild<Button>,
+        #[template_child]
+        pub btn_import: TemplateChild<Button>,
+
+        /// The cells a row at a time, one row for each tipper.
+        cells: RefCell<Vec<TipCell>>,
+        /// The number of games, and so of cells in each row.
+        columns: Cell<usize>,
+        /// The teams by id, to name the games.
+        teams: RefCell<HashMap<i32, Team>>,
+        updating: Cell<bool>,
+    }
+
+    impl TipView {
+        pub fn initialise(&self) {
+            if let Some(rx) = event::manager().register_listener() {
+                glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                    while let Ok(ev) = rx.recv().await {
+                        match ev {
+                            Event::RoundsChanged { .. } | Event::SeasonChanged => view.refresh(),
+                            Event::TeamsChanged | Event::TippersChanged | Event::CompetitionChanged |
+                            Event::GamesChanged | Event::TipsChanged { .. } | Event::PreferencesChanged => view.load_tips(),
+                            _ => {}
+                        }
+                    }
+                }));
+            }
+            self.refresh();
+        }
+
+        /// Reload the list of rounds, keeping the current round selected if it still exists.
+        fn refresh(&self) {
+            glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                let rounds = Rounds::loaded().await;
+                view.show_rounds(&rounds);
+            }));
+        }
+
+        fn show_rounds(&self, rounds: &Rounds) {
+            let selected_id = self.selected_round().map(|r| r.id());
+            let position = {
+                let list = rounds.imp().rounds.read().expect("Failed to read rounds");
+                match selected_id {
+                    Some(id) => list.iter().position(|r| r.id() == id),
+                    None => {
+                        let today = Local::now().date_naive();
+                        list.iter().rposition(|r| r.start_date() <= today)
+                    }
+                }.unwrap_or(0) as u32
+            };
+
+            self.updating.set(true);
+            self.round_select.set_model(Some(rounds));
+            self.round_select.set_selected(position);
+            self.updating.set(false);
+            self.load_tips();
+        }
+
+        fn selected_round(&self) -> Option<Round> {
+            self.round_select.selected_item().and_downcast::<Round>()
+        }
+
+        /// Rebuild the grid of tips for the selected round, one row per tipper and one column per game.
+        /// Everything is fetched before the old grid is cleared, so the window never blocks.
+        fn load_tips(&self) {
+            let Some(round) = self.selected_round() else {
+                self.clear_tips();
+                return;
+            };
+            glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                view.show_tips(&round).await;
+            }));
+        }
+
+        fn clear_tips(&self) {
+            while let Some(child) = self.tip_grid.first_child() {
+                self.tip_grid.remove(&child);
+            }
+            self.cells.borrow_mut().clear();
+        }
+
+        /// Default tips for the active tippers who have tipped nothing in a round that has started.
+        async fn default_tips(&self, round: &Round, games: &[Game], team_ids: &[i32], tippers: &[Tipper],
+                              tips: &[Tip]) -> Vec<Tip> {
+            let rule = DefaultTips::from_preferences();
+            let now = Utc::now();
+            let started = games.iter().any(|g| g.has_result() || g.kickoff().is_some_and(|k| k <= now));
+            if !started || matches!(rule, DefaultTips::None | DefaultTips::LowestScore) {
+                return vec![];
+            }
+
+            let mut positions = HashMap::new();
+            if rule == DefaultTips::LadderLeader && let (Some(repository), Some(season_id)) = (connected_repository(), season::current()) {
+                let results = while_loading(async move {
+                    let games = repository.get_games_for_season(season_id).await?;
+                    let byes = repository.get_byes_for_season(season_id).await?;
+                    Ok::<_, ModelError>((games, byes))
+                }).await;
+                match results {
+                    Ok((season_games, byes)) => {
+                        let earlier_rounds: HashSet<i32> = Rounds::loaded().await.imp().rounds.read().expect("Failed to read rounds")
+                            .iter()
+                            .filter(|r| r.number() < round.number())
+                            .map(|r| r.id())
+                            .collect();
+                        positions = positions_before(team_ids, &season_games, &byes, &earlier_rounds, &LadderPoints::from_preferences());
+                    }
+                    Err(err) => error!("Error loading the ladder for default tips: {}", err),
+                }
+            }
+
+            let tipper_ids: Vec<i32> = tippers.iter().filter(|t| t.is_active()).map(|t| t.id()).collect();
+            default_tips(rule, &tipper_ids, games, tips, &positions)
+        }
+
+        async fn show_tips(&self, round: &Round) {
+            let round_id = round.id();
+            let games = Games::for_round(round_id).await.imp().games.read().expect("Unable to get a lock on games").clone();
+            let teams: HashMap<i32, Team> = Teams::loaded_with_archived().await.imp().teams.read().expect("Unable to get a lock on teams")
+                .iter()
+                .map(|t| (t.id(), t.clone()))
+                .collect();
+            let tippers = Tippers::loaded_with_archived(competition::current()).await.imp().tippers.read().expect("Unable to get a lock on tippers").clone();
+            let tips = match connected_repository() {
+                Some(repository) => while_loading(repository.get_tips_for_round(round_id)).await,
+                None => Ok(vec![]),
+            };
+            let tips = match tips {
+                Ok(tips) => tips,
+                Err(err) => {
+                    error!("Error getting tips for round {}: {}", round_id, err);
+                    vec![]
+                }
+            };
+            let team_ids: Vec<i32> = teams.keys().copied().collect();
+            let defaults = self.default_tips(round, &games, &team_ids, &tippers, &tips).await;
+
+            // Another round may have been picked while this one was loading
+            if self.selected_round().map(|r| r.id()) != Some(round_id) {
+                return;
+            }
+            self.clear_tips();
+            if games.is_empty() {
+                let label = Label::new(Some("There are no games in this round"));
+                self.tip_grid.attach(&label, 0, 0, 1, 1);
+                return;
+            }
+            let tips: HashMap<(i32, i32), Tip> = tips.into_iter().map(|t| ((t.user_id, t.game_id), t)).collect();
+            let defaults: HashMap<(i32, i32), Tip> = defaults.into_iter().map(|t| ((t.user_id, t.game_id), t)).collect();
+            self.teams.replace(teams.clone());
+            self.columns.set(games.len());
+            // Archived tippers only appear in rounds they tipped in
+            let tippers: Vec<Tipper> = tippers.into_iter()
+                .filter(|t| t.is_active() || tips.keys().any(|(user_id, _)| *user_id == t.id()))
+                .collect();
+
+            let header = Label::new(Some("Tipper"));
+            header.set_xalign(0.0);
+            header.add_css_class("heading");
+            self.tip_grid.attach(&header, 0, 0, 1, 1);
+            for (col, game) in games.iter().enumerate() {
+                let label = Label::new(Some(format!("{} v {}",
+                    nickname(&teams, game.home_team_id()),
+                    nickname(&teams, game.away_team_id())).as_str()));
+                let when = match game.kickoff() {
+                    Some(kickoff) => format!("{} {}", to_dmy(&game.game_date()), to_hm(&kickoff)),
+                    None => to_dmy(&game.game_date()),
+                };
+                label.set_tooltip_text(Some(when.as_str()));
+                label.add_css_class("heading");
+                self.tip_grid.attach(&label, col as i32 + 1, 0, 1, 1);
+            }
+
+            for (row, tipper) in tippers.iter().enumerate() {
+                let name = Label::new(Some(tipper.name().as_str()));
+                name.set_xalign(0.0);
+                self.tip_grid.attach(&name, 0, row as i32 + 1, 1, 1);
+
+                for (col, game) in games.iter().enumerate() {
+                    let cell = TipCell::new(
+                        tipper.id(),
+                        game.id(),
+                        nickname(&teams, game.home_team_id()).as_str(),
+                        nickname(&teams, game.away_team_id()).as_str(),
+                        tips.get(&(tipper.id(), game.id())),
+                        defaults.get(&(tipper.id(), game.id())),
+                    );
+                    let index = self.cells.borrow().len();
+                    self.connect_cell_keys(&cell, index);
+                    self.tip_grid.attach(&cell.container, col as i32 + 1, row as i32 + 1, 1, 1);
+                    self.cells.borrow_mut().push(cell);
+                }
+            }
+        }
+
+        /// Typing "h" or "a" in a cell tips the home or away team and moves on to the next cell,
+        /// so a tipper's sheet can be keyed in without the mouse. Tab moves from the teams to the
+        /// margin and then on to the next cell, and the arrow keys move between cells; left and
+        /// right are left to the margin for editing its text.
+        fn connect_cell_keys(&self, cell: &TipCell, index: usize) {
+            let controller = EventControllerKey::new();
+            controller.set_propagation_phase(PropagationPhase::Capture);
+            controller.connect_key_pressed(clone!(#[weak(rename_to = view)] self,
+                #[upgrade_or] Propagation::Proceed,
+                move |_event, key_val, _key_code, modifier| {
+                    if modifier != ModifierType::empty() && modifier != ModifierType::SHIFT_MASK {
+                        return Propagation::Proceed;
+                    }
+                    let cells = view.cells.borrow();
+                    let Some(cell) = cells.get(index) else {
+                        return Propagation::Proceed;
+                    };
+                    let columns = view.columns.get().max(1);
+                    let part = cell.focused_part();
+                    let target = match key_val {
+                        Key::h | Key::H | Key::a | Key::A => {
+                            match key_val {
+                                Key::h | Key::H => cell.home.set_active(true),
+                                _ => cell.away.set_active(true),
+                            }
+                            if let Some(next) = cells.get(index + 1) {
+                                next.home.grab_focus();
+                            }
+                            return Propagation::Stop;
+                        }
+                        Key::Tab if part != Part::Margin => Some((index, Part::Margin)),
+                        Key::Tab => Some((index + 1, Part::Home)),
+                        Key::ISO_Left_Tab if part == Part::Margin => Some((index, Part::Home)),
+                        Key::ISO_Left_Tab => index.checked_sub(1).map(|i| (i, Part::Margin)),
+                        Key::Up => index.checked_sub(columns).map(|i| (i, part)),
+                        Key::Down => Some((index + columns, part)),
+                        Key::Left if part != Part::Margin && index % columns > 0 => Some((index - 1, part)),
+                        Key::Right if part != Part::Margin && index % columns + 1 < columns => Some((index + 1, part)),
+                        _ => None,
+                    };
+                    // Past the edge of the grid the key does what it normally would
+                    match target.and_then(|(target, part)| cells.get(target).map(|next| next.focus(part))) {
+                        Some(_) => Propagation::Stop,
+                        None => Propagation::Proceed,
+                    }
+                }));
+            cell.container.add_controller(controller);
+        }
+
+        fn save_tips(&self) {
+            let Some(round) = self.selected_round() else {
+                return;
+            };
+            let round_id = round.id();
+            // Only the tips that were changed are saved, so the others keep the time they were made.
+            // A default tip shown for a missed round is left to scoring unless it is changed.
+            let tips: Vec<Tip> = self.cells.borrow().iter()
+                .filter(|cell| cell.predicted_scores() != cell.original.or(cell.default))
+                .filter_map(|cell| {
+                    cell.predicted_scores().map(|(home, away)| Tip::new(-1, cell.user_id, cell.game_id, home, away, None))
+                })
+                .collect();
+
+            glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                let games = Games::for_round(round_id).await.imp().games.read().expect("Unable to get a lock on games").clone();
+                let competition = match competition::current() {
+                    Some(id) => repository().get_all_competitions().await
+                        .unwrap_or_default()
+                        .into_iter()
+                        .find(|c| c.id == id),
+                    None => None,
+                };
+                let (lockout, late_tips) = competition
+                    .map(|c| (c.lockout, c.late_tips))
+                    .unwrap_or((Lockout::None, LateTips::Flag));
+                let tips = match check_tips(tips, &games, lockout, late_tips, Utc::now()) {
+                    Ok(tips) => tips,
+                    Err(game_ids) => {
+                        let names: Vec<String> = games.iter()
+                            .filter(|g| game_ids.contains(&g.id()))
+                            .map(|g| view.game_name(g))
+                            .collect();
+                        let message = format!("Tips have closed for {}. No tips were saved.", names.join(", "));
+                        show_error_dialog(&view.obj().root(), message.as_str());
+                        return;
+                    }
+                };
+                match repository().save_tips(&tips).await {
+                    Ok(_) => {
+                        event::manager().notify_listeners(Event::TipsChanged { round_id });
+                    }
+                    Err(err) => {
+                        show_error_dialog(&view.obj().root(), err.to_string().as_str());
+                    }
+                }
+            }));
+        }
+
+        fn import_tips(&self) {
+            let Some(round) = self.selected_round() else {
+                return;
+            };
+            if let Some(window) = self.obj().root().and_downcast::<gtk::Window>() {
+                let import_dialog = TipImportDialog::new();
+                import_dialog.imp().set_round(round);
+                import_dialog.set_transient_for(Some(&window));
+                import_dialog.set_visible(true);
+            }
+        }
+
+        /// The teams playing in a game, by nickname.
+        fn game_name(&self, game: &Game) -> String {
+            let teams = self.teams.borrow();
+            format!("{} v {}", nickname(&teams, game.home_team_id()), nickname(&teams, game.away_team_id()))
+        }
+    }
+
+    fn nickname(teams: &HashMap<i32, Team>, team_id: i32) -> String {
+        teams.get(&team_id).map(|t| t.nickname()).unwrap_or_else(|| team_id.to_string())
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TipView {
+        const NAME: &'static str = "TipView";
+        type Type = super::TipView;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TipView {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.round_select.set_factory(Some(&build_column_factory(|label: Label, round: &Round| {
+                label.set_label(format!("{}", round.number()).as_str());
+                label.set_xalign(0.0);
+            })));
+
+            self.round_select.connect_selected_notify(clone!(#[weak(rename_to = view)] self, move |_drop| {
+                if !view.updating.get() {
+                    view.load_tips();
+                }
+            }));
+
+            self.btn_save.connect_clicked(clone!(#[weak(rename_to = view)] self, move |_button| {
+                view.save_tips();
+            }));
+
+            self.btn_revert.connect_clicked(clone!(#[weak(rename_to = view)] self, move |_button| {
+                view.load_tips();
+            }));
+
+            self.btn_import.connect_clicked(clone!(#[weak(rename_to = view)] self, move |_button| {
+                view.import_tips();
+            }));
+
+            self.initialise();
+        }
+    }
+
+    impl BoxImpl for TipView {}
+
+    impl WidgetImpl for TipView {}
+}
+
+glib::wrapper! {
+    pub struct TipView(ObjectSubclass<imp::TipView>)
+        @extends gtk::Widget, gtk::Box;
+}
+
+impl TipView {
+    pub fn new() -> Self {
+        glib::Object::new::<TipView>()
+    }
+}
+
+impl Default for TipView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -29,14 +29,17 @@ mod imp {
     use super::*;
     use crate::event;
     use crate::event::Event;
-    use crate::model::tipper;
+    use crate::model::{competition, season};
     use crate::model::tipper::{Tipper, Tippers};
-    use crate::util::db;
+    use crate::model::repository::{connected_repository, repository};
     use crate::window::edit_tipper::TipperDialog;
-    use crate::window::util::{build_column_factory, build_del_column_factory};
+    use crate::window::util::{build_check_column_factory, build_column_factory, build_del_column_factory, confirm_archive_or_delete, confirm_delete, show_error_dialog, DeleteChoice};
     use adw::glib::clone;
     use glib::subclass::InitializingObject;
-    use gtk::{Button, ColumnView, ColumnViewColumn, Label, ListItem, SignalListItemFactory, SingleSelection};
+    use gtk::{Button, CheckButton, ColumnView, ColumnViewColumn, Label, ListItem, SignalListItemFactory, SingleSelection};
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use std::rc::Rc;
 
     #[derive(Default, CompositeTemplate)]
     #[template(resource = "/com/shartrec/kelpie_tipping/tipper_view.ui")]
@@ -48,7 +51,18 @@ mod imp {
         #[template_child]
         pub col_email: TemplateChild<ColumnViewColumn>,
         #[template_child]
+        pub col_active: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub col_member: TemplateChild<ColumnViewColumn>,
+        #[template_child]
         pub col_delete: TemplateChild<ColumnViewColumn>,
+        #[template_child]
+        pub show_archived: TemplateChild<CheckButton>,
+        #[template_child]
+        pub show_all: TemplateChild<CheckButton>,
+
+        /// Ids of the tippers in the competition being shown.
+        members: Rc<RefCell<HashSet<i32>>>,
     }
 
     impl TipperView {
@@ -56,7 +70,7 @@ mod imp {
             if let Some(rx) = event::manager().register_listener() {
                 glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
                     while let Ok(ev) = rx.recv().await {
-                        if let Event::TippersChanged = ev {
+                        if let Event::TippersChanged | Event::CompetitionChanged | Event::SeasonChanged = ev {
                             view.refresh();
                         }
                     }
@@ -65,10 +79,28 @@ mod imp {
             self.refresh();
         }
 
+        fn tippers(&self) -> Option<Tippers> {
+            self.tipper_list.model().and_downcast_ref::<SingleSelection>()
+                .and_then(|selection| selection.model())
+                .and_downcast::<Tippers>()
+        }
+
+        /// Reload the tippers into the list already on screen, without blocking the window.
+        /// Only the tippers in the competition are shown, unless asked to show them all.
         fn refresh(&self) {
-            let selection_model = SingleSelection::new(Some(Tippers::new()));
-            self.tipper_list.set_model(Some(&selection_model));
-            self.tipper_list.queue_draw();
+            if let Some(tippers) = self.tippers() {
+                let competition_id = competition::current();
+                tippers.set_competition(if self.show_all.is_active() { None } else { competition_id });
+                self.col_member.set_visible(competition_id.is_some());
+                glib::spawn_future_local(clone!(#[weak(rename_to = view)] self, async move {
+                    let members = match (competition_id, connected_repository()) {
+                        (Some(id), Some(repository)) => repository.get_competition_members(id).await.unwrap_or_default(),
+                        _ => vec![],
+                    };
+                    view.members.replace(members.into_iter().collect());
+                    tippers.load().await;
+                }));
+            }
         }
 
         fn get_model_tipper(&self, sel_ap: u32) -> Option<Tipper> {
@@ -101,6 +133,7 @@ mod imp {
     impl ObjectImpl for TipperView {
         fn constructed(&self) {
             self.parent_constructed();
+            self.tipper_list.set_model(Some(&SingleSelection::new(Some(Tippers::new()))));
             self.initialise();
 
             self.tipper_list.connect_activate(
@@ -131,6 +164,27 @@ mod imp {
                 label.set_xalign(0.0);
             })));
 
+            self.col_active.set_factory(Some(&build_check_column_factory(
+                |tipper: &Tipper| tipper.is_active(),
+                set_tipper_active)));
+
+            let members = self.members.clone();
+            let is_member = self.members.clone();
+            self.col_member.set_factory(Some(&build_check_column_factory(
+                move |tipper: &Tipper| is_member.borrow().contains(&tipper.id()),
+                move |check: &CheckButton, tipper: &Tipper, member| set_tipper_member(check, tipper, member, &members))));
+
+            self.show_archived.connect_toggled(clone!(#[weak(rename_to = view)] self, move |check| {
+                if let Some(tippers) = view.tippers() {
+                    tippers.set_include_archived(check.is_active());
+                    view.refresh();
+                }
+            }));
+
+            self.show_all.connect_toggled(clone!(#[weak(rename_to = view)] self, move |_check| {
+                view.refresh();
+            }));
+
             // Create a factory for the delete button column
             let f = build_del_column_factory(
                 |button: &Button, tipper: &Tipper| button.set_action_target(Some(tipper.id())),
@@ -144,14 +198,89 @@ mod imp {
 
     impl WidgetImpl for TipperView {}
 
+    /// Archive or restore a tipper in the season being shown when the Active box is changed.
+    fn set_tipper_active(check: &CheckButton, tipper: &Tipper, active: bool) {
+        let Some(season_id) = season::current() else {
+            return;
+        };
+        let repository = repository();
+        glib::spawn_future_local(clone!(#[weak] check, #[strong] tipper, async move {
+            match repository.set_tipper_active(season_id, tipper.id(), active).await {
+                Ok(_) => {
+                    tipper.set_active(active);
+                    event::manager().notify_listeners(Event::TippersChanged);
+                }
+                Err(e) => {
+                    check.set_active(!active);
+                    show_error_dialog(&check.root(), format!("The tipper was not updated. {}", e).as_str());
+                }
+            }
+        }));
+    }
+
+    /// Put a tipper in the competition being shown, or take them out of it, when the Member box is changed.
+    fn set_tipper_member(check: &CheckButton, tipper: &Tipper, member: bool, members: &Rc<RefCell<HashSet<i32>>>) {
+        let Some(competition_id) = competition::current() else {
+            return;
+        };
+        let repository = repository();
+        let members = members.clone();
+        glib::spawn_future_local(clone!(#[weak] check, #[strong] tipper, async move {
+            match repository.set_competition_member(competition_id, tipper.id(), member).await {
+                Ok(_) => {
+                    if member {
+                        members.borrow_mut().insert(tipper.id());
+                    } else {
+                        members.borrow_mut().remove(&tipper.id());
+                    }
+                    event::manager().notify_listeners(Event::TippersChanged);
+                }
+                Err(e) => {
+                    check.set_active(!member);
+                    show_error_dialog(&check.root(), format!("The tipper was not updated. {}", e).as_str());
+                }
+            }
+        }));
+    }
+
     fn delete_tipper(button: &Button) {
+        let Some(season_id) = season::current() else {
+            return;
+        };
         if let Some(value) = button.action_target_value() {
             if let Some(id) = value.get::<i32>() {
-                let pool = db::manager().pool();
-                glib::spawn_future_local(clone!(async move {
-                let _ = tipper::delete(pool, id).await;
-                event::manager().notify_listeners(Event::TippersChanged);
-            }));
+                let repository = repository();
+                glib::spawn_future_local(clone!(#[weak] button, async move {
+                    let dependents = match repository.get_tipper_dependents(id).await {
+                        Ok(dependents) => dependents,
+                        Err(e) => {
+                            show_error_dialog(&button.root(), e.to_string().as_str());
+                            return;
+                        }
+                    };
+                    // With a history to lose, archiving is offered ahead of deleting
+                    let choice = if dependents.is_empty() {
+                        if confirm_delete(&button.root(), "Delete tipper?", "This tipper has no tips recorded.").await {
+                            DeleteChoice::Delete
+                        } else {
+                            DeleteChoice::Cancel
+                        }
+                    } else {
+                        let body = format!("This tipper has {}. Archive them to keep their history, or delete them along with their tips.",
+                                           dependents.describe());
+                        confirm_archive_or_delete(&button.root(), "Delete tipper?", &body).await
+                    };
+                    let result = match choice {
+                        DeleteChoice::Cancel => return,
+                        DeleteChoice::Archive => repository.set_tipper_active(season_id, id, false).await,
+                        DeleteChoice::Delete => repository.delete_tipper_cascade(id).await,
+                    };
+                    match result {
+                        Ok(0) if choice == DeleteChoice::Delete => show_error_dialog(&button.root(), "The tipper had already been deleted"),
+                        Ok(_) => event::manager().notify_listeners(Event::TippersChanged),
+                        Err(e) => show_error_dialog(&button.root(), format!("The tipper was not changed. {}", e).as_str()),
+                    }
+                }));
             }
         }
     }
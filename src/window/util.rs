@@ -21,20 +21,22 @@
  *      Trevor Campbell
  *
  */
+use std::rc::Rc;
 use std::sync::Arc;
 use crate::util;
 use crate::window::Window;
 use adw::gdk::{Key, ModifierType};
 use adw::glib;
-use adw::glib::{clone, Propagation};
-use adw::prelude::{AdwDialogExt, AlertDialogExt, Cast, CastNone, EditableExt, GtkWindowExt, IsA, ListItemExt, WidgetExt};
-use adw::AlertDialog;
+use adw::glib::{clone, Propagation, TimeZone};
+use adw::prelude::{AdwDialogExt, AlertDialogExt, AlertDialogExtManual, Cast, CastNone, EditableExt, GtkWindowExt, IsA, ListItemExt, WidgetExt};
+use adw::{AlertDialog, ResponseAppearance};
 use gettextrs::gettext;
 use gtk::gdk::Texture;
 use gtk::glib::Object;
-use gtk::prelude::{ActionableExtManual, ButtonExt};
-use gtk::{AboutDialog, Button, Entry, Label, ListItem, Root, SignalListItemFactory, Widget};
+use gtk::prelude::{ActionableExtManual, ButtonExt, CheckButtonExt, EntryExt, FileExtManual, PopoverExt, TextBufferExt};
+use gtk::{AboutDialog, Button, Calendar, CheckButton, Entry, Label, ListItem, Popover, Root, SignalListItemFactory, TextBuffer, Widget};
 use crate::model::game::Game;
+use chrono::{Datelike, NaiveDate};
 
 pub(crate) fn connect_escape<W: IsA<Widget> + adw::glib::clone::Downgrade>(widget: &W, button: &Button) {
     // Create the key controller
@@ -69,6 +71,78 @@ pub(crate) fn show_error_dialog(root: &Option<Root>, message: &str) {
     };
 }
 
+/// Ask for a CSV or text file to import and show its contents in `buffer`.
+pub(crate) fn open_import_file(window: &gtk::Window, title: &str, buffer: &TextBuffer) {
+    let dialog = gtk::FileDialog::builder()
+        .title(title)
+        .modal(true)
+        .build();
+    glib::spawn_future_local(clone!(#[weak] window, #[weak] buffer, async move {
+        let Ok(file) = dialog.open_future(Some(&window)).await else {
+            return;
+        };
+        match file.load_contents_future().await {
+            Ok((contents, _)) => buffer.set_text(&String::from_utf8_lossy(&contents)),
+            Err(err) => show_error_dialog(&window.root(), err.to_string().as_str()),
+        }
+    }));
+}
+
+/// Ask before deleting something, answering true only if the user chose to delete.
+pub(crate) async fn confirm_delete(root: &Option<Root>, heading: &str, body: &str) -> bool {
+    let Ok(w) = root
+        .as_ref()
+        .expect("Can't get the root window")
+        .clone()
+        .downcast::<gtk::Window>()
+    else {
+        return false;
+    };
+    let heading = gettext(heading);
+    let dialog = AlertDialog::new(Some(heading.as_str()), Some(body));
+    dialog.add_responses(&[("cancel", gettext("_Cancel").as_str()), ("delete", gettext("_Delete").as_str())]);
+    dialog.set_response_appearance("delete", ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog.choose_future(&w).await == "delete"
+}
+
+/// What the user chose to do with a record that other records depend on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum DeleteChoice {
+    Cancel,
+    Archive,
+    Delete,
+}
+
+/// Ask whether to archive a record, keeping its history, or delete it along with its history.
+pub(crate) async fn confirm_archive_or_delete(root: &Option<Root>, heading: &str, body: &str) -> DeleteChoice {
+    let Ok(w) = root
+        .as_ref()
+        .expect("Can't get the root window")
+        .clone()
+        .downcast::<gtk::Window>()
+    else {
+        return DeleteChoice::Cancel;
+    };
+    let heading = gettext(heading);
+    let dialog = AlertDialog::new(Some(heading.as_str()), Some(body));
+    dialog.add_responses(&[
+        ("cancel", gettext("_Cancel").as_str()),
+        ("archive", gettext("_Archive").as_str()),
+        ("delete", gettext("_Delete").as_str()),
+    ]);
+    dialog.set_response_appearance("archive", ResponseAppearance::Suggested);
+    dialog.set_response_appearance("delete", ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("archive"));
+    dialog.set_close_response("cancel");
+    match dialog.choose_future(&w).await.as_str() {
+        "archive" => DeleteChoice::Archive,
+        "delete" => DeleteChoice::Delete,
+        _ => DeleteChoice::Cancel,
+    }
+}
+
 pub(crate) fn build_column_factory<F: Fn(Label, &T) + 'static, T: IsA<Object>>(f: F) -> SignalListItemFactory {
     let factory = SignalListItemFactory::new();
     factory.connect_setup(move |_, list_item| {
@@ -143,6 +217,76 @@ pub(crate) fn build_del_column_factory<FGet: Fn(&Button, &T) + 'static, T: IsA<O
     f
 }
 
+/// A column of check buttons. `set` is only called when the user changes a check button, not when
+/// a row is bound.
+pub(crate) fn build_check_column_factory<FGet: Fn(&T) -> bool + 'static, FSet: Fn(&CheckButton, &T, bool) + 'static, T: IsA<Object>>(get: FGet, set: FSet) -> SignalListItemFactory {
+    let get = Rc::new(get);
+    let set = Rc::new(set);
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(clone!(#[strong] get, move |_, list_item| {
+        let list_item = list_item
+            .downcast_ref::<ListItem>()
+            .expect("Needs to be ListItem")
+            .clone();
+        let check = CheckButton::new();
+        check.set_halign(gtk::Align::Center);
+        check.connect_toggled(clone!(#[weak] list_item, #[strong] get, #[strong] set, move |check| {
+            if let Some(obj) = list_item.item().and_downcast::<T>()
+                && get(&obj) != check.is_active() {
+                set(check, &obj, check.is_active());
+            }
+        }));
+        list_item.set_child(Some(&check));
+    }));
+
+    factory.connect_bind(move |_f, list_item| {
+        let list_item = list_item
+            .downcast_ref::<ListItem>()
+            .expect("Needs to be ListItem");
+        let obj = list_item
+            .item()
+            .and_downcast::<T>()
+            .expect("The item has to be an <T>.");
+        let check = list_item
+            .child()
+            .and_downcast::<CheckButton>()
+            .expect("The child has to be a `CheckButton`.");
+        check.set_active(get(&obj));
+    });
+    factory
+}
+
+pub(crate) fn setup_date_selector(entry: &Entry, popover: &Popover, calendar: &Calendar) {
+    // Show the popover when the button is clicked
+    entry.connect_icon_press(clone!(#[weak] entry, #[weak] popover, #[weak] calendar, move |_, icon| {
+
+        // set the Calendar to the date in the entry, if it is a valid date
+        if icon == gtk::EntryIconPosition::Secondary {
+            let date_str = entry.text().to_string().clone();
+            if let Ok(date) = date_str.parse::<NaiveDate>() {
+                if let Ok(date) = glib::DateTime::new(&TimeZone::local(),
+                    date.year(),
+                    date.month() as i32,
+                    date.day() as i32,
+                    0i32, 0i32, 0f64)
+                {
+                    calendar.select_day(&date);
+                }
+            }
+            popover.popup();
+        }
+    }));
+
+    // Update the entry when a date is selected in the calendar
+    calendar.connect_day_selected(glib::clone!(#[weak] entry, #[weak] popover, move |calendar| {
+        let date = calendar.date();
+        let formatted_date = format!("{:04}-{:02}-{:02}", date.year(), date.month(), date.day_of_month());
+        entry.set_text(&formatted_date);
+
+        popover.popdown(); // Close the popover after selecting a date
+    }));
+}
+
 pub(crate) fn show_help_about(window: &Window) {
     let icon = Texture::from_resource(
         "/com/shartrec/kelpie_tipping/images/kelpiedog_120x120_transparent.png");